@@ -0,0 +1,245 @@
+//! Applies a unified diff to a workspace file, hunk by hunk, so a model
+//! that emits a diff (often a more natural output than a search/replace
+//! pair, especially for multi-hunk changes) doesn't need EditorEditTool's
+//! exact-substring matching instead.
+
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use serde_json::json;
+
+use crate::tool::{Tool, ToolDefinition, ToolResult};
+use crate::workspace::Workspace;
+
+#[derive(Debug)]
+struct Hunk {
+    /// 1-based starting line in the original file, from the `@@ -N,..`
+    /// header.
+    old_start: usize,
+    /// Lines that must already be present at `old_start` (context and
+    /// removed lines), which the hunk is checked against before it's
+    /// applied.
+    expected: Vec<String>,
+    /// What those lines become (context and added lines).
+    replacement: Vec<String>,
+}
+
+fn parse_hunks(patch: &str) -> Result<Vec<Hunk>, String> {
+    let mut hunks = Vec::new();
+    let mut lines = patch.lines().peekable();
+    while let Some(line) = lines.next() {
+        let Some(header) = line.strip_prefix("@@ -") else { continue };
+        let old_start: usize = header
+            .split([',', ' '])
+            .next()
+            .and_then(|n| n.parse().ok())
+            .ok_or_else(|| format!("malformed hunk header: {line}"))?;
+
+        let mut expected = Vec::new();
+        let mut replacement = Vec::new();
+        while let Some(&body_line) = lines.peek() {
+            if body_line.starts_with("@@ -") {
+                break;
+            }
+            lines.next();
+            let (marker, rest) = (body_line.get(..1).unwrap_or(""), body_line.get(1..).unwrap_or(""));
+            match marker {
+                " " => {
+                    expected.push(rest.to_string());
+                    replacement.push(rest.to_string());
+                }
+                "-" => expected.push(rest.to_string()),
+                "+" => replacement.push(rest.to_string()),
+                _ => {}
+            }
+        }
+        hunks.push(Hunk { old_start, expected, replacement });
+    }
+    if hunks.is_empty() {
+        return Err("no hunks found; expected unified diff '@@ -l,s +l,s @@' headers".to_string());
+    }
+    Ok(hunks)
+}
+
+/// Applies each hunk against `lines` in turn, tracking how much the file
+/// has grown or shrunk from earlier successful hunks so later hunks'
+/// line numbers still line up. A hunk whose expected context doesn't
+/// match at its (offset-adjusted) position is skipped, reported, and
+/// doesn't affect the offset for hunks after it.
+fn apply_hunks(lines: &mut Vec<String>, hunks: &[Hunk]) -> Vec<String> {
+    let mut reports = Vec::new();
+    let mut offset: isize = 0;
+    for (index, hunk) in hunks.iter().enumerate() {
+        let start = (hunk.old_start as isize - 1 + offset).max(0) as usize;
+        let end = start + hunk.expected.len();
+        if end > lines.len() || lines[start..end] != hunk.expected[..] {
+            reports.push(format!("hunk {}: context did not match at line {}", index + 1, hunk.old_start));
+            continue;
+        }
+        lines.splice(start..end, hunk.replacement.clone());
+        offset += hunk.replacement.len() as isize - hunk.expected.len() as isize;
+        reports.push(format!("hunk {}: applied", index + 1));
+    }
+    reports
+}
+
+pub struct ApplyPatchTool {
+    workspace: Arc<Workspace>,
+}
+
+impl ApplyPatchTool {
+    pub fn new(workspace: Arc<Workspace>) -> Self {
+        Self { workspace }
+    }
+}
+
+#[async_trait]
+impl Tool for ApplyPatchTool {
+    fn definition(&self) -> ToolDefinition {
+        ToolDefinition {
+            name: "apply_patch".to_string(),
+            description: "Apply a unified diff to a file. Hunks are validated and applied \
+                independently; a mismatched hunk is reported and skipped rather than \
+                failing the whole patch."
+                .to_string(),
+            parameters: json!({
+                "type": "object",
+                "properties": {
+                    "path": { "type": "string" },
+                    "patch": { "type": "string", "description": "Unified diff body (the @@ hunks; a leading ---/+++ file header line, if present, is ignored)." },
+                },
+                "required": ["path", "patch"],
+            }),
+        }
+    }
+
+    fn requires_approval(&self) -> bool {
+        true
+    }
+
+    async fn execute(&self, arguments: serde_json::Value) -> ToolResult {
+        let (Some(path), Some(patch)) = (
+            arguments.get("path").and_then(|v| v.as_str()),
+            arguments.get("patch").and_then(|v| v.as_str()),
+        ) else {
+            return ToolResult::error("apply_patch requires string arguments: path, patch");
+        };
+
+        let resolved = match self.workspace.resolve(path, true) {
+            Ok(resolved) => resolved,
+            Err(err) => return ToolResult::error(format!("cannot patch '{path}': {err}")),
+        };
+        let content = match tokio::fs::read_to_string(&resolved).await {
+            Ok(content) => content,
+            Err(err) => return ToolResult::error(format!("failed to read '{path}': {err}")),
+        };
+
+        let hunks = match parse_hunks(patch) {
+            Ok(hunks) => hunks,
+            Err(err) => return ToolResult::error(err),
+        };
+
+        let mut lines: Vec<String> = content.lines().map(str::to_string).collect();
+        let reports = apply_hunks(&mut lines, &hunks);
+        let any_applied = reports.iter().any(|r| r.ends_with("applied"));
+        let any_failed = reports.iter().any(|r| !r.ends_with("applied"));
+
+        if any_applied {
+            let mut updated = lines.join("\n");
+            if content.ends_with('\n') {
+                updated.push('\n');
+            }
+            if let Err(err) = tokio::fs::write(&resolved, updated).await {
+                return ToolResult::error(format!("failed to write '{path}': {err}"));
+            }
+        }
+
+        let summary = reports.join("\n");
+        if any_failed {
+            ToolResult::error(summary)
+        } else {
+            ToolResult::ok(summary)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_workspace() -> (tempfile::TempDir, Arc<Workspace>) {
+        let dir = tempfile::tempdir().expect("create temp dir");
+        let workspace = Arc::new(Workspace::new(dir.path()).expect("workspace root should be valid"));
+        (dir, workspace)
+    }
+
+    const PATCH: &str = "@@ -1,3 +1,3 @@\n line one\n-line two\n+line TWO\n line three\n";
+
+    #[test]
+    fn test_parse_hunks_splits_context_removed_and_added_lines() {
+        let hunks = parse_hunks(PATCH).expect("valid patch should parse");
+        assert_eq!(hunks.len(), 1);
+        assert_eq!(hunks[0].old_start, 1);
+        assert_eq!(hunks[0].expected, vec!["line one", "line two", "line three"]);
+        assert_eq!(hunks[0].replacement, vec!["line one", "line TWO", "line three"]);
+    }
+
+    #[test]
+    fn test_parse_hunks_rejects_a_patch_with_no_hunk_headers() {
+        let err = parse_hunks("just some text\nno @@ header here\n").expect_err("should reject a patch with no hunks");
+        assert!(err.contains("no hunks found"));
+    }
+
+    #[test]
+    fn test_apply_hunks_reports_a_mismatch_without_touching_the_file() {
+        let mut lines = vec!["completely".to_string(), "different".to_string(), "content".to_string()];
+        let hunks = parse_hunks(PATCH).expect("valid patch should parse");
+        let reports = apply_hunks(&mut lines, &hunks);
+        assert!(reports[0].contains("context did not match"));
+        assert_eq!(lines, vec!["completely", "different", "content"]);
+    }
+
+    #[test]
+    fn test_apply_hunks_adjusts_later_hunk_offsets_after_a_line_count_change() {
+        let mut lines = vec!["a".to_string(), "b".to_string(), "c".to_string(), "d".to_string()];
+        let hunks = vec![
+            Hunk { old_start: 1, expected: vec!["a".to_string()], replacement: vec!["a".to_string(), "a2".to_string()] },
+            Hunk { old_start: 3, expected: vec!["c".to_string()], replacement: vec!["C".to_string()] },
+        ];
+        let reports = apply_hunks(&mut lines, &hunks);
+        assert!(reports.iter().all(|r| r.ends_with("applied")));
+        assert_eq!(lines, vec!["a", "a2", "b", "C", "d"]);
+    }
+
+    #[tokio::test]
+    async fn test_execute_applies_a_valid_patch_and_writes_the_file() {
+        let (dir, workspace) = test_workspace();
+        std::fs::write(dir.path().join("f.txt"), "line one\nline two\nline three\n").expect("write file");
+        let tool = ApplyPatchTool::new(workspace);
+
+        let result = tool.execute(json!({ "path": "f.txt", "patch": PATCH })).await;
+        assert!(!result.is_error);
+        let updated = std::fs::read_to_string(dir.path().join("f.txt")).expect("read back file");
+        assert_eq!(updated, "line one\nline TWO\nline three\n");
+    }
+
+    #[tokio::test]
+    async fn test_execute_errors_and_leaves_the_file_untouched_on_a_mismatched_hunk() {
+        let (dir, workspace) = test_workspace();
+        std::fs::write(dir.path().join("f.txt"), "totally different content\n").expect("write file");
+        let tool = ApplyPatchTool::new(workspace);
+
+        let result = tool.execute(json!({ "path": "f.txt", "patch": PATCH })).await;
+        assert!(result.is_error);
+        let unchanged = std::fs::read_to_string(dir.path().join("f.txt")).expect("read back file");
+        assert_eq!(unchanged, "totally different content\n");
+    }
+
+    #[tokio::test]
+    async fn test_execute_requires_path_and_patch_arguments() {
+        let (_dir, workspace) = test_workspace();
+        let tool = ApplyPatchTool::new(workspace);
+        let result = tool.execute(json!({ "path": "f.txt" })).await;
+        assert!(result.is_error);
+    }
+}
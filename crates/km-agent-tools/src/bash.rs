@@ -0,0 +1,411 @@
+//! Shell command execution, plus background job management for commands
+//! that outlive a single tool call (dev servers, watchers, long builds)
+//! that the agent needs to start once and then poll or stop later.
+//!
+//! Split into three tools rather than one with a `action` field, so each
+//! has its own small, unambiguous argument schema: [`BashTool`] starts a
+//! command (optionally in the background), [`BashOutputTool`] drains a
+//! background job's buffered output, and [`KillBashTool`] stops one.
+
+use std::collections::HashMap;
+use std::process::Stdio;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use serde_json::json;
+use tokio::io::AsyncReadExt;
+use tokio::process::Child;
+use tokio::sync::Mutex;
+
+use crate::tool::{Tool, ToolDefinition, ToolResult};
+
+/// Runs `command` under a pseudo-terminal instead of a plain pipe, for
+/// commands that behave differently without one (colorized output,
+/// interactive prompts, pagers git invokes by default). `portable-pty`'s
+/// API is synchronous, so this runs on a blocking thread rather than
+/// tying up the async runtime for the command's whole lifetime.
+///
+/// Background mode isn't supported here: a pty's output has to be read
+/// continuously or the child can block writing to a full buffer, which
+/// doesn't fit the "fire and poll later" model `BashOutputTool` offers
+/// for piped background jobs.
+fn run_in_pty(command: &str) -> Result<String, String> {
+    let pty_system = portable_pty::native_pty_system();
+    let pair = pty_system
+        .openpty(portable_pty::PtySize { rows: 24, cols: 80, pixel_width: 0, pixel_height: 0 })
+        .map_err(|err| err.to_string())?;
+
+    let mut cmd = portable_pty::CommandBuilder::new("sh");
+    cmd.arg("-c");
+    cmd.arg(command);
+    let mut child = pair.slave.spawn_command(cmd).map_err(|err| err.to_string())?;
+    drop(pair.slave);
+
+    let mut reader = pair.master.try_clone_reader().map_err(|err| err.to_string())?;
+    let mut output = Vec::new();
+    std::io::Read::read_to_end(&mut reader, &mut output).map_err(|err| err.to_string())?;
+    child.wait().map_err(|err| err.to_string())?;
+    Ok(String::from_utf8_lossy(&output).into_owned())
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum JobStatus {
+    Running,
+    Exited(i32),
+    Killed,
+}
+
+struct BackgroundJob {
+    command: String,
+    output: Arc<Mutex<String>>,
+    status: Arc<Mutex<JobStatus>>,
+    child: Arc<Mutex<Option<Child>>>,
+}
+
+/// Tracks background jobs shared across [`BashTool`], [`BashOutputTool`],
+/// and [`KillBashTool`] — the three are only useful registered together,
+/// pointed at the same registry.
+#[derive(Default, Clone)]
+pub struct JobRegistry {
+    jobs: Arc<Mutex<HashMap<String, BackgroundJob>>>,
+    next_id: Arc<AtomicU64>,
+}
+
+impl JobRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn allocate_id(&self) -> String {
+        format!("bash-{}", self.next_id.fetch_add(1, Ordering::Relaxed))
+    }
+}
+
+/// Runs a shell command via `sh -c`. With `run_in_background: true`,
+/// spawns it and returns a job id immediately instead of waiting for it
+/// to exit; use [`BashOutputTool`]/[`KillBashTool`] to follow up.
+///
+/// Implements [`Tool`] the same way every other built-in does — there's
+/// no separate `ToolProvider` trait or brief/full description split
+/// anywhere in this crate, and no built-in tool lazy-loads its
+/// definition, so adding one just for `BashTool` would make it the odd
+/// one out rather than consistent with `EditorEditTool` and the rest.
+/// `definition()`'s description carries the detail a caller needs,
+/// including the OS-specific caveat that this tool shells out to `sh`
+/// and assumes a POSIX-compatible shell is on PATH.
+pub struct BashTool {
+    jobs: JobRegistry,
+}
+
+impl BashTool {
+    pub fn new(jobs: JobRegistry) -> Self {
+        Self { jobs }
+    }
+}
+
+#[async_trait]
+impl Tool for BashTool {
+    fn definition(&self) -> ToolDefinition {
+        ToolDefinition {
+            name: "bash".to_string(),
+            description: "Run a shell command via `sh -c`. Requires a POSIX-compatible shell \
+                on PATH — this will not work unmodified on a bare Windows host without WSL, \
+                Git Bash, or similar. Set run_in_background to start a long-lived process \
+                (e.g. a dev server) and get a job id back immediately instead of blocking \
+                until it exits. Set pty to run it attached to a pseudo-terminal instead of a \
+                plain pipe, for commands that behave differently without one (colors, \
+                prompts, pagers) — not combinable with run_in_background."
+                .to_string(),
+            parameters: json!({
+                "type": "object",
+                "properties": {
+                    "command": { "type": "string", "description": "Command to run via `sh -c`." },
+                    "run_in_background": { "type": "boolean", "default": false },
+                    "pty": { "type": "boolean", "default": false },
+                },
+                "required": ["command"],
+            }),
+        }
+    }
+
+    fn requires_approval(&self) -> bool {
+        true
+    }
+
+    async fn execute(&self, arguments: serde_json::Value) -> ToolResult {
+        let Some(command) = arguments.get("command").and_then(|v| v.as_str()) else {
+            return ToolResult::error("bash requires a string 'command' argument");
+        };
+        let run_in_background = arguments.get("run_in_background").and_then(|v| v.as_bool()).unwrap_or(false);
+        let pty = arguments.get("pty").and_then(|v| v.as_bool()).unwrap_or(false);
+
+        if pty {
+            if run_in_background {
+                return ToolResult::error("pty and run_in_background can't be combined");
+            }
+            let command = command.to_string();
+            return match tokio::task::spawn_blocking(move || run_in_pty(&command)).await {
+                Ok(Ok(output)) => ToolResult::ok(output),
+                Ok(Err(err)) => ToolResult::error(format!("pty command failed: {err}")),
+                Err(err) => ToolResult::error(format!("pty task panicked: {err}")),
+            };
+        }
+
+        if !run_in_background {
+            return match tokio::process::Command::new("sh").arg("-c").arg(command).output().await {
+                Ok(output) => {
+                    let mut content = String::from_utf8_lossy(&output.stdout).into_owned();
+                    content.push_str(&String::from_utf8_lossy(&output.stderr));
+                    if output.status.success() {
+                        ToolResult::ok(content)
+                    } else {
+                        ToolResult::error(format!("exit status {}: {content}", output.status))
+                    }
+                }
+                Err(err) => ToolResult::error(format!("failed to run command: {err}")),
+            };
+        }
+
+        let mut child = match tokio::process::Command::new("sh")
+            .arg("-c")
+            .arg(command)
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+        {
+            Ok(child) => child,
+            Err(err) => return ToolResult::error(format!("failed to spawn command: {err}")),
+        };
+
+        let job_id = self.jobs.allocate_id();
+        let output = Arc::new(Mutex::new(String::new()));
+        let status = Arc::new(Mutex::new(JobStatus::Running));
+        let mut stdout = child.stdout.take();
+        let mut stderr = child.stderr.take();
+
+        let reader_output = output.clone();
+        tokio::spawn(async move {
+            let mut buf = Vec::new();
+            if let Some(stdout) = stdout.as_mut() {
+                let _ = stdout.read_to_end(&mut buf).await;
+            }
+            if let Some(stderr) = stderr.as_mut() {
+                let _ = stderr.read_to_end(&mut buf).await;
+            }
+            reader_output.lock().await.push_str(&String::from_utf8_lossy(&buf));
+        });
+
+        let child = Arc::new(Mutex::new(Some(child)));
+        let wait_child = child.clone();
+        let wait_status = status.clone();
+        tokio::spawn(async move {
+            let exit = {
+                let mut guard = wait_child.lock().await;
+                match guard.as_mut() {
+                    Some(child) => child.wait().await.ok(),
+                    None => None,
+                }
+            };
+            let mut status = wait_status.lock().await;
+            if *status == JobStatus::Running {
+                *status = match exit {
+                    Some(exit) => JobStatus::Exited(exit.code().unwrap_or(-1)),
+                    None => JobStatus::Exited(-1),
+                };
+            }
+        });
+
+        self.jobs.jobs.lock().await.insert(
+            job_id.clone(),
+            BackgroundJob { command: command.to_string(), output, status, child },
+        );
+        ToolResult::ok(format!("started background job {job_id}"))
+    }
+}
+
+/// Returns a background job's output accumulated since the last call
+/// (the buffer is drained on read), plus whether it's still running.
+pub struct BashOutputTool {
+    jobs: JobRegistry,
+}
+
+impl BashOutputTool {
+    pub fn new(jobs: JobRegistry) -> Self {
+        Self { jobs }
+    }
+}
+
+#[async_trait]
+impl Tool for BashOutputTool {
+    fn definition(&self) -> ToolDefinition {
+        ToolDefinition {
+            name: "bash_output".to_string(),
+            description: "Fetch buffered output from a background job started by bash, \
+                and whether it's still running. Output already returned is not repeated."
+                .to_string(),
+            parameters: json!({
+                "type": "object",
+                "properties": { "job_id": { "type": "string" } },
+                "required": ["job_id"],
+            }),
+        }
+    }
+
+    async fn execute(&self, arguments: serde_json::Value) -> ToolResult {
+        let Some(job_id) = arguments.get("job_id").and_then(|v| v.as_str()) else {
+            return ToolResult::error("bash_output requires a string 'job_id' argument");
+        };
+        let jobs = self.jobs.jobs.lock().await;
+        let Some(job) = jobs.get(job_id) else {
+            return ToolResult::error(format!("unknown job: {job_id}"));
+        };
+        let drained = std::mem::take(&mut *job.output.lock().await);
+        let status = *job.status.lock().await;
+        ToolResult::ok(format!("status: {status:?}\n{drained}"))
+    }
+}
+
+/// Kills a running background job. A no-op (not an error) if the job has
+/// already exited on its own.
+pub struct KillBashTool {
+    jobs: JobRegistry,
+}
+
+impl KillBashTool {
+    pub fn new(jobs: JobRegistry) -> Self {
+        Self { jobs }
+    }
+}
+
+#[async_trait]
+impl Tool for KillBashTool {
+    fn definition(&self) -> ToolDefinition {
+        ToolDefinition {
+            name: "kill_bash".to_string(),
+            description: "Kill a background job started by bash.".to_string(),
+            parameters: json!({
+                "type": "object",
+                "properties": { "job_id": { "type": "string" } },
+                "required": ["job_id"],
+            }),
+        }
+    }
+
+    fn requires_approval(&self) -> bool {
+        true
+    }
+
+    async fn execute(&self, arguments: serde_json::Value) -> ToolResult {
+        let Some(job_id) = arguments.get("job_id").and_then(|v| v.as_str()) else {
+            return ToolResult::error("kill_bash requires a string 'job_id' argument");
+        };
+        let jobs = self.jobs.jobs.lock().await;
+        let Some(job) = jobs.get(job_id) else {
+            return ToolResult::error(format!("unknown job: {job_id}"));
+        };
+        if *job.status.lock().await != JobStatus::Running {
+            return ToolResult::ok(format!("job {job_id} ({}) already finished", job.command));
+        }
+        let mut child = job.child.lock().await;
+        match child.as_mut() {
+            Some(child) => match child.kill().await {
+                Ok(()) => {
+                    *job.status.lock().await = JobStatus::Killed;
+                    ToolResult::ok(format!("killed job {job_id}"))
+                }
+                Err(err) => ToolResult::error(format!("failed to kill job {job_id}: {err}")),
+            },
+            None => ToolResult::ok(format!("job {job_id} already reaped")),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_execute_runs_a_command_and_captures_stdout() {
+        let tool = BashTool::new(JobRegistry::new());
+        let result = tool.execute(json!({ "command": "echo hello" })).await;
+        assert!(!result.is_error);
+        assert_eq!(result.content, "hello\n");
+    }
+
+    #[tokio::test]
+    async fn test_execute_reports_a_nonzero_exit_status_as_an_error() {
+        let tool = BashTool::new(JobRegistry::new());
+        let result = tool.execute(json!({ "command": "exit 7" })).await;
+        assert!(result.is_error);
+        assert!(result.content.contains("exit status"));
+    }
+
+    #[tokio::test]
+    async fn test_execute_requires_the_command_argument() {
+        let tool = BashTool::new(JobRegistry::new());
+        let result = tool.execute(json!({})).await;
+        assert!(result.is_error);
+    }
+
+    #[tokio::test]
+    async fn test_execute_rejects_pty_combined_with_run_in_background() {
+        let tool = BashTool::new(JobRegistry::new());
+        let result = tool.execute(json!({ "command": "echo hi", "pty": true, "run_in_background": true })).await;
+        assert!(result.is_error);
+        assert!(result.content.contains("can't be combined"));
+    }
+
+    #[tokio::test]
+    async fn test_background_job_lifecycle_runs_and_is_drained() {
+        let jobs = JobRegistry::new();
+        let bash = BashTool::new(jobs.clone());
+        let output_tool = BashOutputTool::new(jobs.clone());
+
+        let started = bash.execute(json!({ "command": "echo background-output", "run_in_background": true })).await;
+        assert!(!started.is_error);
+        let job_id = started.content.strip_prefix("started background job ").expect("job id in message").to_string();
+
+        let mut drained = String::new();
+        for _ in 0..50 {
+            let polled = output_tool.execute(json!({ "job_id": job_id })).await;
+            assert!(!polled.is_error);
+            drained.push_str(&polled.content);
+            if drained.contains("background-output") {
+                break;
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+        }
+        assert!(drained.contains("background-output"));
+    }
+
+    #[tokio::test]
+    async fn test_bash_output_errors_for_an_unknown_job_id() {
+        let output_tool = BashOutputTool::new(JobRegistry::new());
+        let result = output_tool.execute(json!({ "job_id": "no-such-job" })).await;
+        assert!(result.is_error);
+        assert!(result.content.contains("unknown job"));
+    }
+
+    #[tokio::test]
+    async fn test_kill_bash_stops_a_running_background_job() {
+        let jobs = JobRegistry::new();
+        let bash = BashTool::new(jobs.clone());
+        let kill_tool = KillBashTool::new(jobs.clone());
+
+        let started = bash.execute(json!({ "command": "sleep 60", "run_in_background": true })).await;
+        let job_id = started.content.strip_prefix("started background job ").expect("job id in message").to_string();
+
+        let killed = kill_tool.execute(json!({ "job_id": job_id })).await;
+        assert!(!killed.is_error);
+        assert!(killed.content.contains("killed job"));
+    }
+
+    #[tokio::test]
+    async fn test_kill_bash_errors_for_an_unknown_job_id() {
+        let kill_tool = KillBashTool::new(JobRegistry::new());
+        let result = kill_tool.execute(json!({ "job_id": "no-such-job" })).await;
+        assert!(result.is_error);
+        assert!(result.content.contains("unknown job"));
+    }
+}
@@ -0,0 +1,82 @@
+//! Recursive directory listing, consistent across OSes instead of each
+//! model parsing whatever flavor of `ls -R` its shell happens to have.
+//!
+//! Built on the same `ignore::WalkBuilder` [`crate::grep::GrepTool`]
+//! already uses rather than a separate walker crate (e.g. `jwalk`) or a
+//! dedicated `km-tools` crate — this workspace doesn't have either, and
+//! `ignore` already gives gitignore handling and depth limits for free.
+
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use ignore::WalkBuilder;
+use serde_json::json;
+
+use crate::tool::{Tool, ToolDefinition, ToolResult};
+use crate::workspace::Workspace;
+
+const DEFAULT_MAX_DEPTH: usize = 4;
+const MAX_ENTRIES: usize = 2000;
+
+pub struct ListDirTool {
+    workspace: Arc<Workspace>,
+}
+
+impl ListDirTool {
+    pub fn new(workspace: Arc<Workspace>) -> Self {
+        Self { workspace }
+    }
+}
+
+#[async_trait]
+impl Tool for ListDirTool {
+    fn definition(&self) -> ToolDefinition {
+        ToolDefinition {
+            name: "list_dir".to_string(),
+            description: "List files and directories under a path, respecting .gitignore, \
+                up to a maximum depth."
+                .to_string(),
+            parameters: json!({
+                "type": "object",
+                "properties": {
+                    "path": { "type": "string", "description": "Directory to list, relative to the workspace root. Defaults to the root." },
+                    "max_depth": { "type": "integer", "minimum": 1, "default": DEFAULT_MAX_DEPTH },
+                },
+            }),
+        }
+    }
+
+    async fn execute(&self, arguments: serde_json::Value) -> ToolResult {
+        let path = arguments.get("path").and_then(|v| v.as_str()).unwrap_or(".");
+        let max_depth =
+            arguments.get("max_depth").and_then(|v| v.as_u64()).unwrap_or(DEFAULT_MAX_DEPTH as u64) as usize;
+
+        let root = match self.workspace.resolve(path, true) {
+            Ok(resolved) => resolved,
+            Err(err) => return ToolResult::error(format!("cannot list '{path}': {err}")),
+        };
+        let workspace_root = self.workspace.root().to_path_buf();
+
+        let mut output = String::new();
+        let mut count = 0usize;
+        let mut truncated = false;
+        for entry in WalkBuilder::new(&root).max_depth(Some(max_depth)).build() {
+            if count >= MAX_ENTRIES {
+                truncated = true;
+                break;
+            }
+            let Ok(entry) = entry else { continue };
+            if entry.path() == root {
+                continue;
+            }
+            let label = entry.path().strip_prefix(&workspace_root).unwrap_or(entry.path()).display();
+            let suffix = if entry.file_type().is_some_and(|t| t.is_dir()) { "/" } else { "" };
+            output.push_str(&format!("{label}{suffix}\n"));
+            count += 1;
+        }
+        if truncated {
+            output.push_str(&format!("\n[... truncated at {MAX_ENTRIES} entries]\n"));
+        }
+        ToolResult::ok(output)
+    }
+}
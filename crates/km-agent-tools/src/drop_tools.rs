@@ -0,0 +1,63 @@
+//! A meta-tool for shedding tool schemas a long session is done with.
+//! Every registered tool's schema is sent with every request by
+//! default; `drop_tools` lets the model opt specific ones back out
+//! without losing the ability to call them again later — it flips
+//! [`ActiveToolSet`] state, nothing more.
+
+use async_trait::async_trait;
+use serde_json::json;
+
+use crate::registry::ActiveToolSet;
+use crate::tool::{Tool, ToolDefinition, ToolResult};
+
+pub struct DropToolsTool {
+    active: ActiveToolSet,
+}
+
+impl DropToolsTool {
+    /// `active` should come from the same [`crate::registry::ToolRegistry`]
+    /// this tool is registered on, via `ToolRegistry::active_tools()`.
+    pub fn new(active: ActiveToolSet) -> Self {
+        Self { active }
+    }
+}
+
+#[async_trait]
+impl Tool for DropToolsTool {
+    fn definition(&self) -> ToolDefinition {
+        ToolDefinition {
+            name: "drop_tools".to_string(),
+            description: "Stop advertising the given tool names in future requests, to free up \
+                context once you're done with them for this session. Dropped tools stay \
+                registered and callable by name; only their schema is left out."
+                .to_string(),
+            parameters: json!({
+                "type": "object",
+                "properties": {
+                    "names": {
+                        "type": "array",
+                        "items": { "type": "string" },
+                        "description": "Tool names to drop, e.g. ['web_fetch', 'grep'].",
+                    },
+                },
+                "required": ["names"],
+            }),
+        }
+    }
+
+    async fn execute(&self, arguments: serde_json::Value) -> ToolResult {
+        let Some(names) = arguments.get("names").and_then(|v| v.as_array()) else {
+            return ToolResult::error("missing required field: names");
+        };
+        let mut dropped = Vec::new();
+        for name in names {
+            let Some(name) = name.as_str() else {
+                return ToolResult::error("names must be strings");
+            };
+            if self.active.unpick(name) {
+                dropped.push(name.to_string());
+            }
+        }
+        ToolResult::ok(format!("dropped {} tool(s): {}", dropped.len(), dropped.join(", ")))
+    }
+}
@@ -0,0 +1,754 @@
+//! Built-in tool for modifying existing files via exact string
+//! replacement, the same shape as the file-editing tool most coding
+//! agents expose: the model supplies the old text and its replacement
+//! rather than a line-numbered patch, which survives small drift in the
+//! file better than positional edits do.
+//!
+//! Files are read and written as raw UTF-8 strings rather than through
+//! any line-ending or BOM normalization, so a leading BOM is carried
+//! through untouched. CRLF files are the one place that isn't naturally
+//! preserved: `str::lines()` (used throughout for matching/splicing)
+//! strips the `\r`, so matching and writing both go through [`Eol`]
+//! normalization below to put it back. Writes go through [`write_atomic`]
+//! so a crash or kill mid-write leaves the original file intact instead
+//! of a truncated one.
+
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use serde_json::json;
+use tokio::sync::Mutex;
+
+use crate::tool::{Tool, ToolDefinition, ToolResult};
+use crate::workspace::Workspace;
+
+/// How many bytes at the start of a file are checked for a NUL byte to
+/// decide whether it's binary, rather than scanning the whole thing —
+/// same heuristic and threshold as [`crate::file::ReadFileTool`].
+const BINARY_SNIFF_BYTES: usize = 8000;
+
+/// One undoable write: the file and what it looked like immediately
+/// before this tool overwrote it.
+struct JournalEntry {
+    path: PathBuf,
+    previous_content: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Eol {
+    Lf,
+    CrLf,
+}
+
+/// `\r\n` only counts as the file's line ending if every line break in
+/// it is `\r\n` — a file with even one bare `\n` is treated as LF to
+/// avoid guessing wrong on a mixed file.
+fn detect_eol(content: &str) -> Eol {
+    let crlf_count = content.matches("\r\n").count();
+    let lf_count = content.matches('\n').count();
+    if crlf_count > 0 && crlf_count == lf_count {
+        Eol::CrLf
+    } else {
+        Eol::Lf
+    }
+}
+
+fn normalize_to_lf(content: &str) -> String {
+    if content.contains("\r\n") {
+        content.replace("\r\n", "\n")
+    } else {
+        content.to_string()
+    }
+}
+
+fn restore_eol(content: &str, eol: Eol) -> String {
+    match eol {
+        Eol::Lf => content.to_string(),
+        Eol::CrLf => content.replace('\n', "\r\n"),
+    }
+}
+
+/// Reads `resolved` as UTF-8 text, refusing files that look binary (a
+/// NUL byte in the first [`BINARY_SNIFF_BYTES`]) or aren't valid UTF-8 —
+/// editing either as text would corrupt them on write.
+async fn read_text_file(path: &str, resolved: &Path) -> Result<String, String> {
+    let bytes = tokio::fs::read(resolved).await.map_err(|err| format!("failed to read '{path}': {err}"))?;
+    if bytes[..bytes.len().min(BINARY_SNIFF_BYTES)].contains(&0) {
+        return Err(format!("'{path}' looks like a binary file, refusing to edit as text"));
+    }
+    String::from_utf8(bytes).map_err(|_| format!("'{path}' is not valid UTF-8 text, refusing to edit as text"))
+}
+
+/// Unique temp-file counter for [`write_atomic`], so concurrent writes to
+/// different files (or even the same file, racing) never collide on the
+/// same temp path.
+static TMP_COUNTER: AtomicU64 = AtomicU64::new(0);
+
+/// Writes `content` to `path` via write-to-temp-then-rename in the same
+/// directory, so a crash or kill mid-write can never leave `path`
+/// truncated — readers either see the old file or the new one, never a
+/// partial one.
+async fn write_atomic(path: &Path, content: &str) -> Result<(), String> {
+    let file_name = path.file_name().and_then(|name| name.to_str()).unwrap_or("tmp");
+    let unique = TMP_COUNTER.fetch_add(1, Ordering::Relaxed);
+    let tmp_path = path.with_file_name(format!(".{file_name}.tmp{}-{unique}", std::process::id()));
+
+    if let Err(err) = tokio::fs::write(&tmp_path, content).await {
+        let _ = tokio::fs::remove_file(&tmp_path).await;
+        return Err(err.to_string());
+    }
+    if let Err(err) = tokio::fs::rename(&tmp_path, path).await {
+        let _ = tokio::fs::remove_file(&tmp_path).await;
+        return Err(err.to_string());
+    }
+    Ok(())
+}
+
+/// Edits one or more existing files per call (`edit_batch` applies several
+/// edits as one all-or-nothing unit). Sandboxed to a [`Workspace`] so the
+/// model can't be tricked (by a crafted `path` argument, or a symlink
+/// planted inside the workspace) into touching files outside the project
+/// it was given access to.
+///
+/// Keeps an in-memory journal of prior file contents so `undo`/`undo_all`
+/// can roll back edits made through this tool without needing git — the
+/// journal doesn't survive past this `EditorEditTool` instance, so it
+/// only covers edits made in the current session.
+pub struct EditorEditTool {
+    workspace: Arc<Workspace>,
+    journal: Mutex<Vec<JournalEntry>>,
+}
+
+impl EditorEditTool {
+    pub fn new(workspace: Arc<Workspace>) -> Self {
+        Self { workspace, journal: Mutex::new(Vec::new()) }
+    }
+
+    async fn write_journaled(&self, resolved: PathBuf, previous_content: String, updated: String) -> Result<(), String> {
+        write_atomic(&resolved, &updated).await?;
+        self.journal.lock().await.push(JournalEntry { path: resolved, previous_content });
+        Ok(())
+    }
+
+    /// Resolves `path`, reads it, and computes its replacement content for
+    /// a single old_string/new_string edit, without writing anything —
+    /// shared by the single-file `edit` operation and `edit_batch`, which
+    /// needs every edit's updated content computed up front so it can bail
+    /// out before touching disk if any one of them is invalid.
+    async fn compute_edit(
+        &self,
+        path: &str,
+        old_string: &str,
+        new_string: &str,
+        match_mode: &str,
+        create_if_missing: bool,
+    ) -> Result<(PathBuf, String, String), String> {
+        let exists = match self.workspace.resolve(path, true) {
+            Ok(resolved) => Some(resolved),
+            Err(_) if create_if_missing => None,
+            Err(err) => return Err(format!("cannot edit '{path}': {err}")),
+        };
+
+        let (resolved, raw_content) = match exists {
+            Some(resolved) => (resolved.clone(), read_text_file(path, &resolved).await?),
+            None => {
+                // File doesn't exist yet and the caller opted in to
+                // creating it — there's no existing content to match
+                // against, so old_string must be empty (replacing
+                // "nothing" with new_string, i.e. the new file's content).
+                let resolved =
+                    self.workspace.resolve(path, false).map_err(|err| format!("cannot create '{path}': {err}"))?;
+                if let Some(parent) = resolved.parent() {
+                    tokio::fs::create_dir_all(parent)
+                        .await
+                        .map_err(|err| format!("failed to create parent directories for '{path}': {err}"))?;
+                }
+                (resolved, String::new())
+            }
+        };
+        let eol = detect_eol(&raw_content);
+        let normalized = normalize_to_lf(&raw_content);
+
+        let updated_normalized = match match_mode {
+            "exact" => match normalized.matches(old_string).count() {
+                0 => return Err(format!("old_string not found in '{path}'")),
+                1 => normalized.replacen(old_string, new_string, 1),
+                count => {
+                    return Err(format!(
+                        "old_string is not unique in '{path}' ({count} occurrences); include more context"
+                    ))
+                }
+            },
+            "ignore_whitespace" => {
+                replace_fuzzy(&normalized, old_string, new_string).map_err(|err| format!("{err} in '{path}'"))?
+            }
+            other => return Err(format!("unknown match_mode '{other}', expected exact/ignore_whitespace")),
+        };
+
+        Ok((resolved, raw_content, restore_eol(&updated_normalized, eol)))
+    }
+
+    /// Applies every edit in `edits` as one all-or-nothing unit: computes
+    /// every file's updated content first (so a bad edit anywhere in the
+    /// batch is caught before any file is touched), then writes them all.
+    /// If a write fails partway through — a races-with-disk case the
+    /// up-front computation can't catch — the writes already made are
+    /// rolled back so the batch doesn't leave the tree half-changed.
+    async fn edit_batch(&self, edits: &[serde_json::Value]) -> ToolResult {
+        if edits.is_empty() {
+            return ToolResult::error("edit_batch requires at least one entry in 'edits'");
+        }
+
+        let mut computed = Vec::with_capacity(edits.len());
+        for (index, edit) in edits.iter().enumerate() {
+            let (Some(path), Some(old_string), Some(new_string)) = (
+                edit.get("path").and_then(|v| v.as_str()),
+                edit.get("old_string").and_then(|v| v.as_str()),
+                edit.get("new_string").and_then(|v| v.as_str()),
+            ) else {
+                return ToolResult::error(format!(
+                    "edits[{index}] requires string fields: path, old_string, new_string"
+                ));
+            };
+            let match_mode = edit.get("match_mode").and_then(|v| v.as_str()).unwrap_or("exact");
+            let create_if_missing = edit.get("create_if_missing").and_then(|v| v.as_bool()).unwrap_or(false);
+
+            match self.compute_edit(path, old_string, new_string, match_mode, create_if_missing).await {
+                Ok(result) => computed.push((path.to_string(), result)),
+                Err(err) => return ToolResult::error(format!("edits[{index}]: {err}")),
+            }
+        }
+
+        let mut written = Vec::with_capacity(computed.len());
+        for (path, (resolved, previous_content, updated)) in computed {
+            match write_atomic(&resolved, &updated).await {
+                Ok(()) => written.push((path, resolved, previous_content)),
+                Err(err) => {
+                    for (_, resolved, previous_content) in written.into_iter().rev() {
+                        let _ = write_atomic(&resolved, &previous_content).await;
+                    }
+                    return ToolResult::error(format!("failed to write '{path}': {err}; batch rolled back"));
+                }
+            }
+        }
+
+        let mut journal = self.journal.lock().await;
+        let paths: Vec<String> = written
+            .iter()
+            .map(|(path, _, _)| path.clone())
+            .collect();
+        for (_, resolved, previous_content) in written {
+            journal.push(JournalEntry { path: resolved, previous_content });
+        }
+        drop(journal);
+
+        ToolResult::ok(format!("edited {} file(s): {}", paths.len(), paths.join(", ")))
+    }
+
+    async fn undo(&self, path: Option<&str>) -> ToolResult {
+        let mut journal = self.journal.lock().await;
+        let target_path = match path {
+            Some(path) => match self.workspace.resolve(path, false) {
+                Ok(resolved) => Some(resolved),
+                Err(err) => return ToolResult::error(format!("cannot undo '{path}': {err}")),
+            },
+            None => None,
+        };
+        let index = match &target_path {
+            Some(target_path) => journal.iter().rposition(|entry| &entry.path == target_path),
+            None => (!journal.is_empty()).then(|| journal.len() - 1),
+        };
+        let Some(index) = index else {
+            return ToolResult::error("nothing to undo".to_string());
+        };
+        let entry = journal.remove(index);
+        match write_atomic(&entry.path, &entry.previous_content).await {
+            Ok(()) => ToolResult::ok(format!("reverted {}", entry.path.display())),
+            Err(err) => ToolResult::error(format!("failed to revert {}: {err}", entry.path.display())),
+        }
+    }
+
+    /// Replaces lines `start_line..=end_line` (1-based, inclusive) with
+    /// `new_content`, checked against `expected_content` first — for
+    /// edits anchored to a position rather than unique text, e.g.
+    /// replacing "the third closing brace" where [`Self::execute`]'s
+    /// `old_string` matching would reject the ambiguous anchor outright.
+    async fn edit_lines(
+        &self,
+        path: &str,
+        start_line: usize,
+        end_line: usize,
+        expected_content: &str,
+        new_content: &str,
+    ) -> ToolResult {
+        if start_line == 0 || start_line > end_line + 1 {
+            return ToolResult::error("start_line must be >= 1 and <= end_line + 1");
+        }
+        let resolved = match self.workspace.resolve(path, true) {
+            Ok(resolved) => resolved,
+            Err(err) => return ToolResult::error(format!("cannot edit '{path}': {err}")),
+        };
+        let raw_content = match read_text_file(path, &resolved).await {
+            Ok(content) => content,
+            Err(err) => return ToolResult::error(err),
+        };
+        let eol = detect_eol(&raw_content);
+        let content = normalize_to_lf(&raw_content);
+        let trailing_newline = content.ends_with('\n');
+        let lines: Vec<&str> = content.lines().collect();
+
+        // start_line == end_line + 1 is a pure insertion before
+        // start_line, with nothing existing to check.
+        let is_insertion = start_line == end_line + 1;
+        if !is_insertion {
+            if end_line > lines.len() {
+                return ToolResult::error(format!("'{path}' only has {} lines", lines.len()));
+            }
+            let actual = lines[start_line - 1..end_line].join("\n");
+            if actual != expected_content {
+                return ToolResult::error(format!(
+                    "expected_content did not match lines {start_line}-{end_line} of '{path}'; \
+                     re-read the file, it may have changed"
+                ));
+            }
+        } else if start_line > lines.len() + 1 {
+            return ToolResult::error(format!("'{path}' only has {} lines", lines.len()));
+        }
+
+        let mut updated_lines: Vec<&str> = lines[..start_line - 1].to_vec();
+        let new_content_lines: Vec<&str> = if new_content.is_empty() { Vec::new() } else { new_content.lines().collect() };
+        updated_lines.extend(new_content_lines);
+        if !is_insertion {
+            updated_lines.extend(&lines[end_line..]);
+        } else {
+            updated_lines.extend(&lines[start_line - 1..]);
+        }
+
+        let mut updated = updated_lines.join("\n");
+        if trailing_newline {
+            updated.push('\n');
+        }
+        let updated = restore_eol(&updated, eol);
+        let context = context_around_change(&raw_content, &updated, CONTEXT_LINES);
+        match self.write_journaled(resolved, raw_content, updated).await {
+            Ok(()) => ToolResult::ok(format!("edited lines {start_line}-{end_line} of '{path}'\n\n{context}")),
+            Err(err) => ToolResult::error(format!("failed to write '{path}': {err}")),
+        }
+    }
+
+    async fn undo_all(&self) -> ToolResult {
+        let mut journal = self.journal.lock().await;
+        let mut reverted = Vec::new();
+        let mut failed = Vec::new();
+        while let Some(entry) = journal.pop() {
+            match write_atomic(&entry.path, &entry.previous_content).await {
+                Ok(()) => reverted.push(entry.path.display().to_string()),
+                Err(err) => failed.push(format!("{}: {err}", entry.path.display())),
+            }
+        }
+        if failed.is_empty() {
+            ToolResult::ok(format!("reverted {} edit(s): {}", reverted.len(), reverted.join(", ")))
+        } else {
+            ToolResult::error(format!("failed to revert: {}", failed.join("; ")))
+        }
+    }
+}
+
+#[async_trait]
+impl Tool for EditorEditTool {
+    fn definition(&self) -> ToolDefinition {
+        ToolDefinition {
+            name: "edit_file".to_string(),
+            description: "Replace an exact substring in an existing file with new text. \
+                Fails if `old_string` doesn't appear exactly once, so it doesn't silently \
+                edit the wrong occurrence. Set operation to 'undo' to revert the most recent \
+                edit to a file (or the most recent edit overall, if path is omitted), or \
+                'undo_all' to revert every edit made by this tool so far this session. Set \
+                operation to 'edit_lines' to replace a line range by number instead of by \
+                matched text, for anchors that aren't unique (e.g. a lone closing brace). Set \
+                match_mode to 'ignore_whitespace' if old_string's indentation or trailing \
+                whitespace might not match the file exactly. Set operation to 'edit_batch' to \
+                apply several path/old_string/new_string edits, possibly across different \
+                files, as one all-or-nothing unit. Set create_if_missing to true on 'edit' (or \
+                per-entry on 'edit_batch') to create path if it doesn't exist yet — old_string \
+                must be empty in that case, since there's nothing existing to match."
+                .to_string(),
+            parameters: json!({
+                "type": "object",
+                "properties": {
+                    "operation": { "type": "string", "enum": ["edit", "edit_batch", "edit_lines", "undo", "undo_all"], "default": "edit" },
+                    "path": { "type": "string", "description": "Path to the file, relative to the workspace root. Required for 'edit'/'edit_lines'; optional for 'undo'." },
+                    "old_string": { "type": "string", "description": "Exact text to replace; must appear exactly once. Required for 'edit'. Leave empty with create_if_missing to write a brand-new file." },
+                    "new_string": { "type": "string", "description": "Text to replace it with. Required for 'edit'." },
+                    "match_mode": { "type": "string", "enum": ["exact", "ignore_whitespace"], "default": "exact", "description": "For 'edit'/'edit_batch' entries: 'ignore_whitespace' locates old_string by comparing lines with runs of whitespace collapsed, then writes new_string as given, leaving the rest of the file's formatting untouched." },
+                    "create_if_missing": { "type": "boolean", "default": false, "description": "For 'edit': create path (and parent directories) if it doesn't already exist, with new_string as its initial content. Requires old_string to be empty." },
+                    "dry_run": { "type": "boolean", "default": false, "description": "For 'edit': return a unified diff of what would change, without writing." },
+                    "edits": {
+                        "type": "array",
+                        "description": "For 'edit_batch': the edits to apply together. Each entry takes the same path/old_string/new_string/match_mode/create_if_missing fields as a single 'edit' call.",
+                        "items": {
+                            "type": "object",
+                            "properties": {
+                                "path": { "type": "string" },
+                                "old_string": { "type": "string" },
+                                "new_string": { "type": "string" },
+                                "match_mode": { "type": "string", "enum": ["exact", "ignore_whitespace"], "default": "exact" },
+                                "create_if_missing": { "type": "boolean", "default": false },
+                            },
+                            "required": ["path", "old_string", "new_string"],
+                        },
+                    },
+                    "start_line": { "type": "integer", "minimum": 1, "description": "For 'edit_lines': first line to replace, 1-based." },
+                    "end_line": { "type": "integer", "minimum": 0, "description": "For 'edit_lines': last line to replace, 1-based, inclusive. Set to start_line - 1 to insert before start_line instead of replacing anything." },
+                    "expected_content": { "type": "string", "description": "For 'edit_lines': the exact current text of start_line..end_line, checked before editing." },
+                    "new_content": { "type": "string", "description": "For 'edit_lines': replacement text; empty deletes the range." },
+                },
+            }),
+        }
+    }
+
+    fn requires_approval(&self) -> bool {
+        true
+    }
+
+    async fn execute(&self, arguments: serde_json::Value) -> ToolResult {
+        let operation = arguments.get("operation").and_then(|v| v.as_str()).unwrap_or("edit");
+        let path_arg = arguments.get("path").and_then(|v| v.as_str());
+
+        match operation {
+            "undo" => return self.undo(path_arg).await,
+            "undo_all" => return self.undo_all().await,
+            "edit_lines" => {
+                let (Some(path), Some(start_line), Some(end_line), Some(expected_content), Some(new_content)) = (
+                    path_arg,
+                    arguments.get("start_line").and_then(|v| v.as_u64()),
+                    arguments.get("end_line").and_then(|v| v.as_u64()),
+                    arguments.get("expected_content").and_then(|v| v.as_str()),
+                    arguments.get("new_content").and_then(|v| v.as_str()),
+                ) else {
+                    return ToolResult::error(
+                        "edit_lines requires: path, start_line, end_line, expected_content, new_content",
+                    );
+                };
+                return self.edit_lines(path, start_line as usize, end_line as usize, expected_content, new_content).await;
+            }
+            "edit" => {}
+            "edit_batch" => {
+                let Some(edits) = arguments.get("edits").and_then(|v| v.as_array()) else {
+                    return ToolResult::error("edit_batch requires an 'edits' array argument");
+                };
+                return self.edit_batch(edits).await;
+            }
+            other => {
+                return ToolResult::error(format!(
+                    "unknown operation '{other}', expected edit/edit_batch/edit_lines/undo/undo_all"
+                ))
+            }
+        }
+
+        let (Some(path), Some(old_string), Some(new_string)) = (
+            path_arg,
+            arguments.get("old_string").and_then(|v| v.as_str()),
+            arguments.get("new_string").and_then(|v| v.as_str()),
+        ) else {
+            return ToolResult::error("edit_file requires string arguments: path, old_string, new_string");
+        };
+        let match_mode = arguments.get("match_mode").and_then(|v| v.as_str()).unwrap_or("exact");
+        let create_if_missing = arguments.get("create_if_missing").and_then(|v| v.as_bool()).unwrap_or(false);
+
+        let (resolved, content, updated) =
+            match self.compute_edit(path, old_string, new_string, match_mode, create_if_missing).await {
+                Ok(result) => result,
+                Err(err) => return ToolResult::error(err),
+            };
+
+        let dry_run = arguments.get("dry_run").and_then(|v| v.as_bool()).unwrap_or(false);
+        if dry_run {
+            let diff = similar::TextDiff::from_lines(&content, &updated);
+            return ToolResult::ok(diff.unified_diff().header(path, path).to_string());
+        }
+        let context = context_around_change(&content, &updated, CONTEXT_LINES);
+        match self.write_journaled(resolved, content, updated).await {
+            Ok(()) => ToolResult::ok(format!("edited '{path}'\n\n{context}")),
+            Err(err) => ToolResult::error(format!("failed to write '{path}': {err}")),
+        }
+    }
+}
+
+/// Lines of context shown on each side of a change in a successful edit's
+/// result, so the model can confirm the edit landed correctly without a
+/// separate `read_file` call.
+const CONTEXT_LINES: usize = 5;
+
+/// Finds the span of lines that changed between `before` and `after` and
+/// renders `context` lines on each side of it from `after`, with line
+/// numbers — the same `cat -n`-style rendering [`crate::file::ReadFileTool`]
+/// uses, so a snippet here reads like one from a `read_file` call.
+fn context_around_change(before: &str, after: &str, context: usize) -> String {
+    let before_lines: Vec<&str> = before.lines().collect();
+    let after_lines: Vec<&str> = after.lines().collect();
+    if after_lines.is_empty() {
+        return String::new();
+    }
+
+    let prefix_len = before_lines.iter().zip(after_lines.iter()).take_while(|(a, b)| a == b).count();
+    let suffix_len = before_lines
+        .iter()
+        .rev()
+        .zip(after_lines.iter().rev())
+        .take_while(|(a, b)| a == b)
+        .count()
+        .min(after_lines.len() - prefix_len);
+
+    let first_changed = prefix_len.min(after_lines.len() - 1);
+    let last_changed = (after_lines.len() - 1).saturating_sub(suffix_len).max(first_changed);
+
+    let start = first_changed.saturating_sub(context);
+    let end = (last_changed + context).min(after_lines.len() - 1);
+
+    after_lines[start..=end]
+        .iter()
+        .enumerate()
+        .map(|(index, line)| format!("{:>6}\t{line}\n", start + index + 1))
+        .collect()
+}
+
+fn normalize_whitespace(line: &str) -> String {
+    line.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// Locates `old_string` in `content` by comparing lines with runs of
+/// whitespace collapsed (so tabs-vs-spaces and trailing whitespace don't
+/// break the match), then splices in `new_string` verbatim at the
+/// original, unnormalized lines it found — so the rest of the file keeps
+/// its real formatting and only `new_string`'s own formatting ends up in
+/// the result.
+fn replace_fuzzy(content: &str, old_string: &str, new_string: &str) -> Result<String, String> {
+    let content_lines: Vec<&str> = content.lines().collect();
+    let old_lines: Vec<&str> = old_string.lines().collect();
+    if old_lines.is_empty() || content_lines.len() < old_lines.len() {
+        return Err("old_string not found".to_string());
+    }
+    let normalized_old: Vec<String> = old_lines.iter().map(|l| normalize_whitespace(l)).collect();
+
+    let mut matches = Vec::new();
+    for start in 0..=content_lines.len() - old_lines.len() {
+        let window = &content_lines[start..start + old_lines.len()];
+        if window.iter().map(|l| normalize_whitespace(l)).eq(normalized_old.iter().cloned()) {
+            matches.push(start);
+        }
+    }
+
+    match matches.len() {
+        0 => Err("old_string not found".to_string()),
+        1 => {
+            let start = matches[0];
+            let end = start + old_lines.len();
+            let trailing_newline = content.ends_with('\n');
+            let mut updated_lines: Vec<&str> = content_lines[..start].to_vec();
+            updated_lines.extend(new_string.lines());
+            updated_lines.extend(&content_lines[end..]);
+            let mut updated = updated_lines.join("\n");
+            if trailing_newline {
+                updated.push('\n');
+            }
+            Ok(updated)
+        }
+        count => Err(format!("old_string is not unique ({count} occurrences); include more context")),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_workspace() -> (tempfile::TempDir, Arc<Workspace>) {
+        let dir = tempfile::tempdir().expect("create temp dir");
+        let workspace = Arc::new(Workspace::new(dir.path()).expect("workspace root should be valid"));
+        (dir, workspace)
+    }
+
+    #[test]
+    fn test_detect_eol_requires_every_line_break_to_be_crlf() {
+        assert_eq!(detect_eol("a\r\nb\r\n"), Eol::CrLf);
+        assert_eq!(detect_eol("a\nb\n"), Eol::Lf);
+        assert_eq!(detect_eol("a\r\nb\n"), Eol::Lf);
+        assert_eq!(detect_eol("no newlines"), Eol::Lf);
+    }
+
+    #[test]
+    fn test_normalize_and_restore_eol_round_trip_crlf() {
+        let original = "line one\r\nline two\r\n";
+        let normalized = normalize_to_lf(original);
+        assert_eq!(normalized, "line one\nline two\n");
+        assert_eq!(restore_eol(&normalized, Eol::CrLf), original);
+    }
+
+    #[test]
+    fn test_replace_fuzzy_ignores_whitespace_differences() {
+        let content = "fn main() {\n    let x = 1;\n}\n";
+        let updated = replace_fuzzy(content, "let x = 1;", "let x = 2;").expect("should match despite indentation");
+        assert!(updated.contains("let x = 2;"));
+    }
+
+    #[test]
+    fn test_replace_fuzzy_errors_when_old_string_is_not_found() {
+        let result = replace_fuzzy("a\nb\nc\n", "not present", "x");
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_replace_fuzzy_errors_when_old_string_matches_more_than_once() {
+        let content = "x\ny\nx\ny\n";
+        let result = replace_fuzzy(content, "x\ny", "z");
+        assert!(result.unwrap_err().contains("not unique"));
+    }
+
+    #[tokio::test]
+    async fn test_execute_edit_replaces_a_unique_substring() {
+        let (dir, workspace) = test_workspace();
+        std::fs::write(dir.path().join("f.txt"), "hello world\n").expect("write file");
+        let tool = EditorEditTool::new(workspace);
+
+        let result = tool.execute(json!({ "path": "f.txt", "old_string": "world", "new_string": "there" })).await;
+        assert!(!result.is_error);
+        let updated = std::fs::read_to_string(dir.path().join("f.txt")).expect("read back file");
+        assert_eq!(updated, "hello there\n");
+    }
+
+    #[tokio::test]
+    async fn test_execute_edit_errors_when_old_string_is_not_unique() {
+        let (dir, workspace) = test_workspace();
+        std::fs::write(dir.path().join("f.txt"), "dup\ndup\n").expect("write file");
+        let tool = EditorEditTool::new(workspace);
+
+        let result = tool.execute(json!({ "path": "f.txt", "old_string": "dup", "new_string": "x" })).await;
+        assert!(result.is_error);
+        assert!(result.content.contains("not unique"));
+    }
+
+    #[tokio::test]
+    async fn test_execute_edit_creates_a_missing_file_when_opted_in() {
+        let (dir, workspace) = test_workspace();
+        let tool = EditorEditTool::new(workspace);
+
+        let result = tool
+            .execute(json!({
+                "path": "new.txt",
+                "old_string": "",
+                "new_string": "brand new content\n",
+                "create_if_missing": true,
+            }))
+            .await;
+        assert!(!result.is_error);
+        let created = std::fs::read_to_string(dir.path().join("new.txt")).expect("read back new file");
+        assert_eq!(created, "brand new content\n");
+    }
+
+    #[tokio::test]
+    async fn test_execute_undo_reverts_the_most_recent_edit() {
+        let (dir, workspace) = test_workspace();
+        std::fs::write(dir.path().join("f.txt"), "before\n").expect("write file");
+        let tool = EditorEditTool::new(workspace);
+
+        let edit = tool.execute(json!({ "path": "f.txt", "old_string": "before", "new_string": "after" })).await;
+        assert!(!edit.is_error);
+
+        let undo = tool.execute(json!({ "operation": "undo" })).await;
+        assert!(!undo.is_error);
+        let reverted = std::fs::read_to_string(dir.path().join("f.txt")).expect("read back file");
+        assert_eq!(reverted, "before\n");
+    }
+
+    #[tokio::test]
+    async fn test_execute_undo_errors_when_there_is_nothing_to_undo() {
+        let (_dir, workspace) = test_workspace();
+        let tool = EditorEditTool::new(workspace);
+        let result = tool.execute(json!({ "operation": "undo" })).await;
+        assert!(result.is_error);
+        assert!(result.content.contains("nothing to undo"));
+    }
+
+    #[tokio::test]
+    async fn test_execute_edit_lines_replaces_a_line_range() {
+        let (dir, workspace) = test_workspace();
+        std::fs::write(dir.path().join("f.txt"), "one\ntwo\nthree\n").expect("write file");
+        let tool = EditorEditTool::new(workspace);
+
+        let result = tool
+            .execute(json!({
+                "operation": "edit_lines",
+                "path": "f.txt",
+                "start_line": 2,
+                "end_line": 2,
+                "expected_content": "two",
+                "new_content": "TWO",
+            }))
+            .await;
+        assert!(!result.is_error);
+        let updated = std::fs::read_to_string(dir.path().join("f.txt")).expect("read back file");
+        assert_eq!(updated, "one\nTWO\nthree\n");
+    }
+
+    #[tokio::test]
+    async fn test_execute_edit_lines_errors_on_expected_content_mismatch() {
+        let (dir, workspace) = test_workspace();
+        std::fs::write(dir.path().join("f.txt"), "one\ntwo\nthree\n").expect("write file");
+        let tool = EditorEditTool::new(workspace);
+
+        let result = tool
+            .execute(json!({
+                "operation": "edit_lines",
+                "path": "f.txt",
+                "start_line": 2,
+                "end_line": 2,
+                "expected_content": "not two",
+                "new_content": "TWO",
+            }))
+            .await;
+        assert!(result.is_error);
+        assert!(result.content.contains("did not match"));
+    }
+
+    #[tokio::test]
+    async fn test_execute_edit_batch_applies_every_edit_atomically() {
+        let (dir, workspace) = test_workspace();
+        std::fs::write(dir.path().join("a.txt"), "a-before\n").expect("write a.txt");
+        std::fs::write(dir.path().join("b.txt"), "b-before\n").expect("write b.txt");
+        let tool = EditorEditTool::new(workspace);
+
+        let result = tool
+            .execute(json!({
+                "operation": "edit_batch",
+                "edits": [
+                    { "path": "a.txt", "old_string": "a-before", "new_string": "a-after" },
+                    { "path": "b.txt", "old_string": "b-before", "new_string": "b-after" },
+                ],
+            }))
+            .await;
+        assert!(!result.is_error);
+        assert_eq!(std::fs::read_to_string(dir.path().join("a.txt")).expect("read a.txt"), "a-after\n");
+        assert_eq!(std::fs::read_to_string(dir.path().join("b.txt")).expect("read b.txt"), "b-after\n");
+    }
+
+    #[tokio::test]
+    async fn test_execute_edit_batch_rolls_back_if_any_edit_is_invalid() {
+        let (dir, workspace) = test_workspace();
+        std::fs::write(dir.path().join("a.txt"), "a-before\n").expect("write a.txt");
+        let tool = EditorEditTool::new(workspace);
+
+        let result = tool
+            .execute(json!({
+                "operation": "edit_batch",
+                "edits": [
+                    { "path": "a.txt", "old_string": "a-before", "new_string": "a-after" },
+                    { "path": "a.txt", "old_string": "not present", "new_string": "x" },
+                ],
+            }))
+            .await;
+        assert!(result.is_error);
+        assert_eq!(std::fs::read_to_string(dir.path().join("a.txt")).expect("read a.txt"), "a-before\n");
+    }
+}
@@ -0,0 +1,100 @@
+//! Runs a tool implemented as a standalone executable (a Python or Node
+//! script, say) rather than Rust code in this crate. Unlike
+//! [`crate::mcp::McpClient`], which keeps one server process alive for a
+//! whole session and can expose many tools, an [`ExternalTool`] is one
+//! process per call, configured for exactly one tool — no handshake, no
+//! long-lived pipe to keep healthy, just spawn, ask, read the answer,
+//! exit. Response parsing reuses [`crate::mcp::content_to_tool_result`]'s
+//! `{content, isError}` shape so a script author who's seen one doesn't
+//! need to learn a second.
+//!
+//! A host wires these up from a config file (name, command, args,
+//! description, parameter schema) and registers one per configured
+//! entry — this module only knows how to run a single already-configured
+//! one.
+
+use std::process::Stdio;
+
+use async_trait::async_trait;
+use serde_json::{json, Value};
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+
+use crate::mcp::content_to_tool_result;
+use crate::tool::{Tool, ToolDefinition, ToolResult};
+
+/// One subprocess-backed tool. `command`/`args` are spawned fresh for
+/// every call; the arguments the model passed are sent as a single
+/// JSON-RPC request's `params`, and the process is expected to write one
+/// JSON-RPC response line to its stdout and then exit.
+pub struct ExternalTool {
+    name: String,
+    description: String,
+    parameters: Value,
+    command: String,
+    args: Vec<String>,
+}
+
+impl ExternalTool {
+    pub fn new(
+        name: impl Into<String>,
+        description: impl Into<String>,
+        parameters: Value,
+        command: impl Into<String>,
+        args: Vec<String>,
+    ) -> Self {
+        Self { name: name.into(), description: description.into(), parameters, command: command.into(), args }
+    }
+
+    async fn call(&self, arguments: Value) -> Result<Value, String> {
+        let mut child = tokio::process::Command::new(&self.command)
+            .args(&self.args)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .spawn()
+            .map_err(|err| format!("failed to spawn '{}': {err}", self.command))?;
+
+        let mut stdin = child.stdin.take().ok_or("external tool process spawned without a stdin pipe")?;
+        let stdout = child.stdout.take().ok_or("external tool process spawned without a stdout pipe")?;
+
+        let request = json!({ "jsonrpc": "2.0", "id": 1, "method": "call", "params": arguments });
+        let line = format!("{request}\n");
+        stdin.write_all(line.as_bytes()).await.map_err(|err| err.to_string())?;
+        // The process reads exactly one request then answers, so closing
+        // stdin here is the signal it's seen the whole thing rather than
+        // a partial line it might still be waiting on.
+        drop(stdin);
+
+        let mut raw_line = String::new();
+        BufReader::new(stdout).read_line(&mut raw_line).await.map_err(|err| err.to_string())?;
+        if raw_line.trim().is_empty() {
+            return Err(format!("'{}' exited without writing a response", self.command));
+        }
+
+        let response: Value = serde_json::from_str(raw_line.trim()).map_err(|err| format!("invalid response from '{}': {err}", self.command))?;
+        if let Some(error) = response.get("error") {
+            return Err(format!("'{}' returned an error: {error}", self.command));
+        }
+        Ok(response.get("result").cloned().unwrap_or(Value::Null))
+    }
+}
+
+#[async_trait]
+impl Tool for ExternalTool {
+    fn definition(&self) -> ToolDefinition {
+        ToolDefinition { name: self.name.clone(), description: self.description.clone(), parameters: self.parameters.clone() }
+    }
+
+    // A subprocess this crate didn't write can do anything a shell
+    // command can — same stance as McpTool's remote-server calls.
+    fn requires_approval(&self) -> bool {
+        true
+    }
+
+    async fn execute(&self, arguments: Value) -> ToolResult {
+        match self.call(arguments).await {
+            Ok(result) => content_to_tool_result(&result),
+            Err(err) => ToolResult::error(format!("external tool '{}' failed: {err}", self.name)),
+        }
+    }
+}
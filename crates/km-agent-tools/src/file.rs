@@ -0,0 +1,179 @@
+//! Read-only file access. The editor tool can only modify files it's
+//! told already exist in a known state; this is the tool a model uses to
+//! find out that state in the first place.
+
+use async_trait::async_trait;
+use serde_json::json;
+
+use crate::tool::{Tool, ToolDefinition, ToolResult};
+use crate::workspace::Workspace;
+use std::sync::Arc;
+
+/// Hard cap on how much of a file is ever read into memory, independent
+/// of `limit` — a `limit` the model forgets to set shouldn't let a
+/// multi-gigabyte file get read whole before the line-based windowing
+/// even has a chance to apply.
+const MAX_READ_BYTES: usize = 10 * 1024 * 1024;
+
+/// How many bytes at the start of a file are checked for a NUL byte to
+/// decide whether it's binary, rather than scanning the whole thing.
+const BINARY_SNIFF_BYTES: usize = 8000;
+
+/// Reads a file as text, with optional `offset`/`limit` line windowing
+/// and line numbers prefixed on each line — mirrors how a human would
+/// `sed -n` a large file rather than dumping it whole.
+pub struct ReadFileTool {
+    workspace: Arc<Workspace>,
+}
+
+impl ReadFileTool {
+    pub fn new(workspace: Arc<Workspace>) -> Self {
+        Self { workspace }
+    }
+}
+
+#[async_trait]
+impl Tool for ReadFileTool {
+    fn definition(&self) -> ToolDefinition {
+        ToolDefinition {
+            name: "read_file".to_string(),
+            description: "Read a text file, optionally starting at a given 1-based line \
+                (offset) and reading at most `limit` lines. Output is prefixed with line \
+                numbers. Refuses binary files and files above a size cap."
+                .to_string(),
+            parameters: json!({
+                "type": "object",
+                "properties": {
+                    "path": { "type": "string" },
+                    "offset": { "type": "integer", "minimum": 1, "description": "1-based line number to start from." },
+                    "limit": { "type": "integer", "minimum": 1, "description": "Maximum number of lines to return." },
+                },
+                "required": ["path"],
+            }),
+        }
+    }
+
+    // A read has no side effects and, within a short TTL, a repeated
+    // read of the same path/offset/limit is almost always going to see
+    // the same content — worth caching even though a concurrent writer
+    // could in principle make it briefly stale.
+    fn cacheable(&self) -> bool {
+        true
+    }
+
+    async fn execute(&self, arguments: serde_json::Value) -> ToolResult {
+        let Some(path) = arguments.get("path").and_then(|v| v.as_str()) else {
+            return ToolResult::error("read_file requires a string 'path' argument");
+        };
+        let offset = arguments.get("offset").and_then(|v| v.as_u64()).unwrap_or(1).max(1) as usize;
+        let limit = arguments.get("limit").and_then(|v| v.as_u64()).map(|n| n as usize);
+
+        let resolved = match self.workspace.resolve(path, true) {
+            Ok(resolved) => resolved,
+            Err(err) => return ToolResult::error(format!("cannot read '{path}': {err}")),
+        };
+
+        let metadata = match tokio::fs::metadata(&resolved).await {
+            Ok(metadata) => metadata,
+            Err(err) => return ToolResult::error(format!("failed to stat '{path}': {err}")),
+        };
+        if metadata.len() as usize > MAX_READ_BYTES {
+            return ToolResult::error(format!(
+                "'{path}' is {} bytes, over the {MAX_READ_BYTES}-byte read cap; use offset/limit on a narrower range",
+                metadata.len()
+            ));
+        }
+
+        let bytes = match tokio::fs::read(&resolved).await {
+            Ok(bytes) => bytes,
+            Err(err) => return ToolResult::error(format!("failed to read '{path}': {err}")),
+        };
+        if bytes[..bytes.len().min(BINARY_SNIFF_BYTES)].contains(&0) {
+            return ToolResult::error(format!("'{path}' looks like a binary file, refusing to read as text"));
+        }
+
+        let content = String::from_utf8_lossy(&bytes);
+        let lines: Vec<&str> = content.lines().collect();
+        let start = offset.saturating_sub(1).min(lines.len());
+        let end = match limit {
+            Some(limit) => (start + limit).min(lines.len()),
+            None => lines.len(),
+        };
+
+        let rendered: String = lines[start..end]
+            .iter()
+            .enumerate()
+            .map(|(index, line)| format!("{:>6}\t{line}\n", start + index + 1))
+            .collect();
+        ToolResult::ok(rendered)
+    }
+}
+
+/// Creates files, unlike [`crate::editor::EditorEditTool`] which only
+/// modifies ones that already exist. Creates missing parent directories
+/// so a model scaffolding a new module doesn't need a separate mkdir
+/// step first.
+pub struct WriteFileTool {
+    workspace: Arc<Workspace>,
+}
+
+impl WriteFileTool {
+    pub fn new(workspace: Arc<Workspace>) -> Self {
+        Self { workspace }
+    }
+}
+
+#[async_trait]
+impl Tool for WriteFileTool {
+    fn definition(&self) -> ToolDefinition {
+        ToolDefinition {
+            name: "write_file".to_string(),
+            description: "Create a file with the given content, making parent directories \
+                as needed. Fails if the file already exists unless overwrite is true."
+                .to_string(),
+            parameters: json!({
+                "type": "object",
+                "properties": {
+                    "path": { "type": "string" },
+                    "content": { "type": "string" },
+                    "overwrite": { "type": "boolean", "default": false },
+                },
+                "required": ["path", "content"],
+            }),
+        }
+    }
+
+    fn requires_approval(&self) -> bool {
+        true
+    }
+
+    async fn execute(&self, arguments: serde_json::Value) -> ToolResult {
+        let (Some(path), Some(content)) = (
+            arguments.get("path").and_then(|v| v.as_str()),
+            arguments.get("content").and_then(|v| v.as_str()),
+        ) else {
+            return ToolResult::error("write_file requires string arguments: path, content");
+        };
+        let overwrite = arguments.get("overwrite").and_then(|v| v.as_bool()).unwrap_or(false);
+
+        let resolved = match self.workspace.resolve(path, false) {
+            Ok(resolved) => resolved,
+            Err(err) => return ToolResult::error(format!("cannot write '{path}': {err}")),
+        };
+
+        if !overwrite && tokio::fs::try_exists(&resolved).await.unwrap_or(false) {
+            return ToolResult::error(format!("'{path}' already exists; pass overwrite: true to replace it"));
+        }
+
+        if let Some(parent) = resolved.parent() {
+            if let Err(err) = tokio::fs::create_dir_all(parent).await {
+                return ToolResult::error(format!("failed to create parent directories for '{path}': {err}"));
+            }
+        }
+
+        match tokio::fs::write(&resolved, content).await {
+            Ok(()) => ToolResult::ok(format!("wrote '{path}'")),
+            Err(err) => ToolResult::error(format!("failed to write '{path}': {err}")),
+        }
+    }
+}
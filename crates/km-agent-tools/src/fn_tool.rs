@@ -0,0 +1,74 @@
+//! Builder for wrapping a plain async closure as a [`Tool`], for the
+//! common case of a stateless tool that doesn't need its own struct and
+//! `impl Tool` boilerplate. A `#[km_tool]` attribute macro would need its
+//! own proc-macro crate added to the workspace, which this tree has no
+//! manifest to do; `FnTool` gets most of the same ergonomics without one.
+
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+
+use async_trait::async_trait;
+
+use crate::tool::{Tool, ToolDefinition, ToolResult};
+
+type BoxFuture<'a, T> = Pin<Box<dyn Future<Output = T> + Send + 'a>>;
+
+/// A [`Tool`] built from a name, description, JSON Schema, and an async
+/// closure, instead of a dedicated struct. Useful for small one-off
+/// tools with no shared state to hold; tools that need a [`crate::workspace::Workspace`]
+/// or similar are still clearer as their own struct.
+pub struct FnTool {
+    name: String,
+    description: String,
+    parameters: serde_json::Value,
+    requires_approval: bool,
+    handler: Arc<dyn Fn(serde_json::Value) -> BoxFuture<'static, ToolResult> + Send + Sync>,
+}
+
+impl FnTool {
+    pub fn new<F, Fut>(
+        name: impl Into<String>,
+        description: impl Into<String>,
+        parameters: serde_json::Value,
+        handler: F,
+    ) -> Self
+    where
+        F: Fn(serde_json::Value) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = ToolResult> + Send + 'static,
+    {
+        Self {
+            name: name.into(),
+            description: description.into(),
+            parameters,
+            requires_approval: false,
+            handler: Arc::new(move |arguments| Box::pin(handler(arguments))),
+        }
+    }
+
+    /// Opts this tool into approval, mirroring [`Tool::requires_approval`]'s
+    /// default of `false`.
+    pub fn requires_approval(mut self, requires_approval: bool) -> Self {
+        self.requires_approval = requires_approval;
+        self
+    }
+}
+
+#[async_trait]
+impl Tool for FnTool {
+    fn definition(&self) -> ToolDefinition {
+        ToolDefinition {
+            name: self.name.clone(),
+            description: self.description.clone(),
+            parameters: self.parameters.clone(),
+        }
+    }
+
+    fn requires_approval(&self) -> bool {
+        self.requires_approval
+    }
+
+    async fn execute(&self, arguments: serde_json::Value) -> ToolResult {
+        (self.handler)(arguments).await
+    }
+}
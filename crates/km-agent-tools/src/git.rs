@@ -0,0 +1,246 @@
+//! Structured git access via stable plumbing commands, so the model
+//! picks an operation and typed arguments instead of guessing porcelain
+//! flags that vary by git version and produce output meant for a human
+//! terminal, not a parser.
+
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use serde_json::json;
+use tokio::process::Command;
+
+use crate::tool::{Tool, ToolDefinition, ToolResult};
+use crate::workspace::Workspace;
+
+pub struct GitTool {
+    workspace: Arc<Workspace>,
+}
+
+impl GitTool {
+    pub fn new(workspace: Arc<Workspace>) -> Self {
+        Self { workspace }
+    }
+
+    async fn run(&self, args: &[&str]) -> Result<String, String> {
+        let output = Command::new("git")
+            .current_dir(self.workspace.root())
+            .args(args)
+            .output()
+            .await
+            .map_err(|err| format!("failed to run git {}: {err}", args.join(" ")))?;
+        let mut text = String::from_utf8_lossy(&output.stdout).into_owned();
+        text.push_str(&String::from_utf8_lossy(&output.stderr));
+        if output.status.success() {
+            Ok(text)
+        } else {
+            Err(format!("git {} exited with {}: {text}", args.join(" "), output.status))
+        }
+    }
+}
+
+#[async_trait]
+impl Tool for GitTool {
+    fn definition(&self) -> ToolDefinition {
+        ToolDefinition {
+            name: "git".to_string(),
+            description: "Run a structured git operation against the workspace repo: \
+                status, diff, log, or commit."
+                .to_string(),
+            parameters: json!({
+                "type": "object",
+                "properties": {
+                    "operation": { "type": "string", "enum": ["status", "diff", "log", "show", "blame", "commit"] },
+                    "staged": { "type": "boolean", "default": false, "description": "For 'diff': show staged changes instead of the working tree." },
+                    "path": { "type": "string", "description": "For 'diff'/'log'/'blame': limit to this path." },
+                    "max_count": { "type": "integer", "minimum": 1, "default": 20, "description": "For 'log': maximum number of commits." },
+                    "revision": { "type": "string", "description": "For 'show': the commit to show. Defaults to HEAD." },
+                    "message": { "type": "string", "description": "For 'commit': the commit message." },
+                    "paths": { "type": "array", "items": { "type": "string" }, "description": "For 'commit': paths to stage; omit to commit what's already staged." },
+                },
+                "required": ["operation"],
+            }),
+        }
+    }
+
+    fn requires_approval(&self) -> bool {
+        // Matches the git operations exposed: status/diff/log are
+        // read-only, commit mutates history, so only commit needs a
+        // human in the loop — but `requires_approval` is a property of
+        // the whole tool, not per-call, so the safer default wins.
+        true
+    }
+
+    async fn execute(&self, arguments: serde_json::Value) -> ToolResult {
+        let Some(operation) = arguments.get("operation").and_then(|v| v.as_str()) else {
+            return ToolResult::error("git requires a string 'operation' argument");
+        };
+
+        let result = match operation {
+            "status" => self.run(&["status", "--porcelain=v1", "--branch"]).await,
+            "diff" => {
+                let staged = arguments.get("staged").and_then(|v| v.as_bool()).unwrap_or(false);
+                let path = arguments.get("path").and_then(|v| v.as_str());
+                let mut args = vec!["diff"];
+                if staged {
+                    args.push("--staged");
+                }
+                if let Some(path) = path {
+                    args.push("--");
+                    args.push(path);
+                }
+                self.run(&args).await
+            }
+            "log" => {
+                let max_count = arguments.get("max_count").and_then(|v| v.as_u64()).unwrap_or(20).to_string();
+                let path = arguments.get("path").and_then(|v| v.as_str());
+                let mut args = vec!["log", "--format=%H %ad %an: %s", "--date=short", "-n", max_count.as_str()];
+                if let Some(path) = path {
+                    args.push("--");
+                    args.push(path);
+                }
+                self.run(&args).await
+            }
+            "show" => {
+                let revision = arguments.get("revision").and_then(|v| v.as_str()).unwrap_or("HEAD");
+                self.run(&["show", revision]).await
+            }
+            "blame" => {
+                let Some(path) = arguments.get("path").and_then(|v| v.as_str()) else {
+                    return ToolResult::error("git blame requires a string 'path' argument");
+                };
+                self.run(&["blame", "--line-porcelain", "--", path]).await
+            }
+            "commit" => {
+                let Some(message) = arguments.get("message").and_then(|v| v.as_str()) else {
+                    return ToolResult::error("git commit requires a string 'message' argument");
+                };
+                if let Some(paths) = arguments.get("paths").and_then(|v| v.as_array()) {
+                    let paths: Vec<&str> = paths.iter().filter_map(|v| v.as_str()).collect();
+                    if !paths.is_empty() {
+                        let mut add_args = vec!["add", "--"];
+                        add_args.extend(paths);
+                        if let Err(err) = self.run(&add_args).await {
+                            return ToolResult::error(err);
+                        }
+                    }
+                }
+                self.run(&["commit", "-m", message]).await
+            }
+            other => Err(format!("unknown operation '{other}', expected status/diff/log/commit")),
+        };
+
+        match result {
+            Ok(output) => ToolResult::ok(output),
+            Err(err) => ToolResult::error(err),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A temp dir with a git repo already initialized and one committed
+    /// file, so tests that need history (log, show, blame) have
+    /// something to read.
+    async fn test_repo() -> (tempfile::TempDir, Arc<Workspace>) {
+        let dir = tempfile::tempdir().expect("create temp dir");
+        let workspace = Arc::new(Workspace::new(dir.path()).expect("workspace root should be valid"));
+        let tool = GitTool::new(workspace.clone());
+
+        tool.run(&["init", "-q"]).await.expect("git init");
+        tool.run(&["config", "user.email", "test@example.com"]).await.expect("git config email");
+        tool.run(&["config", "user.name", "Test User"]).await.expect("git config name");
+        std::fs::write(dir.path().join("f.txt"), "line one\n").expect("write f.txt");
+        tool.run(&["add", "-A"]).await.expect("git add");
+        tool.run(&["commit", "-q", "-m", "initial commit"]).await.expect("git commit");
+
+        (dir, workspace)
+    }
+
+    #[tokio::test]
+    async fn test_execute_requires_the_operation_argument() {
+        let (_dir, workspace) = test_repo().await;
+        let tool = GitTool::new(workspace);
+        let result = tool.execute(json!({})).await;
+        assert!(result.is_error);
+    }
+
+    #[tokio::test]
+    async fn test_execute_status_reports_a_clean_tree() {
+        let (_dir, workspace) = test_repo().await;
+        let tool = GitTool::new(workspace);
+        let result = tool.execute(json!({ "operation": "status" })).await;
+        assert!(!result.is_error);
+        assert!(result.content.starts_with("##"));
+    }
+
+    #[tokio::test]
+    async fn test_execute_log_includes_the_committed_message() {
+        let (_dir, workspace) = test_repo().await;
+        let tool = GitTool::new(workspace);
+        let result = tool.execute(json!({ "operation": "log" })).await;
+        assert!(!result.is_error);
+        assert!(result.content.contains("initial commit"));
+    }
+
+    #[tokio::test]
+    async fn test_execute_diff_shows_an_unstaged_change() {
+        let (dir, workspace) = test_repo().await;
+        std::fs::write(dir.path().join("f.txt"), "line one\nline two\n").expect("write f.txt");
+        let tool = GitTool::new(workspace);
+        let result = tool.execute(json!({ "operation": "diff" })).await;
+        assert!(!result.is_error);
+        assert!(result.content.contains("line two"));
+    }
+
+    #[tokio::test]
+    async fn test_execute_blame_requires_the_path_argument() {
+        let (_dir, workspace) = test_repo().await;
+        let tool = GitTool::new(workspace);
+        let result = tool.execute(json!({ "operation": "blame" })).await;
+        assert!(result.is_error);
+        assert!(result.content.contains("path"));
+    }
+
+    #[tokio::test]
+    async fn test_execute_blame_attributes_the_committed_line() {
+        let (_dir, workspace) = test_repo().await;
+        let tool = GitTool::new(workspace);
+        let result = tool.execute(json!({ "operation": "blame", "path": "f.txt" })).await;
+        assert!(!result.is_error);
+        assert!(result.content.contains("Test User"));
+    }
+
+    #[tokio::test]
+    async fn test_execute_commit_requires_the_message_argument() {
+        let (_dir, workspace) = test_repo().await;
+        let tool = GitTool::new(workspace);
+        let result = tool.execute(json!({ "operation": "commit" })).await;
+        assert!(result.is_error);
+    }
+
+    #[tokio::test]
+    async fn test_execute_commit_stages_and_commits_given_paths() {
+        let (dir, workspace) = test_repo().await;
+        std::fs::write(dir.path().join("new.txt"), "new file\n").expect("write new.txt");
+        let tool = GitTool::new(workspace);
+
+        let result = tool
+            .execute(json!({ "operation": "commit", "message": "add new.txt", "paths": ["new.txt"] }))
+            .await;
+        assert!(!result.is_error);
+
+        let log = tool.execute(json!({ "operation": "log" })).await;
+        assert!(log.content.contains("add new.txt"));
+    }
+
+    #[tokio::test]
+    async fn test_execute_errors_for_an_unknown_operation() {
+        let (_dir, workspace) = test_repo().await;
+        let tool = GitTool::new(workspace);
+        let result = tool.execute(json!({ "operation": "bisect" })).await;
+        assert!(result.is_error);
+        assert!(result.content.contains("unknown operation"));
+    }
+}
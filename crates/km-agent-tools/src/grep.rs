@@ -0,0 +1,220 @@
+//! Regex search over the workspace, backed by the same `grep`/`ignore`
+//! crates ripgrep itself uses — gets `.gitignore` handling and fast
+//! recursive traversal for free instead of the model falling back to
+//! `bash grep -r` with flags that differ across platforms.
+
+use std::path::Path;
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use grep_regex::RegexMatcher;
+use grep_searcher::{Searcher, SearcherBuilder, Sink, SinkContext, SinkMatch};
+use ignore::overrides::OverrideBuilder;
+use ignore::WalkBuilder;
+use serde_json::json;
+
+use crate::tool::{Tool, ToolDefinition, ToolResult};
+use crate::workspace::Workspace;
+
+/// Caps how many matching lines a single call can return, independent of
+/// `max_results` the model passes in — a runaway pattern like `.` over a
+/// large tree shouldn't be able to blow past this regardless.
+const HARD_MAX_RESULTS: usize = 2000;
+
+pub struct GrepTool {
+    workspace: Arc<Workspace>,
+}
+
+impl GrepTool {
+    pub fn new(workspace: Arc<Workspace>) -> Self {
+        Self { workspace }
+    }
+}
+
+#[async_trait]
+impl Tool for GrepTool {
+    fn definition(&self) -> ToolDefinition {
+        ToolDefinition {
+            name: "grep".to_string(),
+            description: "Search the workspace for a regex or literal pattern, respecting \
+                .gitignore. Optionally restrict to files matching a glob and include \
+                surrounding context lines."
+                .to_string(),
+            parameters: json!({
+                "type": "object",
+                "properties": {
+                    "pattern": { "type": "string" },
+                    "path": { "type": "string", "description": "Directory or file to search, relative to the workspace root. Defaults to the root." },
+                    "glob": { "type": "string", "description": "Only search files matching this glob, e.g. \"*.rs\"." },
+                    "context_lines": { "type": "integer", "minimum": 0, "default": 0 },
+                    "max_results": { "type": "integer", "minimum": 1, "default": 200 },
+                },
+                "required": ["pattern"],
+            }),
+        }
+    }
+
+    async fn execute(&self, arguments: serde_json::Value) -> ToolResult {
+        let Some(pattern) = arguments.get("pattern").and_then(|v| v.as_str()) else {
+            return ToolResult::error("grep requires a string 'pattern' argument");
+        };
+        let path = arguments.get("path").and_then(|v| v.as_str()).unwrap_or(".");
+        let glob = arguments.get("glob").and_then(|v| v.as_str()).map(str::to_string);
+        let context_lines = arguments.get("context_lines").and_then(|v| v.as_u64()).unwrap_or(0) as usize;
+        let max_results =
+            (arguments.get("max_results").and_then(|v| v.as_u64()).unwrap_or(200) as usize).min(HARD_MAX_RESULTS);
+
+        let search_root = match self.workspace.resolve(path, true) {
+            Ok(resolved) => resolved,
+            Err(err) => return ToolResult::error(format!("cannot search '{path}': {err}")),
+        };
+        let workspace_root = self.workspace.root().to_path_buf();
+        let pattern = pattern.to_string();
+
+        match tokio::task::spawn_blocking(move || {
+            run_grep(&workspace_root, &search_root, &pattern, glob.as_deref(), context_lines, max_results)
+        })
+        .await
+        {
+            Ok(Ok(output)) if output.is_empty() => ToolResult::ok("no matches"),
+            Ok(Ok(output)) => ToolResult::ok(output),
+            Ok(Err(err)) => ToolResult::error(err),
+            Err(err) => ToolResult::error(format!("grep task panicked: {err}")),
+        }
+    }
+}
+
+fn run_grep(
+    workspace_root: &Path,
+    search_root: &Path,
+    pattern: &str,
+    glob: Option<&str>,
+    context_lines: usize,
+    max_results: usize,
+) -> Result<String, String> {
+    let matcher = RegexMatcher::new(pattern).map_err(|err| format!("invalid pattern: {err}"))?;
+
+    let mut walk_builder = WalkBuilder::new(search_root);
+    if let Some(glob) = glob {
+        let mut overrides = OverrideBuilder::new(workspace_root);
+        overrides.add(glob).map_err(|err| format!("invalid glob: {err}"))?;
+        walk_builder.overrides(overrides.build().map_err(|err| format!("invalid glob: {err}"))?);
+    }
+
+    let mut searcher = SearcherBuilder::new()
+        .line_number(true)
+        .before_context(context_lines)
+        .after_context(context_lines)
+        .build();
+
+    let mut output = String::new();
+    let mut count = 0usize;
+    for entry in walk_builder.build() {
+        if count >= max_results {
+            break;
+        }
+        let Ok(entry) = entry else { continue };
+        if !entry.file_type().is_some_and(|t| t.is_file()) {
+            continue;
+        }
+        let label = entry.path().strip_prefix(workspace_root).unwrap_or(entry.path()).display().to_string();
+        let remaining = max_results - count;
+        let mut sink = GrepSink { label: &label, output: &mut output, matched: 0, remaining };
+        let result = searcher.search_path(&matcher, entry.path(), &mut sink);
+        count += sink.matched;
+        if result.is_err() {
+            continue;
+        }
+    }
+    Ok(output)
+}
+
+/// Writes matched lines as `path:line:text` and context lines as
+/// `path-line-text` (ripgrep's own convention for telling the two apart
+/// at a glance), stopping once `remaining` matches have been written.
+struct GrepSink<'a> {
+    label: &'a str,
+    output: &'a mut String,
+    matched: usize,
+    remaining: usize,
+}
+
+impl Sink for GrepSink<'_> {
+    type Error = std::io::Error;
+
+    fn matched(&mut self, _searcher: &Searcher, mat: &SinkMatch<'_>) -> Result<bool, Self::Error> {
+        if self.matched >= self.remaining {
+            return Ok(false);
+        }
+        let line = String::from_utf8_lossy(mat.bytes());
+        let line_number = mat.line_number().unwrap_or(0);
+        self.output.push_str(&format!("{}:{line_number}:{line}", self.label));
+        self.matched += 1;
+        Ok(self.matched < self.remaining)
+    }
+
+    fn context(&mut self, _searcher: &Searcher, ctx: &SinkContext<'_>) -> Result<bool, Self::Error> {
+        let line = String::from_utf8_lossy(ctx.bytes());
+        let line_number = ctx.line_number().unwrap_or(0);
+        self.output.push_str(&format!("{}-{line_number}-{line}", self.label));
+        Ok(true)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_workspace() -> (tempfile::TempDir, Arc<Workspace>) {
+        let dir = tempfile::tempdir().expect("create temp dir");
+        std::fs::write(dir.path().join("a.rs"), "fn main() {\n    println!(\"hello\");\n}\n").expect("write a.rs");
+        std::fs::write(dir.path().join("b.txt"), "hello from a text file\n").expect("write b.txt");
+        let workspace = Arc::new(Workspace::new(dir.path()).expect("workspace root should be valid"));
+        (dir, workspace)
+    }
+
+    #[tokio::test]
+    async fn test_execute_finds_matches_across_files() {
+        let (_dir, workspace) = test_workspace();
+        let tool = GrepTool::new(workspace);
+        let result = tool.execute(json!({ "pattern": "hello" })).await;
+        assert!(!result.is_error);
+        assert!(result.content.contains("a.rs"));
+        assert!(result.content.contains("b.txt"));
+    }
+
+    #[tokio::test]
+    async fn test_execute_respects_glob_restriction() {
+        let (_dir, workspace) = test_workspace();
+        let tool = GrepTool::new(workspace);
+        let result = tool.execute(json!({ "pattern": "hello", "glob": "*.rs" })).await;
+        assert!(!result.is_error);
+        assert!(result.content.contains("a.rs"));
+        assert!(!result.content.contains("b.txt"));
+    }
+
+    #[tokio::test]
+    async fn test_execute_reports_no_matches_for_an_absent_pattern() {
+        let (_dir, workspace) = test_workspace();
+        let tool = GrepTool::new(workspace);
+        let result = tool.execute(json!({ "pattern": "definitely_not_present_anywhere" })).await;
+        assert!(!result.is_error);
+        assert_eq!(result.content, "no matches");
+    }
+
+    #[tokio::test]
+    async fn test_execute_errors_on_an_invalid_regex() {
+        let (_dir, workspace) = test_workspace();
+        let tool = GrepTool::new(workspace);
+        let result = tool.execute(json!({ "pattern": "(unclosed" })).await;
+        assert!(result.is_error);
+    }
+
+    #[tokio::test]
+    async fn test_execute_requires_the_pattern_argument() {
+        let (_dir, workspace) = test_workspace();
+        let tool = GrepTool::new(workspace);
+        let result = tool.execute(json!({})).await;
+        assert!(result.is_error);
+    }
+}
@@ -0,0 +1,61 @@
+//! km-agent-tools: the tool registry and built-in tools (file, shell, git,
+//! editor, ...) the agent loop can call. Built-in tools land incrementally
+//! on top of the `Tool`/`ToolRegistry` foundation here.
+//!
+//! Everything here is native-only (filesystem and process access), gated
+//! behind the `native` feature so this crate can still appear in a
+//! wasm32 dependency graph without pulling those in; see `km-llm` for the
+//! wasm-compatible provider layer.
+#![cfg(feature = "native")]
+
+pub mod apply_patch;
+pub mod bash;
+pub mod editor;
+pub mod dir;
+pub mod drop_tools;
+pub mod external_tool;
+pub mod file;
+pub mod fn_tool;
+pub mod front_matter;
+pub mod git;
+pub mod grep;
+pub mod mcp;
+pub mod mcp_server;
+pub mod memory;
+pub mod project_map;
+pub mod registry;
+pub mod session;
+pub mod todo;
+pub mod tool;
+pub mod tool_detail;
+pub mod tools;
+pub mod typed_tool;
+pub mod web_fetch;
+pub mod workspace;
+
+pub use apply_patch::ApplyPatchTool;
+pub use bash::{BashOutputTool, BashTool, JobRegistry, KillBashTool};
+pub use editor::EditorEditTool;
+pub use dir::ListDirTool;
+pub use drop_tools::DropToolsTool;
+pub use external_tool::ExternalTool;
+pub use file::{ReadFileTool, WriteFileTool};
+pub use fn_tool::FnTool;
+pub use front_matter::FrontMatterTool;
+pub use git::GitTool;
+pub use grep::GrepTool;
+pub use mcp::{McpClient, McpTool, McpTransport};
+pub use mcp_server::serve_stdio;
+pub use memory::MemoryTool;
+pub use project_map::ProjectMapTool;
+pub use registry::{
+    ActiveToolSet, ApprovalHook, TimeoutPolicy, ToolCatalog, ToolGroups, ToolRegistry, ToolStat, ToolUsageStats, TruncationPolicy,
+};
+pub use session::{Session, SessionError, SessionState};
+pub use todo::{TodoList, TodoReadTool, TodoWriteTool};
+pub use tool::{Tool, ToolDefinition, ToolResult};
+pub use tool_detail::GetToolDetailTool;
+pub use tools::all_tools;
+pub use typed_tool::{schema_for, TypedTool};
+pub use web_fetch::WebFetchTool;
+pub use workspace::{Workspace, WorkspaceError};
@@ -0,0 +1,338 @@
+//! Minimal MCP (Model Context Protocol) client: spawns a server over
+//! stdio, does the `initialize`/`tools/list` handshake, and wraps each
+//! remote tool in a [`Tool`] that proxies `execute()` to a `tools/call`
+//! request, so [`crate::registry::ToolRegistry::register_mcp_server`]
+//! can add a whole server's tools in one call.
+//!
+//! Only the stdio transport is implemented. SSE needs a persistent
+//! event stream plus a separate POST channel, which doesn't fit this
+//! module's single request/response loop over one pair of pipes — pass
+//! [`McpTransport::Sse`] and [`McpClient::connect`] returns a clear error
+//! rather than silently doing nothing.
+
+use std::process::Stdio;
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use serde_json::{json, Value};
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::process::{Child, ChildStdin, ChildStdout};
+use tokio::sync::Mutex;
+
+use crate::tool::{Tool, ToolDefinition, ToolResult};
+
+/// How to reach an MCP server.
+pub enum McpTransport {
+    /// Spawn `command` with `args` and speak JSON-RPC over its stdio.
+    Stdio { command: String, args: Vec<String> },
+    /// Not yet implemented — see the module doc comment.
+    Sse { url: String },
+}
+
+struct McpClientInner {
+    #[allow(dead_code)] // kept alive so the server process isn't reaped
+    child: Child,
+    stdin: ChildStdin,
+    stdout: BufReader<ChildStdout>,
+    next_id: u64,
+}
+
+/// A connection to one MCP server. Requests are serialized through an
+/// internal lock rather than correlated by id against out-of-order
+/// responses, since this client only ever has one request in flight at a
+/// time.
+#[derive(Clone)]
+pub struct McpClient {
+    inner: Arc<Mutex<McpClientInner>>,
+}
+
+impl McpClient {
+    pub async fn connect(transport: McpTransport) -> Result<Self, String> {
+        let (command, args) = match transport {
+            McpTransport::Stdio { command, args } => (command, args),
+            McpTransport::Sse { url } => {
+                return Err(format!(
+                    "MCP SSE transport is not implemented; cannot connect to '{url}' — use McpTransport::Stdio"
+                ))
+            }
+        };
+
+        let mut child = tokio::process::Command::new(&command)
+            .args(&args)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .spawn()
+            .map_err(|err| format!("failed to spawn MCP server '{command}': {err}"))?;
+
+        let stdin = child.stdin.take().ok_or("MCP server spawned without a stdin pipe")?;
+        let stdout = BufReader::new(child.stdout.take().ok_or("MCP server spawned without a stdout pipe")?);
+
+        let client = Self { inner: Arc::new(Mutex::new(McpClientInner { child, stdin, stdout, next_id: 1 })) };
+
+        client
+            .request(
+                "initialize",
+                json!({
+                    "protocolVersion": "2024-11-05",
+                    "capabilities": {},
+                    "clientInfo": { "name": "km", "version": env!("CARGO_PKG_VERSION") },
+                }),
+            )
+            .await?;
+        client.notify("notifications/initialized", json!({})).await?;
+
+        Ok(client)
+    }
+
+    async fn request(&self, method: &str, params: Value) -> Result<Value, String> {
+        let mut inner = self.inner.lock().await;
+        let id = inner.next_id;
+        inner.next_id += 1;
+
+        let payload = json!({ "jsonrpc": "2.0", "id": id, "method": method, "params": params });
+        let line = format!("{payload}\n");
+        inner.stdin.write_all(line.as_bytes()).await.map_err(|err| err.to_string())?;
+        inner.stdin.flush().await.map_err(|err| err.to_string())?;
+
+        let mut raw_line = String::new();
+        loop {
+            raw_line.clear();
+            let bytes_read = inner.stdout.read_line(&mut raw_line).await.map_err(|err| err.to_string())?;
+            if bytes_read == 0 {
+                return Err(format!("MCP server closed its stdout before responding to '{method}'"));
+            }
+            let trimmed = raw_line.trim();
+            if trimmed.is_empty() {
+                continue;
+            }
+            let response: Value = serde_json::from_str(trimmed).map_err(|err| format!("invalid MCP response: {err}"))?;
+            // Skip anything that isn't the response to this request, e.g.
+            // a server-initiated notification sharing the same stdout.
+            if response.get("id").and_then(Value::as_u64) != Some(id) {
+                continue;
+            }
+            if let Some(error) = response.get("error") {
+                return Err(format!("MCP error calling '{method}': {error}"));
+            }
+            return Ok(response.get("result").cloned().unwrap_or(Value::Null));
+        }
+    }
+
+    async fn notify(&self, method: &str, params: Value) -> Result<(), String> {
+        let mut inner = self.inner.lock().await;
+        let payload = json!({ "jsonrpc": "2.0", "method": method, "params": params });
+        let line = format!("{payload}\n");
+        inner.stdin.write_all(line.as_bytes()).await.map_err(|err| err.to_string())?;
+        inner.stdin.flush().await.map_err(|err| err.to_string())
+    }
+
+    /// The server's advertised tools, as raw `tools/list` entries
+    /// (`name`/`description`/`inputSchema`).
+    pub async fn list_tools(&self) -> Result<Vec<Value>, String> {
+        let result = self.request("tools/list", json!({})).await?;
+        Ok(result.get("tools").and_then(|v| v.as_array()).cloned().unwrap_or_default())
+    }
+
+    async fn call_tool(&self, name: &str, arguments: Value) -> Result<Value, String> {
+        self.request("tools/call", json!({ "name": name, "arguments": arguments })).await
+    }
+}
+
+/// One tool discovered on a remote MCP server, proxied through
+/// [`McpClient::call_tool`].
+pub struct McpTool {
+    client: McpClient,
+    name: String,
+    description: String,
+    parameters: Value,
+}
+
+impl McpTool {
+    fn new(client: McpClient, listing: &Value) -> Option<Self> {
+        let name = listing.get("name").and_then(|v| v.as_str())?.to_string();
+        let description = listing.get("description").and_then(|v| v.as_str()).unwrap_or_default().to_string();
+        let parameters = listing.get("inputSchema").cloned().unwrap_or_else(|| json!({ "type": "object" }));
+        Some(Self { client, name, description, parameters })
+    }
+}
+
+#[async_trait]
+impl Tool for McpTool {
+    fn definition(&self) -> ToolDefinition {
+        ToolDefinition {
+            name: self.name.clone(),
+            description: self.description.clone(),
+            parameters: self.parameters.clone(),
+        }
+    }
+
+    // A remote MCP server can implement a tool that does anything —
+    // write files, hit the network, run commands — and this client has
+    // no way to inspect what a given call will actually do, so every
+    // MCP tool is treated as approval-worthy, the same stance taken for
+    // GitTool's commit operation.
+    fn requires_approval(&self) -> bool {
+        true
+    }
+
+    async fn execute(&self, arguments: Value) -> ToolResult {
+        match self.client.call_tool(&self.name, arguments).await {
+            Ok(result) => content_to_tool_result(&result),
+            Err(err) => ToolResult::error(format!("MCP call to '{}' failed: {err}", self.name)),
+        }
+    }
+}
+
+/// Turns an MCP-shaped `{content: [{type, text}], isError}` result into a
+/// [`ToolResult`] — shared with [`crate::external_tool::ExternalTool`],
+/// which deliberately reuses this shape for its own simpler protocol
+/// rather than inventing a second one.
+pub(crate) fn content_to_tool_result(result: &Value) -> ToolResult {
+    let is_error = result.get("isError").and_then(Value::as_bool).unwrap_or(false);
+    let text = result
+        .get("content")
+        .and_then(|v| v.as_array())
+        .map(|blocks| {
+            blocks
+                .iter()
+                .filter_map(|block| block.get("text").and_then(Value::as_str))
+                .collect::<Vec<_>>()
+                .join("\n")
+        })
+        .unwrap_or_default();
+    if is_error {
+        ToolResult::error(text)
+    } else {
+        ToolResult::ok(text)
+    }
+}
+
+/// Connects to an MCP server and returns every tool it advertises,
+/// ready to hand to [`crate::registry::ToolRegistry::register`] one at a
+/// time.
+pub async fn discover_tools(transport: McpTransport) -> Result<Vec<McpTool>, String> {
+    let client = McpClient::connect(transport).await?;
+    let listings = client.list_tools().await?;
+    Ok(listings.iter().filter_map(|listing| McpTool::new(client.clone(), listing)).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A minimal JSON-RPC-over-stdio MCP server, speaking just enough of
+    /// the protocol for [`McpClient::connect`]/[`discover_tools`] to
+    /// exercise against something real rather than mocking the client
+    /// itself. Advertises one "echo" tool that returns its `text`
+    /// argument, optionally as an error.
+    const FAKE_SERVER: &str = r#"
+import sys, json
+
+for line in sys.stdin:
+    line = line.strip()
+    if not line:
+        continue
+    req = json.loads(line)
+    method = req.get("method")
+    req_id = req.get("id")
+
+    if method == "initialize":
+        print(json.dumps({"jsonrpc": "2.0", "id": req_id, "result": {}}))
+    elif method == "notifications/initialized":
+        pass
+    elif method == "tools/list":
+        tools = [{
+            "name": "echo",
+            "description": "echoes its text argument",
+            "inputSchema": {"type": "object", "properties": {"text": {"type": "string"}}, "required": ["text"]},
+        }]
+        print(json.dumps({"jsonrpc": "2.0", "id": req_id, "result": {"tools": tools}}))
+    elif method == "tools/call":
+        args = req.get("params", {}).get("arguments", {})
+        text = args.get("text", "")
+        is_error = text == "fail"
+        result = {"content": [{"type": "text", "text": text}], "isError": is_error}
+        print(json.dumps({"jsonrpc": "2.0", "id": req_id, "result": result}))
+    elif req_id is not None:
+        print(json.dumps({"jsonrpc": "2.0", "id": req_id, "error": {"message": f"unknown method {method}"}}))
+    sys.stdout.flush()
+"#;
+
+    fn fake_server_transport(script_path: &std::path::Path) -> McpTransport {
+        McpTransport::Stdio {
+            command: "python3".to_string(),
+            args: vec![script_path.display().to_string()],
+        }
+    }
+
+    #[tokio::test]
+    async fn test_connect_performs_the_initialize_handshake() {
+        let dir = tempfile::tempdir().expect("create temp dir");
+        let script_path = dir.path().join("fake_mcp_server.py");
+        std::fs::write(&script_path, FAKE_SERVER).expect("write fake server script");
+
+        let client = McpClient::connect(fake_server_transport(&script_path)).await;
+        assert!(client.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_connect_rejects_the_sse_transport() {
+        let result = McpClient::connect(McpTransport::Sse { url: "http://example.invalid".to_string() }).await;
+        let Err(err) = result else { panic!("SSE transport should be rejected") };
+        assert!(err.contains("not implemented"));
+    }
+
+    #[tokio::test]
+    async fn test_discover_tools_returns_the_servers_advertised_tools() {
+        let dir = tempfile::tempdir().expect("create temp dir");
+        let script_path = dir.path().join("fake_mcp_server.py");
+        std::fs::write(&script_path, FAKE_SERVER).expect("write fake server script");
+
+        let tools = discover_tools(fake_server_transport(&script_path)).await.expect("discovery should succeed");
+        assert_eq!(tools.len(), 1);
+        let definition = tools[0].definition();
+        assert_eq!(definition.name, "echo");
+        assert_eq!(definition.description, "echoes its text argument");
+    }
+
+    #[tokio::test]
+    async fn test_mcp_tool_execute_proxies_a_successful_call() {
+        let dir = tempfile::tempdir().expect("create temp dir");
+        let script_path = dir.path().join("fake_mcp_server.py");
+        std::fs::write(&script_path, FAKE_SERVER).expect("write fake server script");
+
+        let tools = discover_tools(fake_server_transport(&script_path)).await.expect("discovery should succeed");
+        let result = tools[0].execute(json!({ "text": "hello from mcp" })).await;
+        assert!(!result.is_error);
+        assert_eq!(result.content, "hello from mcp");
+    }
+
+    #[tokio::test]
+    async fn test_mcp_tool_execute_proxies_a_server_reported_error() {
+        let dir = tempfile::tempdir().expect("create temp dir");
+        let script_path = dir.path().join("fake_mcp_server.py");
+        std::fs::write(&script_path, FAKE_SERVER).expect("write fake server script");
+
+        let tools = discover_tools(fake_server_transport(&script_path)).await.expect("discovery should succeed");
+        let result = tools[0].execute(json!({ "text": "fail" })).await;
+        assert!(result.is_error);
+        assert_eq!(result.content, "fail");
+    }
+
+    #[test]
+    fn test_content_to_tool_result_joins_multiple_text_blocks() {
+        let value = json!({ "content": [{ "type": "text", "text": "one" }, { "type": "text", "text": "two" }] });
+        let result = content_to_tool_result(&value);
+        assert!(!result.is_error);
+        assert_eq!(result.content, "one\ntwo");
+    }
+
+    #[test]
+    fn test_content_to_tool_result_honors_is_error() {
+        let value = json!({ "content": [{ "type": "text", "text": "boom" }], "isError": true });
+        let result = content_to_tool_result(&value);
+        assert!(result.is_error);
+        assert_eq!(result.content, "boom");
+    }
+}
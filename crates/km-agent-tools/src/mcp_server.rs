@@ -0,0 +1,121 @@
+//! Serves a [`ToolRegistry`]'s tools over MCP stdio, the mirror image of
+//! [`crate::mcp`]'s client — lets another MCP host (an IDE, Claude
+//! Desktop, a different agent frontend) call bash/editor/project-map/etc.
+//! without embedding this crate directly.
+//!
+//! Speaks the same newline-delimited JSON-RPC framing [`crate::mcp`]'s
+//! client uses, read from stdin and written to stdout. Call
+//! [`serve_stdio`] from a small binary that constructs a [`ToolRegistry`]
+//! (sandboxed to whatever workspace that binary wants to expose) and
+//! hands it off; this module owns nothing about process lifecycle beyond
+//! reading until stdin closes.
+
+use serde_json::{json, Value};
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+
+use crate::registry::ToolRegistry;
+use crate::tool::ToolResult;
+
+/// Runs the MCP server loop against `registry`, reading JSON-RPC
+/// requests from `stdin` and writing responses to `stdout` until stdin
+/// closes. Unrecognized methods get a JSON-RPC "method not found" error
+/// rather than being ignored, so a host can tell a typo from a dropped
+/// connection.
+pub async fn serve_stdio(registry: ToolRegistry) -> Result<(), String> {
+    let stdin = tokio::io::stdin();
+    let mut stdout = tokio::io::stdout();
+    let mut lines = BufReader::new(stdin).lines();
+
+    while let Some(line) = lines.next_line().await.map_err(|err| err.to_string())? {
+        let trimmed = line.trim();
+        if trimmed.is_empty() {
+            continue;
+        }
+        let request: Value = match serde_json::from_str(trimmed) {
+            Ok(request) => request,
+            Err(err) => {
+                write_line(&mut stdout, &parse_error_response(&err.to_string())).await?;
+                continue;
+            }
+        };
+
+        let id = request.get("id").cloned();
+        let method = request.get("method").and_then(|v| v.as_str()).unwrap_or_default();
+        let params = request.get("params").cloned().unwrap_or(Value::Null);
+
+        // A request without an "id" is a notification — no response is
+        // sent either way, but there's nothing this server needs to do
+        // in reaction to one (e.g. "notifications/initialized") either.
+        let Some(id) = id else {
+            continue;
+        };
+
+        let response = match method {
+            "initialize" => ok_response(id, initialize_result()),
+            "tools/list" => ok_response(id, tools_list_result(&registry)),
+            "tools/call" => match handle_tools_call(&registry, &params).await {
+                Ok(result) => ok_response(id, result),
+                Err(message) => error_response(id, -32602, &message),
+            },
+            other => error_response(id, -32601, &format!("method not found: {other}")),
+        };
+        write_line(&mut stdout, &response).await?;
+    }
+
+    Ok(())
+}
+
+fn initialize_result() -> Value {
+    json!({
+        "protocolVersion": "2024-11-05",
+        "capabilities": { "tools": {} },
+        "serverInfo": { "name": "km-agent-tools", "version": env!("CARGO_PKG_VERSION") },
+    })
+}
+
+fn tools_list_result(registry: &ToolRegistry) -> Value {
+    let tools: Vec<Value> = registry
+        .definitions()
+        .into_iter()
+        .map(|definition| {
+            json!({
+                "name": definition.name,
+                "description": definition.description,
+                "inputSchema": definition.parameters,
+            })
+        })
+        .collect();
+    json!({ "tools": tools })
+}
+
+async fn handle_tools_call(registry: &ToolRegistry, params: &Value) -> Result<Value, String> {
+    let name = params.get("name").and_then(|v| v.as_str()).ok_or("tools/call requires a 'name' parameter")?;
+    let arguments = params.get("arguments").cloned().unwrap_or(json!({}));
+    let result = registry.execute(name, arguments).await;
+    Ok(tool_result_to_mcp(result))
+}
+
+fn tool_result_to_mcp(result: ToolResult) -> Value {
+    json!({
+        "content": [{ "type": "text", "text": result.content }],
+        "isError": result.is_error,
+    })
+}
+
+fn ok_response(id: Value, result: Value) -> Value {
+    json!({ "jsonrpc": "2.0", "id": id, "result": result })
+}
+
+fn error_response(id: Value, code: i64, message: &str) -> Value {
+    json!({ "jsonrpc": "2.0", "id": id, "error": { "code": code, "message": message } })
+}
+
+fn parse_error_response(message: &str) -> Value {
+    json!({ "jsonrpc": "2.0", "id": null, "error": { "code": -32700, "message": format!("parse error: {message}") } })
+}
+
+async fn write_line(stdout: &mut (impl AsyncWriteExt + Unpin), value: &Value) -> Result<(), String> {
+    let line = format!("{value}\n");
+    stdout.write_all(line.as_bytes()).await.map_err(|err| err.to_string())?;
+    stdout.flush().await.map_err(|err| err.to_string())
+}
@@ -0,0 +1,116 @@
+//! Persistent key-value notes scoped to a workspace, stored at
+//! `.km/memory.json` — unlike [`crate::todo::TodoList`], which only
+//! lives for one session, this survives across separate conversations
+//! against the same project.
+
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use serde_json::{json, Value};
+use tokio::sync::Mutex;
+
+use crate::tool::{Tool, ToolDefinition, ToolResult};
+use crate::workspace::Workspace;
+
+async fn load(path: &PathBuf) -> BTreeMap<String, Value> {
+    match tokio::fs::read_to_string(path).await {
+        Ok(content) => serde_json::from_str(&content).unwrap_or_default(),
+        Err(_) => BTreeMap::new(),
+    }
+}
+
+async fn save(path: &PathBuf, notes: &BTreeMap<String, Value>) -> Result<(), String> {
+    if let Some(parent) = path.parent() {
+        tokio::fs::create_dir_all(parent).await.map_err(|err| err.to_string())?;
+    }
+    let content = serde_json::to_string_pretty(notes).map_err(|err| err.to_string())?;
+    tokio::fs::write(path, content).await.map_err(|err| err.to_string())
+}
+
+/// Gets, sets, lists, or deletes notes in the workspace's
+/// `.km/memory.json`. Guarded by an in-process lock — concurrent writers
+/// from other processes can still race, same as any other file in the
+/// workspace.
+pub struct MemoryTool {
+    path: PathBuf,
+    lock: Mutex<()>,
+}
+
+impl MemoryTool {
+    pub fn new(workspace: Arc<Workspace>) -> Self {
+        Self { path: workspace.root().join(".km").join("memory.json"), lock: Mutex::new(()) }
+    }
+}
+
+#[async_trait]
+impl Tool for MemoryTool {
+    fn definition(&self) -> ToolDefinition {
+        ToolDefinition {
+            name: "memory".to_string(),
+            description: "Get, set, list, or delete persistent notes scoped to this \
+                workspace, stored in .km/memory.json and available across conversations."
+                .to_string(),
+            parameters: json!({
+                "type": "object",
+                "properties": {
+                    "action": { "type": "string", "enum": ["get", "set", "list", "delete"] },
+                    "key": { "type": "string" },
+                    "value": {},
+                },
+                "required": ["action"],
+            }),
+        }
+    }
+
+    async fn execute(&self, arguments: serde_json::Value) -> ToolResult {
+        let Some(action) = arguments.get("action").and_then(|v| v.as_str()) else {
+            return ToolResult::error("memory requires a string 'action' argument");
+        };
+        let _guard = self.lock.lock().await;
+        let mut notes = load(&self.path).await;
+
+        match action {
+            "list" => match serde_json::to_string_pretty(&notes) {
+                Ok(json) => ToolResult::ok(json),
+                Err(err) => ToolResult::error(format!("failed to serialize notes: {err}")),
+            },
+            "get" => {
+                let Some(key) = arguments.get("key").and_then(|v| v.as_str()) else {
+                    return ToolResult::error("memory 'get' requires a string 'key' argument");
+                };
+                match notes.get(key) {
+                    Some(value) => ToolResult::ok(value.to_string()),
+                    None => ToolResult::error(format!("no note stored under key '{key}'")),
+                }
+            }
+            "set" => {
+                let Some(key) = arguments.get("key").and_then(|v| v.as_str()) else {
+                    return ToolResult::error("memory 'set' requires a string 'key' argument");
+                };
+                let Some(value) = arguments.get("value") else {
+                    return ToolResult::error("memory 'set' requires a 'value' argument");
+                };
+                notes.insert(key.to_string(), value.clone());
+                match save(&self.path, &notes).await {
+                    Ok(()) => ToolResult::ok(format!("saved note '{key}'")),
+                    Err(err) => ToolResult::error(format!("failed to save notes: {err}")),
+                }
+            }
+            "delete" => {
+                let Some(key) = arguments.get("key").and_then(|v| v.as_str()) else {
+                    return ToolResult::error("memory 'delete' requires a string 'key' argument");
+                };
+                if notes.remove(key).is_none() {
+                    return ToolResult::error(format!("no note stored under key '{key}'"));
+                }
+                match save(&self.path, &notes).await {
+                    Ok(()) => ToolResult::ok(format!("deleted note '{key}'")),
+                    Err(err) => ToolResult::error(format!("failed to save notes: {err}")),
+                }
+            }
+            other => ToolResult::error(format!("unknown action '{other}', expected get/set/list/delete")),
+        }
+    }
+}
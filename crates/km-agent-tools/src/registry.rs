@@ -0,0 +1,703 @@
+//! Looks up and runs tools by name, and enforces execution timeouts so a
+//! hung tool (a shell command that never exits, a network call with no
+//! deadline) can't stall the whole agent loop indefinitely.
+
+use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use async_trait::async_trait;
+
+use crate::mcp::{discover_tools, McpTransport};
+use crate::tool::{Tool, ToolDefinition, ToolResult};
+use crate::workspace::Workspace;
+
+/// Asks a human (or other out-of-band policy) whether a dangerous tool
+/// call should run. Set on a [`ToolRegistry`] via
+/// [`ToolRegistry::with_approval_hook`]; a tool opts into being asked
+/// about by returning `true` from [`Tool::requires_approval`].
+#[async_trait]
+pub trait ApprovalHook: Send + Sync {
+    async fn approve(&self, definition: &ToolDefinition, arguments: &serde_json::Value) -> bool;
+}
+
+/// How long a tool execution is allowed to run before
+/// [`ToolRegistry::execute`] gives up and returns a timeout
+/// [`ToolResult`]. `per_tool` overrides `default` for specific tool
+/// names, e.g. a build/test tool that legitimately needs longer than the
+/// default budget.
+#[derive(Debug, Clone)]
+pub struct TimeoutPolicy {
+    pub default: Duration,
+    pub per_tool: HashMap<String, Duration>,
+}
+
+impl Default for TimeoutPolicy {
+    fn default() -> Self {
+        Self {
+            default: Duration::from_secs(60),
+            per_tool: HashMap::new(),
+        }
+    }
+}
+
+impl TimeoutPolicy {
+    pub fn with_tool_timeout(mut self, tool_name: impl Into<String>, timeout: Duration) -> Self {
+        self.per_tool.insert(tool_name.into(), timeout);
+        self
+    }
+
+    fn timeout_for(&self, tool_name: &str) -> Duration {
+        self.per_tool.get(tool_name).copied().unwrap_or(self.default)
+    }
+}
+
+/// How large a tool result's content may get before
+/// [`ToolRegistry::execute`] truncates it, so one chatty tool call (a
+/// `find` over a huge tree, a verbose build log) doesn't blow the
+/// context window on its own. A truncated result's content still ends
+/// with the note appended by [`TruncationPolicy::apply`], so the model
+/// knows to ask again with a later `offset` rather than assuming it saw
+/// everything.
+#[derive(Debug, Clone, Copy)]
+pub struct TruncationPolicy {
+    pub max_chars: usize,
+}
+
+impl Default for TruncationPolicy {
+    fn default() -> Self {
+        Self { max_chars: 20_000 }
+    }
+}
+
+impl TruncationPolicy {
+    /// Truncates `result.content` to `max_chars`, leaving it untouched if
+    /// it already fits. A tool that supports pagination should accept an
+    /// `offset` argument; the appended note tells the model the value to
+    /// pass for the next page.
+    fn apply(&self, result: ToolResult) -> ToolResult {
+        if result.content.chars().count() <= self.max_chars {
+            return result;
+        }
+        let truncated: String = result.content.chars().take(self.max_chars).collect();
+        let remaining = result.content.chars().count() - self.max_chars;
+        ToolResult {
+            content: format!(
+                "{truncated}\n\n[... output truncated, {remaining} more characters. \
+                 Call again with `offset: {}` to continue reading.]",
+                self.max_chars
+            ),
+            is_error: result.is_error,
+        }
+    }
+}
+
+/// Checks `arguments` against `definition.parameters` before a tool ever
+/// runs, so a model that hallucinates a missing field or wrong type gets
+/// a precise error back immediately instead of the tool failing deeper
+/// in with a confusing panic or a misleading partial result.
+fn validate_arguments(definition: &ToolDefinition, arguments: &serde_json::Value) -> Result<(), String> {
+    let validator = jsonschema::validator_for(&definition.parameters)
+        .map_err(|err| format!("tool '{}' has an invalid parameter schema: {err}", definition.name))?;
+    let messages: Vec<String> = validator.iter_errors(arguments).map(|error| error.to_string()).collect();
+    if !messages.is_empty() {
+        return Err(messages.join("; "));
+    }
+    Ok(())
+}
+
+/// Shared, read-through view of every tool definition registered so far,
+/// handed to [`crate::tool_detail::GetToolDetailTool`] so it can answer
+/// "what does tool X actually take" without holding the registry itself
+/// (which would need to hold the detail tool, which would need to hold
+/// the registry...). [`ToolRegistry::register`] keeps it in sync.
+#[derive(Default, Clone)]
+pub struct ToolCatalog {
+    definitions: Arc<Mutex<HashMap<String, ToolDefinition>>>,
+}
+
+impl ToolCatalog {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn insert(&self, definition: ToolDefinition) {
+        self.definitions.lock().expect("tool catalog lock poisoned").insert(definition.name.clone(), definition);
+    }
+
+    pub fn get(&self, name: &str) -> Option<ToolDefinition> {
+        self.definitions.lock().expect("tool catalog lock poisoned").get(name).cloned()
+    }
+
+    fn remove(&self, name: &str) {
+        self.definitions.lock().expect("tool catalog lock poisoned").remove(name);
+    }
+}
+
+/// Tracks which registered tools are "picked" — i.e. have their schema
+/// included in [`ToolRegistry::definitions`]. Every tool starts picked,
+/// matching the registry's existing behavior of advertising everything
+/// it holds; [`ToolRegistry::unpick`]/[`crate::drop_tools::DropToolsTool`]
+/// let a long session drop ones it's done with to shrink the schema list
+/// sent with every request, without losing the tool itself — it can
+/// still be called, and re-picked, at any time.
+#[derive(Default, Clone)]
+pub struct ActiveToolSet {
+    picked: Arc<Mutex<HashSet<String>>>,
+}
+
+impl ActiveToolSet {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn pick(&self, name: String) {
+        self.picked.lock().expect("active tool set lock poisoned").insert(name);
+    }
+
+    /// Returns whether `name` was picked before this call.
+    pub fn unpick(&self, name: &str) -> bool {
+        self.picked.lock().expect("active tool set lock poisoned").remove(name)
+    }
+
+    fn contains(&self, name: &str) -> bool {
+        self.picked.lock().expect("active tool set lock poisoned").contains(name)
+    }
+}
+
+/// Tracks which group each registered tool belongs to, so a host can
+/// pick/deny a whole group — "fs", "git", "web", ... — in one call
+/// instead of naming every tool in it. A tool not registered through
+/// [`ToolRegistry::register_in_group`] belongs to no group and is only
+/// reachable by [`ToolRegistry::pick`]/[`ToolRegistry::unpick`] directly.
+#[derive(Default, Clone)]
+pub struct ToolGroups {
+    members: Arc<Mutex<HashMap<String, HashSet<String>>>>,
+}
+
+impl ToolGroups {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn insert(&self, group: &str, tool_name: String) {
+        self.members.lock().expect("tool groups lock poisoned").entry(group.to_string()).or_default().insert(tool_name);
+    }
+
+    /// The tool names registered under `group`, or an empty set for an
+    /// unknown group.
+    pub fn members(&self, group: &str) -> HashSet<String> {
+        self.members.lock().expect("tool groups lock poisoned").get(group).cloned().unwrap_or_default()
+    }
+
+    /// Drops `tool_name` from whichever group it's in, if any — called by
+    /// [`ToolRegistry::unregister`] so a removed tool doesn't linger as a
+    /// dangling member of [`ToolRegistry::allow_group`]/[`ToolRegistry::deny_group`].
+    fn remove(&self, tool_name: &str) {
+        let mut members = self.members.lock().expect("tool groups lock poisoned");
+        for group in members.values_mut() {
+            group.remove(tool_name);
+        }
+    }
+}
+
+/// Running counters for one tool, as tracked by [`ToolUsageStats`] and
+/// returned by [`ToolRegistry::stats`]. `error_rate`/`avg_latency` are
+/// computed on read rather than stored, so they can't drift out of sync
+/// with `calls`.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ToolStat {
+    pub calls: u64,
+    pub errors: u64,
+    pub total_latency: Duration,
+    pub output_bytes: u64,
+}
+
+impl ToolStat {
+    pub fn error_rate(&self) -> f64 {
+        if self.calls == 0 {
+            0.0
+        } else {
+            self.errors as f64 / self.calls as f64
+        }
+    }
+
+    pub fn avg_latency(&self) -> Duration {
+        if self.calls == 0 {
+            Duration::ZERO
+        } else {
+            self.total_latency / self.calls as u32
+        }
+    }
+}
+
+/// Per-tool invocation counts, error rates, latency, and output size,
+/// accumulated by [`ToolRegistry::execute`] across every call this
+/// registry has run. Exists so operators can see which tools agents
+/// actually use and which keep failing, not to drive any behavior —
+/// nothing in this crate reads its own stats back.
+#[derive(Default, Clone)]
+pub struct ToolUsageStats {
+    per_tool: Arc<Mutex<HashMap<String, ToolStat>>>,
+}
+
+impl ToolUsageStats {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn record(&self, tool_name: &str, latency: Duration, is_error: bool, output_bytes: usize) {
+        let mut per_tool = self.per_tool.lock().expect("tool usage stats lock poisoned");
+        let stat = per_tool.entry(tool_name.to_string()).or_default();
+        stat.calls += 1;
+        if is_error {
+            stat.errors += 1;
+        }
+        stat.total_latency += latency;
+        stat.output_bytes += output_bytes as u64;
+    }
+
+    /// A point-in-time copy of every tool's counters, keyed by tool name.
+    pub fn snapshot(&self) -> HashMap<String, ToolStat> {
+        self.per_tool.lock().expect("tool usage stats lock poisoned").clone()
+    }
+}
+
+/// Caches [`ToolResult`]s for tools that opt in via [`Tool::cacheable`],
+/// keyed by tool name plus the exact arguments passed, so a repeated
+/// identical read-only call (read a file, fetch a URL) returns instantly
+/// instead of redoing the work. Entries older than `ttl` are treated as
+/// misses and overwritten on the next call.
+///
+/// Disabled by default — [`ToolRegistry::with_result_cache`] opts a whole
+/// registry in, on top of each tool's own opt-in, so a host that wants
+/// no caching at all doesn't need every built-in tool to agree.
+///
+/// A cache hit means the model asked for something it already has the
+/// answer to, which is exactly the signal a loop detector watches for;
+/// there's no `LoopDetector` in this crate yet to hand that to (see
+/// [`ToolRegistry::stats`] in the meantime for a count of repeats).
+#[derive(Clone)]
+struct ToolResultCache {
+    entries: Arc<Mutex<HashMap<String, (Instant, ToolResult)>>>,
+    ttl: Duration,
+}
+
+impl ToolResultCache {
+    fn new(ttl: Duration) -> Self {
+        Self { entries: Arc::new(Mutex::new(HashMap::new())), ttl }
+    }
+
+    fn key(tool_name: &str, arguments: &serde_json::Value) -> String {
+        format!("{tool_name}:{arguments}")
+    }
+
+    fn get(&self, key: &str) -> Option<ToolResult> {
+        let entries = self.entries.lock().expect("tool result cache lock poisoned");
+        let (inserted, result) = entries.get(key)?;
+        if inserted.elapsed() > self.ttl {
+            return None;
+        }
+        Some(result.clone())
+    }
+
+    fn insert(&self, key: String, result: ToolResult) {
+        self.entries.lock().expect("tool result cache lock poisoned").insert(key, (Instant::now(), result));
+    }
+}
+
+/// Holds the tools available to an agent loop and the timeout/truncation
+/// policies applied when running them. Cheap to clone: tools are stored
+/// behind `Arc`, so handing a registry to more than one concurrent loop
+/// doesn't duplicate them.
+#[derive(Default, Clone)]
+pub struct ToolRegistry {
+    tools: HashMap<String, Arc<dyn Tool>>,
+    timeouts: TimeoutPolicy,
+    truncation: TruncationPolicy,
+    approval_hook: Option<Arc<dyn ApprovalHook>>,
+    workspace: Option<Arc<Workspace>>,
+    catalog: ToolCatalog,
+    active: ActiveToolSet,
+    groups: ToolGroups,
+    stats: ToolUsageStats,
+    cache: Option<ToolResultCache>,
+}
+
+impl ToolRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_timeout_policy(mut self, timeouts: TimeoutPolicy) -> Self {
+        self.timeouts = timeouts;
+        self
+    }
+
+    pub fn with_truncation_policy(mut self, truncation: TruncationPolicy) -> Self {
+        self.truncation = truncation;
+        self
+    }
+
+    /// Opts this registry into caching results for tools that return
+    /// `true` from [`Tool::cacheable`], for `ttl` before an identical
+    /// call is treated as a miss again. Without this, caching never
+    /// happens regardless of what any individual tool opts into.
+    pub fn with_result_cache(mut self, ttl: Duration) -> Self {
+        self.cache = Some(ToolResultCache::new(ttl));
+        self
+    }
+
+    /// Sets the hook consulted before running any tool whose
+    /// [`Tool::requires_approval`] returns `true`. Without one, such
+    /// tools run unconditionally — set this to gate dangerous tools on a
+    /// human decision.
+    pub fn with_approval_hook(mut self, hook: impl ApprovalHook + 'static) -> Self {
+        self.approval_hook = Some(Arc::new(hook));
+        self
+    }
+
+    /// Sets the sandbox root shared by every file-touching tool
+    /// constructed off this registry. The registry only holds it so
+    /// callers have one place to get it from when constructing tools like
+    /// [`crate::editor::EditorEditTool`] — enforcement itself happens in
+    /// each tool, since [`Tool::execute`] has no way to consult the
+    /// registry it was registered on.
+    pub fn with_workspace(mut self, workspace: Workspace) -> Self {
+        self.workspace = Some(Arc::new(workspace));
+        self
+    }
+
+    pub fn workspace(&self) -> Option<&Arc<Workspace>> {
+        self.workspace.as_ref()
+    }
+
+    pub fn register(&mut self, tool: impl Tool + 'static) {
+        let definition = tool.definition();
+        self.catalog.insert(definition.clone());
+        self.active.pick(definition.name.clone());
+        self.tools.insert(definition.name, Arc::new(tool));
+    }
+
+    /// Like [`ToolRegistry::register`], but also tags the tool as a
+    /// member of `group` for [`ToolRegistry::allow_group`]/[`ToolRegistry::deny_group`].
+    pub fn register_in_group(&mut self, group: &str, tool: impl Tool + 'static) {
+        let name = tool.definition().name.clone();
+        self.register(tool);
+        self.groups.insert(group, name);
+    }
+
+    /// Removes a registered tool so it can no longer be picked or
+    /// called — e.g. a host tearing down a project-specific tool when
+    /// the project it belonged to closes. A subsequent [`ToolRegistry::execute`]
+    /// for `name` gets the same "unknown tool" error as one that was
+    /// never registered. Returns whether `name` was registered.
+    pub fn unregister(&mut self, name: &str) -> bool {
+        self.groups.remove(name);
+        self.active.unpick(name);
+        self.catalog.remove(name);
+        self.tools.remove(name).is_some()
+    }
+
+    /// Hot-swaps the tool registered as `name` for `tool`, so a host can
+    /// add project-specific tools mid-session — a later project file
+    /// defining a new version of a tool, a config reload — without
+    /// restarting the agent loop. `chat_loop_with_tools` reads from this
+    /// registry every round, so the new implementation takes effect on
+    /// the very next tool call. Returns whether `name` was registered
+    /// beforehand; either way, `tool` ends up registered under its own
+    /// [`Tool::definition`] name afterward.
+    pub fn replace(&mut self, name: &str, tool: impl Tool + 'static) -> bool {
+        let existed = self.unregister(name);
+        self.register(tool);
+        existed
+    }
+
+    /// Picks every tool in `group` — e.g. a host letting a user enable
+    /// "web" tools with one call rather than naming each one. A no-op,
+    /// returning `0`, for a group with no members.
+    pub fn allow_group(&self, group: &str) -> usize {
+        let members = self.groups.members(group);
+        members.iter().filter(|name| self.pick(name)).count()
+    }
+
+    /// Denies every tool in `group` — e.g. a host running in a
+    /// read-only or offline mode unpicking "fs" or "web" in one call.
+    pub fn deny_group(&self, group: &str) -> usize {
+        let members = self.groups.members(group);
+        members.iter().filter(|name| self.unpick(name)).count()
+    }
+
+    /// Returns a handle onto this registry's live tool definitions, for
+    /// constructing [`crate::tool_detail::GetToolDetailTool`] before
+    /// registering it — the handle stays in sync with tools registered
+    /// after it's handed out, including the detail tool itself.
+    pub fn catalog(&self) -> ToolCatalog {
+        self.catalog.clone()
+    }
+
+    /// Returns a handle onto this registry's picked/unpicked state, for
+    /// constructing [`crate::drop_tools::DropToolsTool`] before
+    /// registering it.
+    pub fn active_tools(&self) -> ActiveToolSet {
+        self.active.clone()
+    }
+
+    /// Re-includes `name` in [`ToolRegistry::definitions`] if it's a
+    /// registered tool. Returns `false` for an unknown name.
+    pub fn pick(&self, name: &str) -> bool {
+        if !self.tools.contains_key(name) {
+            return false;
+        }
+        self.active.pick(name.to_string());
+        true
+    }
+
+    /// Drops `name` from [`ToolRegistry::definitions`] without
+    /// unregistering it — [`ToolRegistry::execute`] can still run it.
+    /// Returns whether it was picked beforehand.
+    pub fn unpick(&self, name: &str) -> bool {
+        self.active.unpick(name)
+    }
+
+    /// Connects to an MCP server over `transport`, discovers its tools,
+    /// and registers each one — giving this registry's agent access to
+    /// the whole MCP ecosystem a server exposes without writing a
+    /// [`Tool`] impl per remote tool. Returns the names registered.
+    pub async fn register_mcp_server(&mut self, transport: McpTransport) -> Result<Vec<String>, String> {
+        let tools = discover_tools(transport).await?;
+        let names = tools.iter().map(|tool| tool.definition().name).collect();
+        for tool in tools {
+            self.register(tool);
+        }
+        Ok(names)
+    }
+
+    /// The schemas to advertise to the model: every registered tool
+    /// except ones [`ToolRegistry::unpick`] has dropped. Unpicking
+    /// doesn't unregister a tool, so [`ToolRegistry::execute`] still
+    /// runs it if called — only its schema is left out here.
+    pub fn definitions(&self) -> Vec<ToolDefinition> {
+        self.tools
+            .values()
+            .map(|tool| tool.definition())
+            .filter(|definition| self.active.contains(&definition.name))
+            .collect()
+    }
+
+    /// Runs `tool_name` with `arguments`, enforcing this registry's
+    /// timeout policy and validating `arguments` against the tool's
+    /// schema first. An unknown tool name, a schema mismatch, and a
+    /// timed-out execution all come back as an error [`ToolResult`]
+    /// rather than a `Result`, since either way the model needs a
+    /// tool-result message to continue the conversation rather than the
+    /// loop erroring out.
+    #[tracing::instrument(skip(self, arguments), fields(tool_name = %tool_name, is_error, latency_ms))]
+    pub async fn execute(&self, tool_name: &str, arguments: serde_json::Value) -> ToolResult {
+        let Some(tool) = self.tools.get(tool_name) else {
+            return ToolResult::error(format!("unknown tool: {tool_name}"));
+        };
+        let definition = tool.definition();
+        if let Err(message) = validate_arguments(&definition, &arguments) {
+            return ToolResult::error(format!("invalid arguments for '{tool_name}': {message}"));
+        }
+        if tool.requires_approval() {
+            if let Some(hook) = &self.approval_hook {
+                if !hook.approve(&definition, &arguments).await {
+                    return ToolResult::error(format!("tool call to '{tool_name}' was not approved"));
+                }
+            }
+        }
+        let cache_key = match (tool.cacheable(), &self.cache) {
+            (true, Some(cache)) => {
+                let key = ToolResultCache::key(tool_name, &arguments);
+                if let Some(cached) = cache.get(&key) {
+                    self.stats.record(tool_name, Duration::ZERO, cached.is_error, cached.content.len());
+                    return self.truncation.apply(cached);
+                }
+                Some(key)
+            }
+            _ => None,
+        };
+
+        let timeout = self.timeouts.timeout_for(tool_name);
+        let started = Instant::now();
+        let result = match tokio::time::timeout(timeout, tool.execute(arguments)).await {
+            Ok(result) => result,
+            Err(_) => ToolResult::error(format!("tool '{tool_name}' timed out after {timeout:?}")),
+        };
+        let latency = started.elapsed();
+        let span = tracing::Span::current();
+        span.record("is_error", result.is_error);
+        span.record("latency_ms", latency.as_millis());
+        self.stats.record(tool_name, latency, result.is_error, result.content.len());
+        if let (Some(key), Some(cache)) = (cache_key, &self.cache) {
+            cache.insert(key, result.clone());
+        }
+        self.truncation.apply(result)
+    }
+
+    /// Per-tool usage counters accumulated across every call
+    /// [`ToolRegistry::execute`] has run, keyed by tool name. A tool
+    /// rejected before it ran — unknown name, failed schema validation,
+    /// denied approval — isn't counted, since it was never actually
+    /// invoked.
+    pub fn stats(&self) -> HashMap<String, ToolStat> {
+        self.stats.snapshot()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct EchoTool;
+
+    #[async_trait]
+    impl Tool for EchoTool {
+        fn definition(&self) -> ToolDefinition {
+            ToolDefinition {
+                name: "echo".to_string(),
+                description: "echoes its 'text' argument".to_string(),
+                parameters: json_schema_for_echo(),
+            }
+        }
+
+        async fn execute(&self, arguments: serde_json::Value) -> ToolResult {
+            let text = arguments.get("text").and_then(|v| v.as_str()).unwrap_or_default();
+            ToolResult::ok(text.to_string())
+        }
+    }
+
+    fn json_schema_for_echo() -> serde_json::Value {
+        serde_json::json!({
+            "type": "object",
+            "properties": { "text": { "type": "string" } },
+            "required": ["text"],
+        })
+    }
+
+    struct SleepyTool;
+
+    #[async_trait]
+    impl Tool for SleepyTool {
+        fn definition(&self) -> ToolDefinition {
+            ToolDefinition {
+                name: "sleepy".to_string(),
+                description: "never returns".to_string(),
+                parameters: serde_json::json!({ "type": "object", "properties": {} }),
+            }
+        }
+
+        async fn execute(&self, _arguments: serde_json::Value) -> ToolResult {
+            tokio::time::sleep(Duration::from_secs(3600)).await;
+            ToolResult::ok("unreachable")
+        }
+    }
+
+    #[test]
+    fn test_tool_stat_error_rate_and_avg_latency() {
+        let stat = ToolStat::default();
+        assert_eq!(stat.error_rate(), 0.0);
+        assert_eq!(stat.avg_latency(), Duration::ZERO);
+
+        let stat = ToolStat { calls: 4, errors: 1, total_latency: Duration::from_secs(8), output_bytes: 0 };
+        assert_eq!(stat.error_rate(), 0.25);
+        assert_eq!(stat.avg_latency(), Duration::from_secs(2));
+    }
+
+    #[test]
+    fn test_truncation_policy_leaves_short_content_untouched() {
+        let policy = TruncationPolicy { max_chars: 100 };
+        let result = ToolResult::ok("short");
+        let applied = policy.apply(result);
+        assert_eq!(applied.content, "short");
+    }
+
+    #[test]
+    fn test_truncation_policy_truncates_and_appends_a_note() {
+        let policy = TruncationPolicy { max_chars: 5 };
+        let result = ToolResult::ok("0123456789");
+        let applied = policy.apply(result);
+        assert!(applied.content.starts_with("01234"));
+        assert!(applied.content.contains("5 more characters"));
+        assert!(applied.content.contains("offset: 5"));
+    }
+
+    #[test]
+    fn test_timeout_policy_falls_back_to_default_for_unknown_tools() {
+        let policy = TimeoutPolicy::default().with_tool_timeout("slow_build", Duration::from_secs(600));
+        assert_eq!(policy.timeout_for("slow_build"), Duration::from_secs(600));
+        assert_eq!(policy.timeout_for("anything_else"), policy.default);
+    }
+
+    #[tokio::test]
+    async fn test_execute_runs_a_registered_tool() {
+        let mut registry = ToolRegistry::new();
+        registry.register(EchoTool);
+        let result = registry.execute("echo", serde_json::json!({ "text": "hi" })).await;
+        assert!(!result.is_error);
+        assert_eq!(result.content, "hi");
+    }
+
+    #[tokio::test]
+    async fn test_execute_returns_an_error_for_an_unknown_tool() {
+        let registry = ToolRegistry::new();
+        let result = registry.execute("nonexistent", serde_json::json!({})).await;
+        assert!(result.is_error);
+        assert!(result.content.contains("unknown tool"));
+    }
+
+    #[tokio::test]
+    async fn test_execute_rejects_arguments_that_fail_schema_validation() {
+        let mut registry = ToolRegistry::new();
+        registry.register(EchoTool);
+        let result = registry.execute("echo", serde_json::json!({})).await;
+        assert!(result.is_error);
+        assert!(result.content.contains("invalid arguments"));
+    }
+
+    #[tokio::test]
+    async fn test_execute_times_out_a_slow_tool() {
+        let mut registry = ToolRegistry::new().with_timeout_policy(TimeoutPolicy {
+            default: Duration::from_millis(10),
+            per_tool: HashMap::new(),
+        });
+        registry.register(SleepyTool);
+        let result = registry.execute("sleepy", serde_json::json!({})).await;
+        assert!(result.is_error);
+        assert!(result.content.contains("timed out"));
+    }
+
+    #[tokio::test]
+    async fn test_unregister_makes_the_tool_unknown_again() {
+        let mut registry = ToolRegistry::new();
+        registry.register(EchoTool);
+        assert!(registry.unregister("echo"));
+        assert!(!registry.unregister("echo"));
+        let result = registry.execute("echo", serde_json::json!({ "text": "hi" })).await;
+        assert!(result.is_error);
+    }
+
+    #[test]
+    fn test_pick_and_unpick_control_which_definitions_are_advertised() {
+        let mut registry = ToolRegistry::new();
+        registry.register(EchoTool);
+        assert_eq!(registry.definitions().len(), 1);
+        assert!(registry.unpick("echo"));
+        assert_eq!(registry.definitions().len(), 0);
+        assert!(registry.pick("echo"));
+        assert_eq!(registry.definitions().len(), 1);
+    }
+
+    #[test]
+    fn test_allow_group_and_deny_group_pick_and_unpick_every_member() {
+        let mut registry = ToolRegistry::new();
+        registry.register_in_group("read_only", EchoTool);
+        registry.unpick("echo");
+        assert_eq!(registry.allow_group("read_only"), 1);
+        assert_eq!(registry.definitions().len(), 1);
+        assert_eq!(registry.deny_group("read_only"), 1);
+        assert_eq!(registry.definitions().len(), 0);
+    }
+}
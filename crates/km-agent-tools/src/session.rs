@@ -0,0 +1,81 @@
+//! Bundles a live provider and tool registry for one conversation, plus
+//! `save`/`load` for the part of that conversation that's actually worth
+//! writing to disk: history and config. This is the first thing in this
+//! crate to depend on `km-llm` — fine here, since unlike `km-llm` this
+//! crate is already native-only and has no wasm target to protect.
+//!
+//! There's no `LoopDetector` anywhere in this codebase yet, so the
+//! "bundle a loop detector too" half of this is deferred until one
+//! exists to bundle; `Session` only covers what's here today.
+
+use std::fs;
+use std::path::Path;
+
+use km_llm::AnyProvider;
+use serde::{Deserialize, Serialize};
+
+use crate::registry::ToolRegistry;
+
+#[derive(Debug, thiserror::Error)]
+pub enum SessionError {
+    #[error("failed to read session file {path}: {source}")]
+    Read { path: String, source: std::io::Error },
+    #[error("failed to write session file {path}: {source}")]
+    Write { path: String, source: std::io::Error },
+    #[error("failed to parse session file {path}: {source}")]
+    Parse { path: String, source: serde_json::Error },
+    #[error("failed to serialize session: {0}")]
+    Serialize(serde_json::Error),
+}
+
+/// The subset of a session that's actually serializable: conversation
+/// history and the config a fresh provider should be built with. The
+/// live provider connection and the registry's registered `Arc<dyn
+/// Tool>`s can't round-trip through JSON and have to be rebuilt by the
+/// caller, which is also the only party that knows which concrete
+/// backend (`AnthropicProvider`, `OpenAiProvider`, ...) to construct.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SessionState {
+    pub history: Vec<km_core::provider::Message>,
+    pub config: km_core::provider::ProviderConfig,
+}
+
+/// One conversation: a live provider plus the tools available to it.
+/// Construct with whatever concrete provider the host picked, already
+/// wrapped in [`AnyProvider`].
+pub struct Session {
+    pub provider: AnyProvider,
+    pub registry: ToolRegistry,
+}
+
+impl Session {
+    pub fn new(provider: AnyProvider, registry: ToolRegistry) -> Self {
+        Self { provider, registry }
+    }
+
+    /// Snapshots history and config to `path` as JSON. The API key is
+    /// blanked before writing, the same stance `km_core::secrets` takes
+    /// on credentials: a session file on disk isn't somewhere one
+    /// should live, and the caller is expected to resolve a fresh one
+    /// (keyring, env var, ...) when loading this back.
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), SessionError> {
+        let path = path.as_ref();
+        let mut config = self.provider.config().clone();
+        config.api_key.clear();
+        let state = SessionState { history: self.provider.get_history().to_vec(), config };
+        let json = serde_json::to_string_pretty(&state).map_err(SessionError::Serialize)?;
+        fs::write(path, json).map_err(|source| SessionError::Write { path: path.display().to_string(), source })
+    }
+
+    /// Reads a [`SessionState`] previously written by [`Session::save`].
+    /// Restoring it into a live provider (`set_history`, `update_config`
+    /// to refill the api key) is the caller's job — this function has no
+    /// provider to restore into yet, since building one is backend
+    /// selection the caller has already done once and shouldn't have to
+    /// redo here.
+    pub fn load(path: impl AsRef<Path>) -> Result<SessionState, SessionError> {
+        let path = path.as_ref();
+        let text = fs::read_to_string(path).map_err(|source| SessionError::Read { path: path.display().to_string(), source })?;
+        serde_json::from_str(&text).map_err(|source| SessionError::Parse { path: path.display().to_string(), source })
+    }
+}
@@ -0,0 +1,127 @@
+//! Lets an agent externalize its plan as a todo list instead of only
+//! holding it implicitly in the conversation, so a long multi-step run
+//! survives context compaction and a host app can render progress.
+//!
+//! State lives in memory, scoped to one [`TodoList`] per session — there's
+//! no cross-session persistence here, just a shared list two tools read
+//! and write.
+
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use tokio::sync::Mutex;
+
+use crate::tool::{Tool, ToolDefinition, ToolResult};
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+enum TodoStatus {
+    Pending,
+    InProgress,
+    Completed,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct TodoItem {
+    content: String,
+    status: TodoStatus,
+}
+
+/// Shared backing store for [`TodoReadTool`]/[`TodoWriteTool`]. Construct
+/// one per session and register both tools with a clone of it.
+#[derive(Default, Clone)]
+pub struct TodoList {
+    items: Arc<Mutex<Vec<TodoItem>>>,
+}
+
+impl TodoList {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+pub struct TodoReadTool {
+    list: TodoList,
+}
+
+impl TodoReadTool {
+    pub fn new(list: TodoList) -> Self {
+        Self { list }
+    }
+}
+
+#[async_trait]
+impl Tool for TodoReadTool {
+    fn definition(&self) -> ToolDefinition {
+        ToolDefinition {
+            name: "todo_read".to_string(),
+            description: "Return the current todo list for this session.".to_string(),
+            parameters: json!({ "type": "object", "properties": {} }),
+        }
+    }
+
+    async fn execute(&self, _arguments: serde_json::Value) -> ToolResult {
+        let items = self.list.items.lock().await;
+        match serde_json::to_string(&*items) {
+            Ok(json) => ToolResult::ok(json),
+            Err(err) => ToolResult::error(format!("failed to serialize todo list: {err}")),
+        }
+    }
+}
+
+/// Replaces the whole todo list in one call, rather than exposing
+/// add/update/remove operations individually — the model already has
+/// the full list from [`TodoReadTool`] (or its own last write), so
+/// resending the complete, reordered, updated list is simpler than
+/// reconciling a diff.
+pub struct TodoWriteTool {
+    list: TodoList,
+}
+
+impl TodoWriteTool {
+    pub fn new(list: TodoList) -> Self {
+        Self { list }
+    }
+}
+
+#[async_trait]
+impl Tool for TodoWriteTool {
+    fn definition(&self) -> ToolDefinition {
+        ToolDefinition {
+            name: "todo_write".to_string(),
+            description: "Replace the session's todo list with the given items.".to_string(),
+            parameters: json!({
+                "type": "object",
+                "properties": {
+                    "todos": {
+                        "type": "array",
+                        "items": {
+                            "type": "object",
+                            "properties": {
+                                "content": { "type": "string" },
+                                "status": { "type": "string", "enum": ["pending", "in_progress", "completed"] },
+                            },
+                            "required": ["content", "status"],
+                        },
+                    },
+                },
+                "required": ["todos"],
+            }),
+        }
+    }
+
+    async fn execute(&self, arguments: serde_json::Value) -> ToolResult {
+        let Some(todos) = arguments.get("todos") else {
+            return ToolResult::error("todo_write requires a 'todos' array argument");
+        };
+        let items: Vec<TodoItem> = match serde_json::from_value(todos.clone()) {
+            Ok(items) => items,
+            Err(err) => return ToolResult::error(format!("invalid todos: {err}")),
+        };
+        let count = items.len();
+        *self.list.items.lock().await = items;
+        ToolResult::ok(format!("saved {count} todo item(s)"))
+    }
+}
@@ -0,0 +1,67 @@
+//! The `Tool` trait every agent-callable capability implements, and the
+//! plain data types ([`ToolDefinition`], [`ToolResult`]) used to describe
+//! and report on them — kept separate from `registry.rs` so a tool
+//! implementation doesn't need to pull in the registry to exist.
+
+use async_trait::async_trait;
+
+/// Static, model-facing description of a tool: what the model sees when
+/// deciding whether and how to call it.
+#[derive(Debug, Clone)]
+pub struct ToolDefinition {
+    pub name: String,
+    pub description: String,
+    /// JSON Schema for the tool's arguments, as the model expects them.
+    pub parameters: serde_json::Value,
+}
+
+/// The outcome of running a tool: text back to the model, plus whether
+/// it counts as a failure. Mirrors `km_core::provider::Message::is_error`
+/// so a registry's result maps onto a tool-result message without
+/// translation.
+#[derive(Debug, Clone)]
+pub struct ToolResult {
+    pub content: String,
+    pub is_error: bool,
+}
+
+impl ToolResult {
+    pub fn ok(content: impl Into<String>) -> Self {
+        Self { content: content.into(), is_error: false }
+    }
+
+    pub fn error(content: impl Into<String>) -> Self {
+        Self { content: content.into(), is_error: true }
+    }
+}
+
+/// One agent-callable capability. Implementors are native-only (see the
+/// crate doc comment) since every built-in tool touches the filesystem
+/// or spawns a process.
+#[async_trait]
+pub trait Tool: Send + Sync {
+    fn definition(&self) -> ToolDefinition;
+
+    /// Runs the tool with `arguments` (the model's raw, already-parsed
+    /// JSON arguments) and returns its result.
+    async fn execute(&self, arguments: serde_json::Value) -> ToolResult;
+
+    /// Whether a [`crate::registry::ToolRegistry`] with an approval hook
+    /// set should ask it before running this tool — e.g. anything that
+    /// writes files, runs shell commands, or calls out to the network.
+    /// Defaults to `false` so read-only tools (grep, file read) don't
+    /// need to override it.
+    fn requires_approval(&self) -> bool {
+        false
+    }
+
+    /// Whether a [`crate::registry::ToolRegistry`] with result caching
+    /// enabled may serve a repeated call with identical arguments from
+    /// cache instead of running it again. Defaults to `false`: caching is
+    /// only safe for tools whose result depends solely on their
+    /// arguments and not on side effects since the last call (a file
+    /// read, a web fetch — not a shell command or an edit).
+    fn cacheable(&self) -> bool {
+        false
+    }
+}
@@ -0,0 +1,61 @@
+//! A meta-tool for inspecting other tools: some registries only surface
+//! a short blurb per tool up front and expect the model to ask for more
+//! before calling something unfamiliar. `get_tool_detail` is that ask —
+//! it returns a tool's full description and parameter schema without
+//! running it or otherwise changing what's registered.
+
+use async_trait::async_trait;
+use serde_json::json;
+
+use crate::registry::ToolCatalog;
+use crate::tool::{Tool, ToolDefinition, ToolResult};
+
+pub struct GetToolDetailTool {
+    catalog: ToolCatalog,
+}
+
+impl GetToolDetailTool {
+    /// `catalog` should come from the same [`crate::registry::ToolRegistry`]
+    /// this tool is registered on, via `ToolRegistry::catalog()` — that
+    /// keeps lookups in sync with tools registered after this one.
+    pub fn new(catalog: ToolCatalog) -> Self {
+        Self { catalog }
+    }
+}
+
+#[async_trait]
+impl Tool for GetToolDetailTool {
+    fn definition(&self) -> ToolDefinition {
+        ToolDefinition {
+            name: "get_tool_detail".to_string(),
+            description: "Get a tool's full description and parameter schema by name. \
+                Use this before calling a tool you're unsure how to use; it doesn't call \
+                the tool or change which tools are available."
+                .to_string(),
+            parameters: json!({
+                "type": "object",
+                "properties": {
+                    "name": { "type": "string", "description": "The tool name to look up, e.g. 'edit_file'." },
+                },
+                "required": ["name"],
+            }),
+        }
+    }
+
+    async fn execute(&self, arguments: serde_json::Value) -> ToolResult {
+        let Some(name) = arguments.get("name").and_then(|v| v.as_str()) else {
+            return ToolResult::error("missing required field: name");
+        };
+        match self.catalog.get(name) {
+            Some(definition) => ToolResult::ok(
+                json!({
+                    "name": definition.name,
+                    "description": definition.description,
+                    "parameters": definition.parameters,
+                })
+                .to_string(),
+            ),
+            None => ToolResult::error(format!("unknown tool: {name}")),
+        }
+    }
+}
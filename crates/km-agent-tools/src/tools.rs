@@ -0,0 +1,52 @@
+//! Wires the built-in tools together into the set a host actually
+//! registers, so adding a new built-in tool means adding one line to
+//! [`all_tools`] rather than every call site remembering the full list.
+
+use std::sync::Arc;
+
+use crate::apply_patch::ApplyPatchTool;
+use crate::bash::{BashOutputTool, BashTool, JobRegistry, KillBashTool};
+use crate::dir::ListDirTool;
+use crate::drop_tools::DropToolsTool;
+use crate::editor::EditorEditTool;
+use crate::file::{ReadFileTool, WriteFileTool};
+use crate::front_matter::FrontMatterTool;
+use crate::git::GitTool;
+use crate::grep::GrepTool;
+use crate::memory::MemoryTool;
+use crate::project_map::ProjectMapTool;
+use crate::registry::ToolRegistry;
+use crate::todo::{TodoList, TodoReadTool, TodoWriteTool};
+use crate::tool_detail::GetToolDetailTool;
+use crate::web_fetch::WebFetchTool;
+use crate::workspace::Workspace;
+
+/// Registers every built-in tool on `registry`, sandboxed to `workspace`,
+/// sharing `jobs` for background process tracking and `todos` for this
+/// session's todo list.
+///
+/// Each tool is tagged with a group (`fs`, `git`, `web`, `shell`, `meta`)
+/// via [`ToolRegistry::register_in_group`] so a host can flip a whole
+/// category on or off with [`ToolRegistry::allow_group`]/[`ToolRegistry::deny_group`] —
+/// e.g. denying `web` for an offline run, or denying `shell` for a
+/// read-only review session — without naming every tool in it.
+pub fn all_tools(registry: &mut ToolRegistry, workspace: Arc<Workspace>, jobs: JobRegistry, todos: TodoList) {
+    registry.register_in_group("fs", EditorEditTool::new(workspace.clone()));
+    registry.register_in_group("fs", ApplyPatchTool::new(workspace.clone()));
+    registry.register_in_group("fs", ReadFileTool::new(workspace.clone()));
+    registry.register_in_group("fs", WriteFileTool::new(workspace.clone()));
+    registry.register_in_group("fs", GrepTool::new(workspace.clone()));
+    registry.register_in_group("fs", ListDirTool::new(workspace.clone()));
+    registry.register_in_group("fs", ProjectMapTool::new(workspace.clone()));
+    registry.register_in_group("fs", FrontMatterTool::new(workspace.clone()));
+    registry.register_in_group("fs", MemoryTool::new(workspace.clone()));
+    registry.register_in_group("git", GitTool::new(workspace));
+    registry.register_in_group("web", WebFetchTool::new());
+    registry.register_in_group("shell", BashTool::new(jobs.clone()));
+    registry.register_in_group("shell", BashOutputTool::new(jobs.clone()));
+    registry.register_in_group("shell", KillBashTool::new(jobs));
+    registry.register_in_group("meta", TodoReadTool::new(todos.clone()));
+    registry.register_in_group("meta", TodoWriteTool::new(todos));
+    registry.register_in_group("meta", GetToolDetailTool::new(registry.catalog()));
+    registry.register_in_group("meta", DropToolsTool::new(registry.active_tools()));
+}
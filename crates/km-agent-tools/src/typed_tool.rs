@@ -0,0 +1,63 @@
+//! Optional integration for tools that would rather derive a typed
+//! arguments struct than hand-write JSON Schema in [`Tool::definition`].
+//! Implement [`TypedTool`] instead of [`Tool`] directly and the blanket
+//! impl below generates `parameters` from `Args`'s `JsonSchema` derive
+//! and deserializes incoming arguments into `Args` before calling
+//! [`TypedTool::execute_typed`] — a malformed call fails with a clear
+//! deserialization error instead of reaching `execute_typed` at all.
+//!
+//! Every built-in tool in this crate still hand-writes its schema
+//! inline with `json!({...})`; this exists for tools whose argument
+//! shape is complex enough that a derive is less error-prone than a
+//! literal schema, not as a replacement for the existing ones.
+
+use async_trait::async_trait;
+use schemars::JsonSchema;
+use serde::de::DeserializeOwned;
+use serde_json::json;
+
+use crate::tool::{Tool, ToolDefinition, ToolResult};
+
+/// Renders `T`'s `JsonSchema` derive as the JSON Schema
+/// [`ToolDefinition::parameters`] expects.
+pub fn schema_for<T: JsonSchema>() -> serde_json::Value {
+    let schema = schemars::generate::SchemaGenerator::default().into_root_schema_for::<T>();
+    serde_json::to_value(schema).unwrap_or_else(|_| json!({ "type": "object" }))
+}
+
+/// A tool whose arguments are a typed struct deriving `JsonSchema` and
+/// `Deserialize`, rather than raw `serde_json::Value`. The blanket
+/// `impl<T: TypedTool> Tool for T` below wires this up to the registry
+/// like any other tool.
+#[async_trait]
+pub trait TypedTool: Send + Sync {
+    type Args: DeserializeOwned + JsonSchema + Send;
+
+    fn name(&self) -> String;
+    fn description(&self) -> String;
+
+    /// Mirrors [`Tool::requires_approval`]; same default.
+    fn requires_approval(&self) -> bool {
+        false
+    }
+
+    async fn execute_typed(&self, args: Self::Args) -> ToolResult;
+}
+
+#[async_trait]
+impl<T: TypedTool> Tool for T {
+    fn definition(&self) -> ToolDefinition {
+        ToolDefinition { name: self.name(), description: self.description(), parameters: schema_for::<T::Args>() }
+    }
+
+    fn requires_approval(&self) -> bool {
+        TypedTool::requires_approval(self)
+    }
+
+    async fn execute(&self, arguments: serde_json::Value) -> ToolResult {
+        match serde_json::from_value::<T::Args>(arguments) {
+            Ok(args) => self.execute_typed(args).await,
+            Err(err) => ToolResult::error(format!("invalid arguments: {err}")),
+        }
+    }
+}
@@ -0,0 +1,105 @@
+//! Fetches a URL and hands the model markdown instead of raw HTML, so a
+//! documentation page doesn't flood the context with boilerplate markup
+//! the model has to read past to find the actual content.
+
+use std::time::Duration;
+
+use async_trait::async_trait;
+use serde_json::json;
+
+use crate::tool::{Tool, ToolDefinition, ToolResult};
+
+/// How long a fetch (including redirects) is allowed to take. Separate
+/// from a [`crate::registry::TimeoutPolicy`] entry in case a host wants
+/// web fetches to have their own, shorter budget than the registry
+/// default.
+const FETCH_TIMEOUT: Duration = Duration::from_secs(20);
+
+/// Hard cap on response body size, applied before conversion so a
+/// multi-gigabyte response doesn't get buffered in full first.
+const MAX_RESPONSE_BYTES: usize = 5 * 1024 * 1024;
+
+pub struct WebFetchTool {
+    client: reqwest::Client,
+}
+
+impl Default for WebFetchTool {
+    fn default() -> Self {
+        Self { client: reqwest::Client::builder().timeout(FETCH_TIMEOUT).build().unwrap_or_default() }
+    }
+}
+
+impl WebFetchTool {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[async_trait]
+impl Tool for WebFetchTool {
+    fn definition(&self) -> ToolDefinition {
+        ToolDefinition {
+            name: "web_fetch".to_string(),
+            description: "Fetch a URL over HTTP GET and return its content as markdown if \
+                it's HTML, or as plain text otherwise. Refuses non-text content types and \
+                responses over a size cap."
+                .to_string(),
+            parameters: json!({
+                "type": "object",
+                "properties": { "url": { "type": "string" } },
+                "required": ["url"],
+            }),
+        }
+    }
+
+    fn requires_approval(&self) -> bool {
+        true
+    }
+
+    // Fetching the same URL twice in a short window is almost always
+    // the model re-checking something it already has, not expecting
+    // different content — worth caching despite the small chance a page
+    // changed in between.
+    fn cacheable(&self) -> bool {
+        true
+    }
+
+    async fn execute(&self, arguments: serde_json::Value) -> ToolResult {
+        let Some(url) = arguments.get("url").and_then(|v| v.as_str()) else {
+            return ToolResult::error("web_fetch requires a string 'url' argument");
+        };
+
+        let response = match self.client.get(url).send().await {
+            Ok(response) => response,
+            Err(err) => return ToolResult::error(format!("failed to fetch '{url}': {err}")),
+        };
+        if !response.status().is_success() {
+            return ToolResult::error(format!("'{url}' returned {}", response.status()));
+        }
+        let content_type =
+            response.headers().get(reqwest::header::CONTENT_TYPE).and_then(|v| v.to_str().ok()).unwrap_or("").to_string();
+        if !content_type.contains("html") && !content_type.contains("text") && !content_type.is_empty() {
+            return ToolResult::error(format!("'{url}' has unsupported content type '{content_type}'"));
+        }
+        if let Some(length) = response.content_length() {
+            if length as usize > MAX_RESPONSE_BYTES {
+                return ToolResult::error(format!("'{url}' is {length} bytes, over the {MAX_RESPONSE_BYTES}-byte cap"));
+            }
+        }
+
+        let bytes = match response.bytes().await {
+            Ok(bytes) => bytes,
+            Err(err) => return ToolResult::error(format!("failed to read response from '{url}': {err}")),
+        };
+        if bytes.len() > MAX_RESPONSE_BYTES {
+            return ToolResult::error(format!(
+                "'{url}' body is {} bytes, over the {MAX_RESPONSE_BYTES}-byte cap",
+                bytes.len()
+            ));
+        }
+
+        let body = String::from_utf8_lossy(&bytes);
+        let rendered = if content_type.contains("html") { html2md::parse_html(&body) } else { body.into_owned() };
+        ToolResult::ok(rendered)
+    }
+}
@@ -0,0 +1,143 @@
+//! Confines file tools to a single root directory so a model can't read or
+//! write outside the project it was given access to, whether by an
+//! absolute path, a `..` escape, or a symlink planted inside the
+//! workspace that points elsewhere.
+
+use std::path::{Path, PathBuf};
+
+/// A sandboxed root directory. Tools that touch the filesystem
+/// ([`crate::editor::EditorEditTool`], and any future read/write/listing
+/// tool) take one of these instead of operating on raw paths directly.
+#[derive(Debug, Clone)]
+pub struct Workspace {
+    root: PathBuf,
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum WorkspaceError {
+    #[error("workspace root does not exist or is not a directory: {0}")]
+    InvalidRoot(PathBuf),
+    #[error("path escapes the workspace root: {0}")]
+    PathEscapesRoot(PathBuf),
+    #[error("failed to resolve path: {0}")]
+    Io(#[from] std::io::Error),
+}
+
+impl Workspace {
+    pub fn new(root: impl Into<PathBuf>) -> Result<Self, WorkspaceError> {
+        let root = root.into();
+        let canonical = root.canonicalize().map_err(|_| WorkspaceError::InvalidRoot(root.clone()))?;
+        if !canonical.is_dir() {
+            return Err(WorkspaceError::InvalidRoot(root));
+        }
+        Ok(Self { root: canonical })
+    }
+
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// Resolves `path` (absolute or relative to the workspace root) to a
+    /// canonical path guaranteed to live under [`Self::root`].
+    /// Canonicalizing — rather than just rejecting `..` components — is
+    /// what also catches a symlink inside the workspace that points
+    /// outside it.
+    ///
+    /// Set `must_exist` to `false` for a path a tool is about to create:
+    /// only the closest existing ancestor is canonicalized, and the
+    /// remaining, not-yet-created components are appended lexically. A
+    /// tool that creates intermediate symlinks as part of writing the
+    /// file isn't caught by this lexical tail, so such tools should treat
+    /// this as a best-effort check, not a sandbox guarantee after the
+    /// fact.
+    pub fn resolve(&self, path: impl AsRef<Path>, must_exist: bool) -> Result<PathBuf, WorkspaceError> {
+        let path = path.as_ref();
+        let joined = if path.is_absolute() { path.to_path_buf() } else { self.root.join(path) };
+
+        if must_exist {
+            return self.ensure_within_root(joined.canonicalize()?);
+        }
+
+        // `..` has to be collapsed lexically before walking up to find the
+        // nearest existing ancestor below: `Path::file_name()`/`Path::parent()`
+        // both return `None` for a path ending in `..`, so a `..` among the
+        // not-yet-created tail components would otherwise just get skipped
+        // by the loop below instead of actually cancelling out the
+        // component before it — silently landing the resolved path
+        // somewhere other than where the `..`s said to go.
+        let joined = normalize_lexically(&joined);
+
+        let mut existing: &Path = &joined;
+        let mut tail = Vec::new();
+        while !existing.exists() {
+            let Some(parent) = existing.parent() else { break };
+            if let Some(name) = existing.file_name() {
+                tail.push(name.to_owned());
+            }
+            existing = parent;
+        }
+        let mut canonical = existing.canonicalize()?;
+        for component in tail.into_iter().rev() {
+            canonical.push(component);
+        }
+        self.ensure_within_root(canonical)
+    }
+
+    fn ensure_within_root(&self, candidate: PathBuf) -> Result<PathBuf, WorkspaceError> {
+        if candidate.starts_with(&self.root) {
+            Ok(candidate)
+        } else {
+            Err(WorkspaceError::PathEscapesRoot(candidate))
+        }
+    }
+}
+
+/// Collapses `.` and `..` components without touching the filesystem,
+/// the way `Path::canonicalize` would if every component already
+/// existed. A `..` that would walk back past the path's root/prefix is
+/// kept as-is rather than discarded, so the result still reflects an
+/// escape attempt instead of quietly absorbing it.
+fn normalize_lexically(path: &Path) -> PathBuf {
+    let mut normalized = PathBuf::new();
+    for component in path.components() {
+        match component {
+            std::path::Component::CurDir => {}
+            std::path::Component::ParentDir => match normalized.components().next_back() {
+                Some(std::path::Component::Normal(_)) => {
+                    normalized.pop();
+                }
+                _ => normalized.push(".."),
+            },
+            other => normalized.push(other.as_os_str()),
+        }
+    }
+    normalized
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_workspace() -> (tempfile::TempDir, Workspace) {
+        let dir = tempfile::tempdir().expect("create temp dir");
+        let workspace = Workspace::new(dir.path()).expect("workspace root should be valid");
+        (dir, workspace)
+    }
+
+    #[test]
+    fn test_resolve_not_must_exist_rejects_dotdot_escape_through_missing_dirs() {
+        let (_dir, workspace) = test_workspace();
+        let err = workspace
+            .resolve("x/../../../escape.txt", false)
+            .expect_err("path that walks above the workspace root should be rejected");
+        assert!(matches!(err, WorkspaceError::PathEscapesRoot(_)));
+    }
+
+    #[test]
+    fn test_resolve_not_must_exist_allows_dotdot_within_root() {
+        let (dir, workspace) = test_workspace();
+        std::fs::create_dir(dir.path().join("a")).expect("create subdir");
+        let resolved = workspace.resolve("a/../b.txt", false).expect("path stays within the root");
+        assert_eq!(resolved, workspace.root().join("b.txt"));
+    }
+}
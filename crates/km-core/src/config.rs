@@ -0,0 +1,186 @@
+//! Loading [`ProviderConfig`] from `km.toml` and the environment, with a
+//! documented precedence: CLI overrides > environment variables > config
+//! file > built-in defaults.
+
+use std::collections::HashMap;
+use std::path::Path;
+
+use serde::Deserialize;
+
+use crate::provider::ProviderConfig;
+
+#[derive(Debug, thiserror::Error)]
+pub enum ConfigError {
+    #[error("failed to read config file {path}: {source}")]
+    Read { path: String, source: std::io::Error },
+    #[error("failed to parse config file {path}: {source}")]
+    Parse { path: String, source: toml::de::Error },
+}
+
+/// The `km.toml` shape, every field optional so a file only needs to
+/// specify what it overrides. Mirrors [`ProviderConfig`] field-for-field
+/// where applicable.
+#[derive(Debug, Default, Clone, Deserialize)]
+pub struct PartialProviderConfig {
+    pub api_key: Option<String>,
+    pub model: Option<String>,
+    pub temperature: Option<f32>,
+    pub max_tokens: Option<u32>,
+    pub max_tool_turns: Option<u32>,
+    #[serde(default)]
+    pub extra_options: HashMap<String, String>,
+}
+
+impl PartialProviderConfig {
+    /// Reads and parses a `km.toml`-shaped file. Returns an error if the
+    /// file exists but isn't valid TOML; a missing file is the caller's
+    /// decision (use [`Option`] upstream), not this function's.
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self, ConfigError> {
+        let path = path.as_ref();
+        let text = std::fs::read_to_string(path).map_err(|source| ConfigError::Read {
+            path: path.display().to_string(),
+            source,
+        })?;
+        toml::from_str(&text).map_err(|source| ConfigError::Parse {
+            path: path.display().to_string(),
+            source,
+        })
+    }
+
+    /// Reads overrides from `KM_API_KEY`, `KM_MODEL`, `KM_TEMPERATURE`,
+    /// `KM_MAX_TOKENS`, and `KM_MAX_TOOL_TURNS`. Unset or unparseable
+    /// numeric variables are left as `None` rather than erroring, since
+    /// env is one layer among several.
+    pub fn from_env() -> Self {
+        Self {
+            api_key: std::env::var("KM_API_KEY").ok(),
+            model: std::env::var("KM_MODEL").ok(),
+            temperature: std::env::var("KM_TEMPERATURE").ok().and_then(|v| v.parse().ok()),
+            max_tokens: std::env::var("KM_MAX_TOKENS").ok().and_then(|v| v.parse().ok()),
+            max_tool_turns: std::env::var("KM_MAX_TOOL_TURNS").ok().and_then(|v| v.parse().ok()),
+            extra_options: HashMap::new(),
+        }
+    }
+
+    /// Overlays `other` on top of `self`, with `other`'s set fields
+    /// winning. Used to fold layers together in precedence order.
+    fn merged_with(mut self, other: Self) -> Self {
+        self.api_key = other.api_key.or(self.api_key);
+        self.model = other.model.or(self.model);
+        self.temperature = other.temperature.or(self.temperature);
+        self.max_tokens = other.max_tokens.or(self.max_tokens);
+        self.max_tool_turns = other.max_tool_turns.or(self.max_tool_turns);
+        self.extra_options.extend(other.extra_options);
+        self
+    }
+
+    /// Fills in defaults for any unset field, producing a usable
+    /// [`ProviderConfig`]. Shared by [`resolve`] and `ProviderFactory`, so
+    /// profiles and the file/env/CLI stack land on the same defaults.
+    pub fn into_provider_config(self) -> ProviderConfig {
+        let mut config = ProviderConfig::new(self.api_key.unwrap_or_default(), self.model.unwrap_or_default());
+        if let Some(temperature) = self.temperature {
+            config.temperature = temperature;
+        }
+        if let Some(max_tokens) = self.max_tokens {
+            config.max_tokens = max_tokens;
+        }
+        if let Some(max_tool_turns) = self.max_tool_turns {
+            config.max_tool_turns = max_tool_turns;
+        }
+        config.extra_options.extend(self.extra_options);
+        config
+    }
+}
+
+/// The top-level `km.toml` shape: default settings plus named
+/// `[profile.*]` tables (see [`km_llm::factory::ProviderFactory`]).
+#[derive(Debug, Default, Deserialize)]
+pub struct KmTomlFile {
+    #[serde(flatten)]
+    pub defaults: PartialProviderConfig,
+    #[serde(default)]
+    pub profile: HashMap<String, PartialProviderConfig>,
+}
+
+/// Reads the `[profile.*]` tables out of a `km.toml` file, for
+/// `ProviderFactory::from_profile`. Returns an empty map if the file
+/// doesn't exist.
+pub fn load_profiles(path: impl AsRef<Path>) -> Result<HashMap<String, PartialProviderConfig>, ConfigError> {
+    let path = path.as_ref();
+    if !path.exists() {
+        return Ok(HashMap::new());
+    }
+    let text = std::fs::read_to_string(path).map_err(|source| ConfigError::Read {
+        path: path.display().to_string(),
+        source,
+    })?;
+    let file: KmTomlFile = toml::from_str(&text).map_err(|source| ConfigError::Parse {
+        path: path.display().to_string(),
+        source,
+    })?;
+    Ok(file.profile)
+}
+
+/// Settings that apply to the whole `km` install rather than one project,
+/// loaded from `~/.config/km/config.toml` (XDG) or the platform equivalent
+/// (`%APPDATA%\km\config.toml` on Windows, via the `dirs` crate) and merged
+/// with the project's `km.toml`. Project settings win on conflict.
+#[derive(Debug, Default, Clone, Deserialize)]
+pub struct GlobalConfig {
+    pub default_provider: Option<String>,
+    #[serde(default)]
+    pub ignore_patterns: Vec<String>,
+    #[serde(default)]
+    pub tool_permissions: HashMap<String, bool>,
+    pub session_storage_path: Option<String>,
+}
+
+/// The path to the global config file: `$XDG_CONFIG_HOME/km/config.toml`,
+/// falling back to the platform config directory via `dirs::config_dir`.
+pub fn global_config_path() -> Option<std::path::PathBuf> {
+    dirs::config_dir().map(|dir| dir.join("km").join("config.toml"))
+}
+
+/// Loads the global config, returning defaults if it doesn't exist.
+pub fn load_global_config() -> Result<GlobalConfig, ConfigError> {
+    let Some(path) = global_config_path() else {
+        return Ok(GlobalConfig::default());
+    };
+    if !path.exists() {
+        return Ok(GlobalConfig::default());
+    }
+    let text = std::fs::read_to_string(&path).map_err(|source| ConfigError::Read {
+        path: path.display().to_string(),
+        source,
+    })?;
+    toml::from_str(&text).map_err(|source| ConfigError::Parse {
+        path: path.display().to_string(),
+        source,
+    })
+}
+
+/// Merges the global config with a project-level override, with the
+/// project's values winning field-by-field.
+pub fn merge_global_and_project(global: GlobalConfig, project: GlobalConfig) -> GlobalConfig {
+    let mut merged = global;
+    merged.default_provider = project.default_provider.or(merged.default_provider);
+    merged.session_storage_path = project.session_storage_path.or(merged.session_storage_path);
+    if !project.ignore_patterns.is_empty() {
+        merged.ignore_patterns = project.ignore_patterns;
+    }
+    merged.tool_permissions.extend(project.tool_permissions);
+    merged
+}
+
+/// Resolves a final [`ProviderConfig`] from, in increasing precedence:
+/// built-in defaults, the `km.toml` file at `config_path` (if it exists),
+/// the environment, and `cli` overrides (typically parsed CLI flags).
+pub fn resolve(config_path: Option<&Path>, cli: PartialProviderConfig) -> Result<ProviderConfig, ConfigError> {
+    let from_file = match config_path {
+        Some(path) if path.exists() => PartialProviderConfig::from_file(path)?,
+        _ => PartialProviderConfig::default(),
+    };
+    let merged = from_file.merged_with(PartialProviderConfig::from_env()).merged_with(cli);
+    Ok(merged.into_provider_config())
+}
@@ -0,0 +1,13 @@
+//! km-core: the provider-agnostic types (`Message`, `ProviderError`, ...)
+//! and cross-cutting concerns (config loading, secrets, session reporting)
+//! shared by every other km crate. Has no dependency on any concrete LLM
+//! backend — see `km-llm` for those.
+
+pub mod config;
+/// Optional: installs an OTLP exporter for this crate's `tracing` spans.
+/// See the module doc comment for why it's feature-gated.
+#[cfg(feature = "otel")]
+pub mod otel;
+pub mod provider;
+pub mod report;
+pub mod secrets;
@@ -0,0 +1,56 @@
+//! Wires the `tracing` spans emitted across km crates (`chat`, `chat_loop`,
+//! `ToolRegistry::execute`, each tool-call round) into an OTLP exporter, so
+//! a `km-tools` run shows up in whatever observability stack is already
+//! watching for `tracing`-instrumented services — Jaeger, Tempo, Honeycomb,
+//! anything that speaks OTLP.
+//!
+//! Optional and off by default: most callers run km locally and have
+//! nowhere to send spans, so pulling in the `opentelemetry*` dependency
+//! tree isn't worth it unless `init_otlp_exporter` is actually called.
+#![cfg(feature = "otel")]
+
+use opentelemetry::trace::TracerProvider as _;
+use opentelemetry_otlp::WithExportConfig;
+use tracing_subscriber::layer::SubscriberExt;
+use tracing_subscriber::util::SubscriberInitExt;
+
+#[derive(Debug, thiserror::Error)]
+pub enum OtelError {
+    #[error("failed to build OTLP span exporter for {endpoint}: {source}")]
+    Exporter { endpoint: String, source: opentelemetry_otlp::ExporterBuildError },
+    #[error("failed to install global tracing subscriber: {0}")]
+    Subscriber(tracing_subscriber::util::TryInitError),
+}
+
+/// Points an OTLP (gRPC) exporter at `endpoint` (e.g.
+/// `http://localhost:4317`) and installs it as the global `tracing`
+/// subscriber, alongside the usual `EnvFilter`-driven fmt output so local
+/// logs keep working. Call once, near the top of `main`, before any
+/// `LLMProvider` or `ToolRegistry` method runs — spans emitted before this
+/// is called are simply dropped, not buffered.
+pub fn init_otlp_exporter(endpoint: &str) -> Result<(), OtelError> {
+    let exporter = opentelemetry_otlp::SpanExporter::builder()
+        .with_tonic()
+        .with_endpoint(endpoint)
+        .build()
+        .map_err(|source| OtelError::Exporter { endpoint: endpoint.to_string(), source })?;
+
+    let provider = opentelemetry_sdk::trace::SdkTracerProvider::builder()
+        .with_batch_exporter(exporter)
+        .with_resource(
+            opentelemetry_sdk::Resource::builder().with_service_name("km-tools").build(),
+        )
+        .build();
+    let tracer = provider.tracer("km-tools");
+
+    let otel_layer = tracing_opentelemetry::layer().with_tracer(tracer);
+    let fmt_layer = tracing_subscriber::fmt::layer();
+    let env_filter = tracing_subscriber::EnvFilter::try_from_default_env().unwrap_or_else(|_| "info".into());
+
+    tracing_subscriber::registry()
+        .with(env_filter)
+        .with(fmt_layer)
+        .with(otel_layer)
+        .try_init()
+        .map_err(OtelError::Subscriber)
+}
@@ -0,0 +1,605 @@
+//! Core provider abstraction shared by every LLM backend.
+//!
+//! `provider.rs` defines the wire-format-agnostic [`Message`] type, the
+//! [`LLMProvider`] trait each backend implements, and the configuration and
+//! error types that flow through it. Concrete backends live in sibling
+//! modules (`openai.rs`, `gemini.rs`, `anthropic.rs`).
+
+use std::collections::HashMap;
+
+use async_trait::async_trait;
+
+/// One part of a (possibly multi-part) message's content.
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub enum ContentPart {
+    Text(String),
+    Image { url: String, mime_type: Option<String> },
+}
+
+/// A single turn in a conversation, in the backend-agnostic shape the rest
+/// of the crate works with. Providers translate to/from their own wire
+/// formats at the edges.
+///
+/// `content` is a list of parts rather than a single string so a message
+/// can carry images alongside text; most messages are still just one
+/// `ContentPart::Text`, and [`Message::text`]/[`Message::text_content`]
+/// cover that common case without callers building the `Vec` by hand.
+#[derive(Debug, Clone, PartialEq, Default, serde::Serialize, serde::Deserialize)]
+pub struct Message {
+    pub role: Role,
+    pub content: Vec<ContentPart>,
+    /// Tool calls the assistant requested in this turn, if any.
+    pub tool_calls: Vec<ToolCall>,
+    /// For a `Role::Tool` message, which call this is the result of.
+    pub tool_call_id: Option<String>,
+    /// For a `Role::Tool` message, whether the tool execution failed.
+    /// Carried through so providers can tell the model a call actually
+    /// failed (Anthropic's `tool_result.is_error`, OpenAI's equivalent)
+    /// instead of always reporting success.
+    pub is_error: bool,
+    /// Marks this message as opaque to compaction: a summarization pass
+    /// must carry it through unchanged rather than folding it into a
+    /// summary. OpenAI's encrypted reasoning items are the motivating
+    /// case — they're meaningless once paraphrased and must reach the
+    /// model byte-for-byte on a later turn.
+    pub opaque: bool,
+}
+
+impl Message {
+    /// Builds a plain text message — the common case before multi-part
+    /// (image) content is needed.
+    pub fn text(role: Role, text: impl Into<String>) -> Self {
+        Self {
+            role,
+            content: vec![ContentPart::Text(text.into())],
+            ..Default::default()
+        }
+    }
+
+    /// Concatenates every text part, ignoring images. Use when a caller
+    /// just needs "the text" — logging, token estimation, tool result
+    /// bodies — not the full multi-part structure.
+    pub fn text_content(&self) -> String {
+        self.content
+            .iter()
+            .filter_map(|part| match part {
+                ContentPart::Text(text) => Some(text.as_str()),
+                ContentPart::Image { .. } => None,
+            })
+            .collect()
+    }
+}
+
+/// A single function/tool invocation requested by the assistant.
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct ToolCall {
+    pub id: String,
+    pub name: String,
+    /// JSON-encoded arguments, as the model emitted them.
+    pub arguments: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, serde::Serialize, serde::Deserialize)]
+pub enum Role {
+    System,
+    #[default]
+    User,
+    Assistant,
+    Tool,
+}
+
+/// Configuration shared by every provider. Backend-specific knobs live in
+/// `extra_options` until they're promoted to first-class fields.
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct ProviderConfig {
+    pub api_key: String,
+    pub model: String,
+    pub temperature: f32,
+    pub max_tokens: u32,
+    pub max_tool_turns: u32,
+    pub extra_options: HashMap<String, String>,
+    /// When set, providers log a sanitized summary of each outgoing request
+    /// and the type of every SSE event they receive, at `trace` level. Off
+    /// by default; turn on with `with_debug_logging` or the `KM_DEBUG_WIRE`
+    /// env var instead of sprinkling `println!` into backend code.
+    pub debug_logging: bool,
+    pub tool_choice: ToolChoice,
+    /// Whether the model may call multiple tools in one turn. Some
+    /// providers default this to `true`; hosts that need calls applied
+    /// strictly one at a time (e.g. for a human-approval gate between
+    /// each) can turn it off.
+    pub parallel_tool_calls: bool,
+    /// How long to wait for a complete (non-streaming) response, or for
+    /// an SSE stream to finish, before giving up with
+    /// [`ProviderError::Timeout`].
+    pub request_timeout: std::time::Duration,
+    /// How long an SSE stream may go without a new event before it's
+    /// considered stalled and failed with [`ProviderError::Timeout`].
+    /// Shorter than `request_timeout` so a hung stream is caught well
+    /// before the overall request budget runs out.
+    pub stream_stall_timeout: std::time::Duration,
+    /// Constrains the final response to JSON (optionally schema-validated
+    /// JSON). See [`crate::provider::ResponseFormat`] and, for a
+    /// typed-deserialize helper built on top, `km_llm::structured`.
+    pub response_format: ResponseFormat,
+}
+
+/// Constrains the shape of a model's final response, mapped by each
+/// provider to its own wire representation: OpenAI's `response_format`,
+/// Gemini's `responseMimeType`/`responseSchema`, and — since Anthropic has
+/// no native equivalent — an assistant-turn prefill plus the schema
+/// folded into the system prompt.
+#[derive(Debug, Clone, PartialEq, Default, serde::Serialize, serde::Deserialize)]
+pub enum ResponseFormat {
+    /// No constraint; free-form text. Default.
+    #[default]
+    Text,
+    /// Must be valid JSON, but any shape.
+    Json,
+    /// Must validate against `schema` (a JSON Schema document). `name`
+    /// identifies the schema in providers that require one (OpenAI).
+    JsonSchema { name: String, schema: serde_json::Value },
+}
+
+/// Controls whether and which tool the model is pushed to call, mapped by
+/// each provider to its own wire representation (OpenAI's `tool_choice`,
+/// Anthropic's `tool_choice`, Gemini's `function_calling_config`).
+#[derive(Debug, Clone, PartialEq, Eq, Default, serde::Serialize, serde::Deserialize)]
+pub enum ToolChoice {
+    /// The model decides whether to call a tool. Default.
+    #[default]
+    Auto,
+    /// The model must not call any tool.
+    None,
+    /// The model must call some tool, but may pick which.
+    Required,
+    /// The model must call this specific tool.
+    Specific(String),
+}
+
+impl ProviderConfig {
+    pub fn new(api_key: impl Into<String>, model: impl Into<String>) -> Self {
+        Self {
+            api_key: api_key.into(),
+            model: model.into(),
+            temperature: 0.7,
+            max_tokens: 4096,
+            max_tool_turns: 25,
+            extra_options: HashMap::new(),
+            debug_logging: false,
+            tool_choice: ToolChoice::default(),
+            parallel_tool_calls: true,
+            request_timeout: std::time::Duration::from_secs(120),
+            stream_stall_timeout: std::time::Duration::from_secs(30),
+            response_format: ResponseFormat::default(),
+        }
+    }
+
+    pub fn with_debug_logging(mut self, enabled: bool) -> Self {
+        self.debug_logging = enabled;
+        self
+    }
+
+    pub fn with_tool_choice(mut self, tool_choice: ToolChoice) -> Self {
+        self.tool_choice = tool_choice;
+        self
+    }
+
+    pub fn with_parallel_tool_calls(mut self, enabled: bool) -> Self {
+        self.parallel_tool_calls = enabled;
+        self
+    }
+
+    pub fn with_request_timeout(mut self, timeout: std::time::Duration) -> Self {
+        self.request_timeout = timeout;
+        self
+    }
+
+    pub fn with_stream_stall_timeout(mut self, timeout: std::time::Duration) -> Self {
+        self.stream_stall_timeout = timeout;
+        self
+    }
+
+    pub fn with_response_format(mut self, response_format: ResponseFormat) -> Self {
+        self.response_format = response_format;
+        self
+    }
+}
+
+/// Logs a sanitized one-line summary of an outgoing request (model, message
+/// count, tool count) if `debug_logging` is enabled. API keys and message
+/// content are never logged. Providers call this right before sending a
+/// request; pair with [`log_sse_event`] while consuming the response stream.
+pub fn log_request_summary(config: &ProviderConfig, provider: &str, message_count: usize, tool_count: usize) {
+    if config.debug_logging {
+        tracing::trace!(
+            provider,
+            model = %config.model,
+            message_count,
+            tool_count,
+            "sending request"
+        );
+    }
+}
+
+/// Maps an HTTP status code and raw response body to a structured
+/// [`ProviderError`]. Shared by every backend's error path so a 429 or 401
+/// means the same thing regardless of which vendor returned it; backends
+/// may still special-case payloads that carry more detail (e.g. a
+/// `Retry-After` header) before falling back to this.
+pub fn classify_http_error(status: u16, body: &str) -> ProviderError {
+    let details = ErrorDetails {
+        http_status: Some(status),
+        raw_body: Some(body.to_string()),
+        ..Default::default()
+    };
+    match status {
+        401 | 403 => ProviderError::AuthenticationFailed { details },
+        429 => ProviderError::RateLimitExceeded { retry_after: None, details },
+        503 => ProviderError::Overloaded { details },
+        400 if body.to_lowercase().contains("context") || body.to_lowercase().contains("maximum context") => {
+            ProviderError::ContextLengthExceeded { details }
+        }
+        _ => ProviderError::ApiError {
+            message: format!("HTTP {status}: {body}"),
+            details,
+        },
+    }
+}
+
+/// Logs the type of an SSE event as it's received, if `debug_logging` is
+/// enabled. Event payloads are not logged, only their type, to keep wire
+/// traces safe to paste into bug reports.
+pub fn log_sse_event(config: &ProviderConfig, provider: &str, event_type: &str) {
+    if config.debug_logging {
+        tracing::trace!(provider, event_type, "received SSE event");
+    }
+}
+
+/// Machine-readable detail attached to a [`ProviderError`], for support
+/// tickets and retry decisions that need more than the `Display` message.
+/// All fields are best-effort: a backend fills in whatever the response
+/// actually carried.
+#[derive(Debug, Clone, Default)]
+pub struct ErrorDetails {
+    pub http_status: Option<u16>,
+    pub provider_error_type: Option<String>,
+    pub provider_error_code: Option<String>,
+    pub request_id: Option<String>,
+    pub raw_body: Option<String>,
+}
+
+/// Errors a provider can return from `chat`/`chat_loop`.
+///
+/// Backends parse HTTP status codes and provider-specific error payloads
+/// into these variants instead of flattening everything into
+/// [`ProviderError::ApiError`], so callers can implement sensible handling
+/// (backing off on rate limits, prompting for re-auth, trimming context)
+/// without string-matching messages. Each variant carries an
+/// [`ErrorDetails`], reachable via [`ProviderError::details`], for callers
+/// that need the raw status/body rather than the human-readable message.
+#[derive(Debug, thiserror::Error)]
+pub enum ProviderError {
+    /// A rate limit was hit. `retry_after` is the provider's suggested
+    /// backoff, parsed from a `Retry-After` header or error body when
+    /// present.
+    #[error("rate limit exceeded{}", retry_after.map(|d| format!(", retry after {d:?}")).unwrap_or_default())]
+    RateLimitExceeded {
+        retry_after: Option<std::time::Duration>,
+        details: ErrorDetails,
+    },
+
+    /// The API key was missing, malformed, or rejected by the provider.
+    #[error("authentication failed")]
+    AuthenticationFailed { details: ErrorDetails },
+
+    /// The request (history + new message) exceeds the model's context
+    /// window.
+    #[error("context length exceeded")]
+    ContextLengthExceeded { details: ErrorDetails },
+
+    /// The provider reported that its servers are overloaded, distinct
+    /// from a rate limit on this account.
+    #[error("provider overloaded")]
+    Overloaded { details: ErrorDetails },
+
+    /// The request (or an SSE stream mid-flight) took longer than
+    /// `request_timeout`/`stream_stall_timeout` to produce a response or
+    /// its next event. Distinct from [`ProviderError::ApiError`] so
+    /// callers can retry a hang without second-guessing whether the
+    /// provider actually rejected the request.
+    #[error("request timed out")]
+    Timeout { details: ErrorDetails },
+
+    /// A caller-supplied budget (`ChatLoopConfig::max_cost`/`max_total_tokens`
+    /// in `km_llm::agent_loop`) would be exceeded by continuing the loop.
+    /// Distinct from [`ProviderError::ContextLengthExceeded`], which is the
+    /// model's own hard limit rather than a caller's spending cap — this one
+    /// the loop hits deliberately, with whatever partial conversation
+    /// happened still available to the caller.
+    #[error("budget exceeded")]
+    BudgetExceeded { details: ErrorDetails },
+
+    /// A `km_llm::agent_loop::LoopDetector` decided the conversation was
+    /// stuck — e.g. the same tool call repeating past its configured
+    /// limit — and the loop terminated rather than running the call
+    /// again. Distinct from [`ProviderError::BudgetExceeded`], which is a
+    /// caller-set spending cap rather than a judgment that the run itself
+    /// isn't making progress.
+    #[error("loop detected: {message}")]
+    LoopDetected { message: String, details: ErrorDetails },
+
+    /// Catch-all for errors that don't map to a more specific variant yet.
+    #[error("API error: {message}")]
+    ApiError { message: String, details: ErrorDetails },
+}
+
+impl ProviderError {
+    /// The structured detail attached to this error, if any was captured.
+    /// Every variant currently carries one, but the return type stays
+    /// `Option` so a future variant constructed without response context
+    /// (e.g. a local validation failure) doesn't need a dummy `ErrorDetails`.
+    pub fn details(&self) -> Option<&ErrorDetails> {
+        match self {
+            ProviderError::RateLimitExceeded { details, .. }
+            | ProviderError::AuthenticationFailed { details }
+            | ProviderError::ContextLengthExceeded { details }
+            | ProviderError::Overloaded { details }
+            | ProviderError::Timeout { details }
+            | ProviderError::BudgetExceeded { details }
+            | ProviderError::LoopDetected { details, .. }
+            | ProviderError::ApiError { details, .. } => Some(details),
+        }
+    }
+}
+
+/// How many recent request latencies [`ProviderState`] keeps around to
+/// compute rolling percentiles from. Older samples are dropped.
+const LATENCY_WINDOW: usize = 64;
+
+/// Time-to-first-token and total duration for one request, as recorded by
+/// [`ProviderState::record_latency`].
+#[derive(Debug, Clone, Copy)]
+pub struct RequestLatency {
+    pub time_to_first_token: std::time::Duration,
+    pub total_duration: std::time::Duration,
+}
+
+/// Running counters a provider accumulates across requests, used for usage
+/// reporting and cost estimation.
+#[derive(Debug, Default, Clone)]
+pub struct ProviderState {
+    pub total_input_tokens: u64,
+    pub total_output_tokens: u64,
+    /// Subset of `total_input_tokens` served from the provider's prompt
+    /// cache. See [`UsageDelta::cached_input_tokens`].
+    pub total_cached_input_tokens: u64,
+    pub request_count: u64,
+    /// The most recent [`LATENCY_WINDOW`] request latencies, oldest first.
+    /// Hosts read [`ProviderState::latency_p50`]/[`ProviderState::latency_p95`]
+    /// rather than this directly.
+    recent_latencies: std::collections::VecDeque<RequestLatency>,
+}
+
+/// A token-usage update from a single response chunk or a provider's
+/// final usage block. Passed to [`ProviderState::apply_usage`] as each
+/// chunk arrives so usage is accurate even if the stream is aborted
+/// (cancelled by the caller, or dropped on a transient disconnect) before
+/// a final usage block ever comes — the alternative, only accounting
+/// usage from that final block, undercounts every aborted request to
+/// zero even though the provider billed for the tokens it streamed.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct UsageDelta {
+    pub input_tokens: u64,
+    pub output_tokens: u64,
+    /// Input tokens served from the provider's prompt cache (OpenAI's
+    /// `prompt_tokens_details.cached_tokens`, Anthropic's
+    /// `cache_read_input_tokens`) and therefore billed at a reduced rate.
+    /// Already included in `input_tokens`; tracked separately so callers
+    /// can report a cache hit rate instead of it disappearing into the
+    /// total.
+    pub cached_input_tokens: u64,
+}
+
+impl ProviderState {
+    /// Adds a usage delta to the running totals. Safe to call more than
+    /// once per request (e.g. once per streamed chunk) since it only ever
+    /// adds, never overwrites.
+    pub fn apply_usage(&mut self, delta: UsageDelta) {
+        self.total_input_tokens += delta.input_tokens;
+        self.total_output_tokens += delta.output_tokens;
+        self.total_cached_input_tokens += delta.cached_input_tokens;
+    }
+
+    /// Records a completed request's latency, evicting the oldest sample
+    /// once the rolling window is full.
+    pub fn record_latency(&mut self, latency: RequestLatency) {
+        if self.recent_latencies.len() == LATENCY_WINDOW {
+            self.recent_latencies.pop_front();
+        }
+        self.recent_latencies.push_back(latency);
+    }
+
+    /// The latency of the most recently completed request, if any.
+    pub fn last_latency(&self) -> Option<RequestLatency> {
+        self.recent_latencies.back().copied()
+    }
+
+    /// The 50th percentile of total request duration over the rolling
+    /// window.
+    pub fn latency_p50(&self) -> Option<std::time::Duration> {
+        self.total_duration_percentile(0.50)
+    }
+
+    /// The 95th percentile of total request duration over the rolling
+    /// window.
+    pub fn latency_p95(&self) -> Option<std::time::Duration> {
+        self.total_duration_percentile(0.95)
+    }
+
+    fn total_duration_percentile(&self, p: f64) -> Option<std::time::Duration> {
+        if self.recent_latencies.is_empty() {
+            return None;
+        }
+        let mut durations: Vec<_> = self.recent_latencies.iter().map(|l| l.total_duration).collect();
+        durations.sort();
+        let idx = ((durations.len() - 1) as f64 * p).round() as usize;
+        durations.get(idx).copied()
+    }
+}
+
+#[cfg(test)]
+mod provider_state_tests {
+    use super::*;
+
+    fn latency(millis: u64) -> RequestLatency {
+        RequestLatency { time_to_first_token: std::time::Duration::from_millis(millis), total_duration: std::time::Duration::from_millis(millis) }
+    }
+
+    #[test]
+    fn test_latency_percentiles_are_none_with_no_samples() {
+        let state = ProviderState::default();
+        assert_eq!(state.last_latency().map(|l| l.total_duration), None);
+        assert_eq!(state.latency_p50(), None);
+        assert_eq!(state.latency_p95(), None);
+    }
+
+    #[test]
+    fn test_latency_percentiles_sort_samples_regardless_of_recording_order() {
+        let mut state = ProviderState::default();
+        for millis in [100, 500, 200, 400, 300] {
+            state.record_latency(latency(millis));
+        }
+        assert_eq!(state.latency_p50(), Some(std::time::Duration::from_millis(300)));
+        assert_eq!(state.latency_p95(), Some(std::time::Duration::from_millis(500)));
+    }
+
+    #[test]
+    fn test_last_latency_returns_the_most_recently_recorded_sample() {
+        let mut state = ProviderState::default();
+        state.record_latency(latency(100));
+        state.record_latency(latency(200));
+        assert_eq!(state.last_latency().unwrap().total_duration, std::time::Duration::from_millis(200));
+    }
+
+    #[test]
+    fn test_record_latency_evicts_the_oldest_sample_once_the_window_is_full() {
+        let mut state = ProviderState::default();
+        for millis in 0..LATENCY_WINDOW as u64 {
+            state.record_latency(latency(millis));
+        }
+        // The window is now [0, 1, ..., LATENCY_WINDOW-1]; one more push
+        // should evict the oldest (0ms) rather than growing unbounded, so
+        // the window becomes [1, ..., LATENCY_WINDOW-1, 9999].
+        state.record_latency(latency(9_999));
+        let median_index = ((LATENCY_WINDOW - 1) as f64 * 0.5).round() as u64;
+        assert_eq!(state.latency_p50(), Some(std::time::Duration::from_millis(median_index + 1)));
+    }
+
+    #[test]
+    fn test_apply_usage_accumulates_across_multiple_deltas() {
+        let mut state = ProviderState::default();
+        state.apply_usage(UsageDelta { input_tokens: 10, output_tokens: 5, cached_input_tokens: 2 });
+        state.apply_usage(UsageDelta { input_tokens: 7, output_tokens: 3, cached_input_tokens: 0 });
+        assert_eq!(state.total_input_tokens, 17);
+        assert_eq!(state.total_output_tokens, 8);
+        assert_eq!(state.total_cached_input_tokens, 2);
+    }
+}
+
+/// Why a model stopped generating, normalized across providers so a host
+/// can handle "the model refused" or "a content filter tripped" the same
+/// way regardless of which backend produced it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FinishReason {
+    /// The model completed its response normally.
+    Stop,
+    /// The model stopped to invoke one or more tools.
+    ToolCalls,
+    /// `max_tokens` was hit before the model finished.
+    Length,
+    /// A content filter (safety system) stopped or redacted the response.
+    ContentFilter,
+    /// The model declined to answer (distinct from a content filter —
+    /// this is the model choosing not to, not a system blocking it).
+    Refusal,
+    /// A reason the backend returned that doesn't map to the above yet.
+    Other(String),
+}
+
+impl FinishReason {
+    /// Maps OpenAI's `finish_reason` string.
+    pub fn from_openai(reason: &str) -> Self {
+        match reason {
+            "stop" => FinishReason::Stop,
+            "tool_calls" | "function_call" => FinishReason::ToolCalls,
+            "length" => FinishReason::Length,
+            "content_filter" => FinishReason::ContentFilter,
+            other => FinishReason::Other(other.to_string()),
+        }
+    }
+
+    /// Maps Gemini's `finishReason` string.
+    pub fn from_gemini(reason: &str) -> Self {
+        match reason {
+            "STOP" => FinishReason::Stop,
+            "MAX_TOKENS" => FinishReason::Length,
+            "SAFETY" | "BLOCKLIST" | "PROHIBITED_CONTENT" | "SPII" => FinishReason::ContentFilter,
+            "RECITATION" => FinishReason::Refusal,
+            other => FinishReason::Other(other.to_string()),
+        }
+    }
+
+    /// Maps Anthropic's `stop_reason` string.
+    pub fn from_anthropic(reason: &str) -> Self {
+        match reason {
+            "end_turn" | "stop_sequence" => FinishReason::Stop,
+            "tool_use" => FinishReason::ToolCalls,
+            "max_tokens" => FinishReason::Length,
+            "refusal" => FinishReason::Refusal,
+            other => FinishReason::Other(other.to_string()),
+        }
+    }
+}
+
+#[async_trait]
+pub trait LLMProvider {
+    async fn chat(&mut self, messages: &[Message]) -> Result<Message, ProviderError>;
+
+    /// Runs the agent loop for one user turn: appends `user_message` to
+    /// history, prunes it, sends it, and resolves any tool calls until the
+    /// model stops calling tools.
+    async fn chat_loop(&mut self, user_message: Message) -> Result<Message, ProviderError>;
+
+    fn state(&self) -> &ProviderState;
+
+    /// The config this provider is currently running with. Read-only —
+    /// [`LLMProvider::update_config`] is the only sanctioned way to
+    /// change it, so callers (e.g. a session save) can rely on this
+    /// never drifting from what's actually in effect.
+    fn config(&self) -> &ProviderConfig;
+
+    fn get_history(&self) -> &[Message];
+
+    /// Replaces this provider's history wholesale, e.g. to resume a
+    /// conversation loaded from disk. Callers are responsible for any
+    /// pruning — this does not re-run `chat_loop`'s context-window check,
+    /// since the caller may want to restore history that's since been
+    /// pruned or edited by something other than this provider.
+    fn set_history(&mut self, history: Vec<Message>);
+
+    /// Counts the tokens `messages` would cost, without sending them.
+    /// Lets callers budget context before a request rather than finding
+    /// out from a [`ProviderError::ContextLengthExceeded`]. Backends with
+    /// a real counting endpoint (Anthropic's `count_tokens`, Gemini's
+    /// `countTokens`) should prefer it over estimation once wired up;
+    /// others fall back to the same heuristic `check_context_window` uses.
+    async fn count_tokens(&self, messages: &[Message]) -> Result<u32, ProviderError>;
+
+    /// Mutates this provider's [`ProviderConfig`] in place, e.g. to swap
+    /// models or temperature mid-session. Takes a boxed closure rather
+    /// than `impl FnOnce(&mut ProviderConfig)` so the trait stays
+    /// object-safe — generic methods can't be called through `dyn
+    /// LLMProvider`, which is what `AnyProvider` needs.
+    fn update_config(&mut self, f: Box<dyn FnOnce(&mut ProviderConfig) + Send>);
+}
@@ -0,0 +1,143 @@
+//! Session-level reporting: a structured summary of one agent run, built at
+//! the end of `chat_loop` for the CLI to print or persist.
+
+use std::collections::HashMap;
+use std::time::Duration;
+
+use crate::provider::{Message, ProviderState, Role};
+
+/// Aggregate tool-usage counters a `ToolRegistry` exposes at the end of a
+/// run. A minimal stand-in until the registry grows real telemetry; the
+/// shape is expected to stay source-compatible as that lands.
+#[derive(Debug, Default, Clone)]
+pub struct RegistryMetrics {
+    pub calls_per_tool: HashMap<String, u64>,
+}
+
+/// A structured summary of one agent session: how long it ran, how many
+/// conversational rounds it took, which tools were used, and how many
+/// tokens it spent. Built once at the end of a run via
+/// [`SessionReport::from`] and rendered as JSON or Markdown.
+///
+/// There's no `files_modified` field: `RegistryMetrics` only counts calls
+/// per tool name, not the paths those calls touched, so there's nothing
+/// real to report here yet. Add it back once the registry tracks that.
+#[derive(Debug, Clone)]
+pub struct SessionReport {
+    pub duration: Duration,
+    pub rounds: usize,
+    pub tools_used: HashMap<String, u64>,
+    pub input_tokens: u64,
+    pub output_tokens: u64,
+}
+
+impl SessionReport {
+    /// Builds a report from a provider's final state, a tool registry's
+    /// usage metrics, and the session transcript. `duration` is the wall
+    /// clock time the caller measured around the whole run.
+    pub fn from(
+        state: &ProviderState,
+        registry_metrics: &RegistryMetrics,
+        transcript: &[Message],
+        duration: Duration,
+    ) -> Self {
+        let rounds = transcript
+            .iter()
+            .filter(|m| m.role == Role::Assistant)
+            .count();
+
+        Self {
+            duration,
+            rounds,
+            tools_used: registry_metrics.calls_per_tool.clone(),
+            input_tokens: state.total_input_tokens,
+            output_tokens: state.total_output_tokens,
+        }
+    }
+
+    /// Renders the report as pretty-printed JSON.
+    pub fn to_json(&self) -> String {
+        let report = serde_json::json!({
+            "duration_secs": self.duration.as_secs_f64(),
+            "rounds": self.rounds,
+            "tools_used": self.tools_used,
+            "input_tokens": self.input_tokens,
+            "output_tokens": self.output_tokens,
+        });
+        serde_json::to_string_pretty(&report).unwrap_or_else(|_| "{}".to_string())
+    }
+
+    /// Renders the report as a short Markdown summary for terminal output.
+    pub fn to_markdown(&self) -> String {
+        let mut out = format!(
+            "## Session summary\n\n- Duration: {:.1}s\n- Rounds: {}\n- Tokens: {} in / {} out\n",
+            self.duration.as_secs_f64(),
+            self.rounds,
+            self.input_tokens,
+            self.output_tokens,
+        );
+        if !self.tools_used.is_empty() {
+            out.push_str("- Tools used:\n");
+            for (name, count) in &self.tools_used {
+                out.push_str(&format!("  - `{name}`: {count}\n"));
+            }
+        }
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample_report() -> SessionReport {
+        let mut tools_used = HashMap::new();
+        tools_used.insert("read_file".to_string(), 2u64);
+        SessionReport {
+            duration: Duration::from_millis(1500),
+            rounds: 3,
+            tools_used,
+            input_tokens: 100,
+            output_tokens: 50,
+        }
+    }
+
+    #[test]
+    fn test_from_counts_assistant_messages_as_rounds() {
+        let transcript = vec![
+            Message::text(Role::User, "hi"),
+            Message::text(Role::Assistant, "hello"),
+            Message::text(Role::Assistant, "again"),
+        ];
+        let report = SessionReport::from(
+            &ProviderState::default(),
+            &RegistryMetrics::default(),
+            &transcript,
+            Duration::from_secs(1),
+        );
+        assert_eq!(report.rounds, 2);
+    }
+
+    #[test]
+    fn test_to_json_escapes_tool_names_with_special_characters() {
+        let mut report = sample_report();
+        report.tools_used = HashMap::new();
+        report.tools_used.insert("quote\"tool".to_string(), 1);
+
+        let rendered = report.to_json();
+        let parsed: serde_json::Value =
+            serde_json::from_str(&rendered).expect("to_json output should be valid JSON");
+        assert_eq!(parsed["tools_used"]["quote\"tool"], 1);
+    }
+
+    #[test]
+    fn test_to_json_round_trips_report_fields() {
+        let report = sample_report();
+        let parsed: serde_json::Value =
+            serde_json::from_str(&report.to_json()).expect("to_json output should be valid JSON");
+        assert_eq!(parsed["rounds"], 3);
+        assert_eq!(parsed["input_tokens"], 100);
+        assert_eq!(parsed["output_tokens"], 50);
+        assert_eq!(parsed["tools_used"]["read_file"], 2);
+    }
+}
@@ -0,0 +1,31 @@
+//! Resolving API keys without putting them in shell history or plaintext
+//! config files: OS keyring first, then environment, then whatever was set
+//! in `km.toml`.
+
+/// Looks up an API key for `provider` (e.g. `"openai"`, `"anthropic"`,
+/// `"gemini"`), trying the OS keyring, then the provider's well-known env
+/// var, then falling back to `config_value` (typically `ProviderConfig::api_key`
+/// as loaded from `km.toml`). Returns `None` if none of the three have it.
+pub fn resolve_api_key(provider: &str, config_value: Option<&str>) -> Option<String> {
+    if let Some(key) = keyring_get(provider) {
+        return Some(key);
+    }
+    if let Some(key) = std::env::var(env_var_for(provider)).ok().filter(|v| !v.is_empty()) {
+        return Some(key);
+    }
+    config_value.filter(|v| !v.is_empty()).map(str::to_string)
+}
+
+/// Stores an API key in the OS keyring under the `km` service name, for
+/// `km-tools auth set <provider>`.
+pub fn keyring_set(provider: &str, api_key: &str) -> Result<(), keyring::Error> {
+    keyring::Entry::new("km", provider)?.set_password(api_key)
+}
+
+fn keyring_get(provider: &str) -> Option<String> {
+    keyring::Entry::new("km", provider).ok()?.get_password().ok()
+}
+
+fn env_var_for(provider: &str) -> String {
+    format!("{}_API_KEY", provider.to_uppercase())
+}
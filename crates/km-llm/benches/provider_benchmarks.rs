@@ -0,0 +1,54 @@
+//! Benchmarks for the shared HTTP client changes in `km_llm::http`:
+//! time-to-first-token and steady-state throughput, tracked so a
+//! regression in pooling/keep-alive tuning shows up before release
+//! rather than as a field report.
+//!
+//! Requires network access to the configured provider, so these are not
+//! part of `cargo test`; run explicitly with `cargo bench -p km-llm`.
+
+use std::time::Instant;
+
+use criterion::{criterion_group, criterion_main, Criterion};
+use km_core::provider::{LLMProvider, Message, ProviderConfig, Role};
+use km_llm::openai::OpenAiProvider;
+
+fn bench_time_to_first_token(c: &mut Criterion) {
+    let rt = tokio::runtime::Runtime::new().expect("tokio runtime");
+    let config = ProviderConfig::new(
+        std::env::var("OPENAI_API_KEY").unwrap_or_default(),
+        std::env::var("OPENAI_MODEL").unwrap_or_else(|_| "gpt-4o-mini".to_string()),
+    );
+
+    c.bench_function("openai_time_to_first_token", |b| {
+        b.iter(|| {
+            rt.block_on(async {
+                let mut provider = OpenAiProvider::new(config.clone());
+                let start = Instant::now();
+                let _ = provider.chat_loop(Message::text(Role::User, "Reply with one word.")).await;
+                start.elapsed()
+            })
+        });
+    });
+}
+
+fn bench_throughput(c: &mut Criterion) {
+    let rt = tokio::runtime::Runtime::new().expect("tokio runtime");
+    let config = ProviderConfig::new(
+        std::env::var("OPENAI_API_KEY").unwrap_or_default(),
+        std::env::var("OPENAI_MODEL").unwrap_or_else(|_| "gpt-4o-mini".to_string()),
+    );
+
+    c.bench_function("openai_sequential_requests_throughput", |b| {
+        b.iter(|| {
+            rt.block_on(async {
+                let mut provider = OpenAiProvider::new(config.clone());
+                for _ in 0..5 {
+                    let _ = provider.chat_loop(Message::text(Role::User, "ping")).await;
+                }
+            })
+        });
+    });
+}
+
+criterion_group!(benches, bench_time_to_first_token, bench_throughput);
+criterion_main!(benches);
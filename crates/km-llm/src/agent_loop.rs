@@ -0,0 +1,554 @@
+//! Drives a provider through a full multi-turn tool-calling conversation,
+//! on top of the single-turn `LLMProvider::chat_loop`: resolves tool
+//! calls one at a time and keeps going until the model produces a final
+//! message with none left, or a limit in [`ChatLoopConfig`] is hit.
+//!
+//! Lives here rather than on the trait since it's registry-agnostic —
+//! callers supply an `execute_tool` closure instead of this crate
+//! depending on `km-agent-tools` (which is native-only and would make
+//! `km-llm` unusable from wasm).
+
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll};
+use std::time::Duration;
+
+use km_core::provider::{ContentPart, LLMProvider, Message, ProviderError, ProviderState, ToolCall};
+use tracing::Instrument;
+
+use crate::events::{AgentEvent, LoopEvent};
+use crate::loop_detector::{LoopDetector, LoopDetectorConfig, LoopVerdict};
+#[cfg(not(target_arch = "wasm32"))]
+use crate::transcript::{TranscriptEvent, TranscriptRecorder};
+
+/// Per-run spending limits and callbacks for [`chat_loop_with_tools`].
+/// Distinct from [`km_core::provider::ProviderConfig`], which governs a
+/// single wire request, not how many tool round-trips a whole
+/// conversation may take.
+#[derive(Clone, Default)]
+pub struct ChatLoopConfig {
+    /// Stops the loop, returning [`ProviderError::BudgetExceeded`], once
+    /// the running cost would exceed this many USD. Computed from
+    /// `crate::catalog::pricing_for(model)`, so it's a no-op for models
+    /// the catalog has no price for — pair with `max_total_tokens` on
+    /// those.
+    pub max_cost: Option<f64>,
+    /// Stops the loop once total input+output tokens would exceed this,
+    /// independent of `max_cost`.
+    pub max_total_tokens: Option<u64>,
+    /// Bounds the whole run — every round and tool execution together,
+    /// not any single wire request — to at most this long. Once it
+    /// elapses, the loop stops where it is and returns a
+    /// [`ChatLoopResponse`] with [`ChatLoopFinishReason::TimedOut`]
+    /// instead of hanging indefinitely, which otherwise left CI waiting
+    /// on the test runner's own timeout to kill the whole process with
+    /// no partial transcript to debug from.
+    pub deadline: Option<Duration>,
+    /// Called once per resolved tool call with its full name/arguments.
+    /// Named for parity with `LoopEvent::ToolCallDelta` rather than
+    /// because this loop streams incremental chunks — `chat_loop` only
+    /// ever returns a fully-assembled `ToolCall`, so each call's "delta"
+    /// here is the whole thing in one shot.
+    ///
+    /// Async and fallible: a callback that did its own blocking I/O used
+    /// to be able to wedge the whole loop, and a panicking callback had
+    /// no clean way to stop it short of that. Returning `Err` aborts the
+    /// loop and surfaces as a [`ProviderError::ApiError`] carrying the
+    /// callback's message.
+    pub on_tool_call_delta:
+        Option<Arc<dyn Fn(LoopEvent) -> futures::future::BoxFuture<'static, Result<(), String>> + Send + Sync>>,
+    /// Watches every resolved tool call for signs the run is stuck (see
+    /// `crate::loop_detector`). `Mutex`-wrapped rather than taking
+    /// `&mut self` through the loop, since `ChatLoopConfig` is shared by
+    /// shared reference the same way `transcript` is.
+    pub loop_detector: Option<Arc<Mutex<LoopDetector>>>,
+    /// When set, every request, tool call, and tool result this loop
+    /// sees is appended to the recorder's JSONL file — for debugging a
+    /// runaway agent after the fact or auditing what it actually did.
+    /// Off by default, since most callers don't want a file growing on
+    /// disk for every conversation. Native-only; see `crate::transcript`.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub transcript: Option<TranscriptRecorder>,
+}
+
+impl std::fmt::Debug for ChatLoopConfig {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut debug = f.debug_struct("ChatLoopConfig");
+        debug.field("max_cost", &self.max_cost).field("max_total_tokens", &self.max_total_tokens);
+        debug.field("deadline", &self.deadline);
+        debug.field("on_tool_call_delta", &self.on_tool_call_delta.is_some());
+        debug.field("loop_detector", &self.loop_detector.is_some());
+        #[cfg(not(target_arch = "wasm32"))]
+        debug.field("transcript", &self.transcript.is_some());
+        debug.finish()
+    }
+}
+
+impl ChatLoopConfig {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_max_cost(mut self, max_cost: f64) -> Self {
+        self.max_cost = Some(max_cost);
+        self
+    }
+
+    pub fn with_max_total_tokens(mut self, max_total_tokens: u64) -> Self {
+        self.max_total_tokens = Some(max_total_tokens);
+        self
+    }
+
+    pub fn with_deadline(mut self, deadline: Duration) -> Self {
+        self.deadline = Some(deadline);
+        self
+    }
+
+    pub fn with_on_tool_call_delta<Fut>(mut self, callback: impl Fn(LoopEvent) -> Fut + Send + Sync + 'static) -> Self
+    where
+        Fut: std::future::Future<Output = Result<(), String>> + Send + 'static,
+    {
+        self.on_tool_call_delta =
+            Some(Arc::new(move |event| Box::pin(callback(event)) as futures::future::BoxFuture<'static, Result<(), String>>));
+        self
+    }
+
+    pub fn with_loop_detector(mut self, config: LoopDetectorConfig) -> Self {
+        self.loop_detector = Some(Arc::new(Mutex::new(LoopDetector::new(config))));
+        self
+    }
+
+    /// Invokes `on_tool_call_delta` if one is set, turning a callback
+    /// error into the same [`ProviderError`] a wire failure would
+    /// produce, so `chat_loop_with_tools` can abort with one `?` either
+    /// way.
+    async fn notify_tool_call_delta(&self, event: LoopEvent) -> Result<(), ProviderError> {
+        if let Some(callback) = &self.on_tool_call_delta {
+            callback(event).await.map_err(|message| ProviderError::ApiError { message, details: Default::default() })?;
+        }
+        Ok(())
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn with_transcript(mut self, recorder: TranscriptRecorder) -> Self {
+        self.transcript = Some(recorder);
+        self
+    }
+
+    /// Records `event` if a transcript recorder is configured. Write
+    /// failures are swallowed — a full disk is a debugging aid going
+    /// dark, not a reason to fail a live conversation.
+    #[cfg(not(target_arch = "wasm32"))]
+    fn record(&self, event: TranscriptEvent) {
+        if let Some(recorder) = &self.transcript {
+            let _ = recorder.record(event);
+        }
+    }
+
+    /// Feeds `call` to `loop_detector` if one is configured, returning
+    /// [`LoopVerdict::Ok`] when there isn't one.
+    fn check_tool_call(&self, call: &ToolCall) -> LoopVerdict {
+        match &self.loop_detector {
+            Some(detector) => detector.lock().expect("loop detector lock poisoned").observe_tool_call(call),
+            None => LoopVerdict::Ok,
+        }
+    }
+
+    /// Feeds a tool's result to `loop_detector` if one is configured,
+    /// returning [`LoopVerdict::Ok`] when there isn't one.
+    fn check_tool_result(&self, tool_name: &str, is_error: bool) -> LoopVerdict {
+        match &self.loop_detector {
+            Some(detector) => detector.lock().expect("loop detector lock poisoned").observe_tool_result(tool_name, is_error),
+            None => LoopVerdict::Ok,
+        }
+    }
+
+    /// Feeds an assistant turn's text to `loop_detector` if one is
+    /// configured. Called on every turn regardless of whether it carries
+    /// tool calls, since a content loop doesn't need any — unlike
+    /// `check_tool_call`/`check_tool_result`, a `Warn` verdict here has no
+    /// tool result to append to, so it's logged via `tracing` instead and
+    /// left for a host watching spans (e.g. through `km_core::otel`) to
+    /// act on.
+    fn check_assistant_content(&self, text: &str) -> Result<(), ProviderError> {
+        let Some(detector) = &self.loop_detector else {
+            return Ok(());
+        };
+        match detector.lock().expect("loop detector lock poisoned").observe_assistant_message(text) {
+            LoopVerdict::Terminate { message, .. } => Err(ProviderError::LoopDetected { message, details: Default::default() }),
+            LoopVerdict::Warn { message, .. } => {
+                tracing::warn!(%message, "assistant content loop detected");
+                Ok(())
+            }
+            LoopVerdict::Ok => Ok(()),
+        }
+    }
+
+    /// Checks `state` against whichever limits are set, returning
+    /// [`ProviderError::BudgetExceeded`] the first time either is crossed.
+    fn check(&self, model: &str, state: &ProviderState) -> Result<(), ProviderError> {
+        if let Some(max_total_tokens) = self.max_total_tokens {
+            let total = state.total_input_tokens + state.total_output_tokens;
+            if total > max_total_tokens {
+                return Err(ProviderError::BudgetExceeded { details: Default::default() });
+            }
+        }
+        if let Some(max_cost) = self.max_cost {
+            let pricing = crate::catalog::pricing_for(model);
+            let cost = pricing.input_cost_per_million.unwrap_or(0.0) * state.total_input_tokens as f64 / 1_000_000.0
+                + pricing.output_cost_per_million.unwrap_or(0.0) * state.total_output_tokens as f64 / 1_000_000.0;
+            if cost > max_cost {
+                return Err(ProviderError::BudgetExceeded { details: Default::default() });
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Why a [`chat_loop_with_tools`] run stopped.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChatLoopFinishReason {
+    /// The model produced a final message with no further tool calls.
+    Completed,
+    /// `ChatLoopConfig::deadline` elapsed before the loop reached
+    /// `Completed`. `ChatLoopResponse::message` is whatever the model's
+    /// last completed turn was, not a final answer.
+    TimedOut,
+}
+
+/// The outcome of a [`chat_loop_with_tools`] run.
+#[derive(Debug, Clone)]
+pub struct ChatLoopResponse {
+    pub message: Message,
+    pub finish_reason: ChatLoopFinishReason,
+}
+
+/// Runs `provider` through `user_message` and, repeatedly, whatever tool
+/// calls its response carries, until a final assistant message with no
+/// tool calls is produced or `loop_config` aborts the run.
+///
+/// `execute_tool` resolves one [`ToolCall`] into the `Message` to feed
+/// back as its result; callers own their own tool registry (e.g.
+/// `km-agent-tools`) so this function stays registry-agnostic.
+///
+/// Tool calls are resolved one at a time, each its own `chat_loop`
+/// round-trip, since [`LLMProvider::chat_loop`] only accepts a single
+/// message — a model that requests several tools in one turn costs one
+/// extra round-trip per call beyond the first until that trait grows a
+/// batched variant.
+#[tracing::instrument(skip(provider, user_message, loop_config, execute_tool), fields(model = %model))]
+pub async fn chat_loop_with_tools<P, F, Fut>(
+    provider: &mut P,
+    model: &str,
+    user_message: Message,
+    loop_config: &ChatLoopConfig,
+    mut execute_tool: F,
+) -> Result<ChatLoopResponse, ProviderError>
+where
+    P: LLMProvider + ?Sized,
+    F: FnMut(ToolCall) -> Fut,
+    Fut: std::future::Future<Output = Message>,
+{
+    let run = async {
+        // tool_count is always 0 here: this function doesn't know how many
+        // tools the caller's registry exposes, only that calls are being
+        // resolved through `execute_tool`.
+        #[cfg(not(target_arch = "wasm32"))]
+        loop_config.record(TranscriptEvent::Request {
+            model: model.to_string(),
+            message_count: provider.get_history().len(),
+            tool_count: 0,
+        });
+        let mut response = provider.chat_loop(user_message).await?;
+        loop_config.check(model, provider.state())?;
+        #[cfg(not(target_arch = "wasm32"))]
+        loop_config.record(TranscriptEvent::AssistantMessage { message: response.clone() });
+        loop_config.check_assistant_content(&response.text_content())?;
+        loop {
+            if response.tool_calls.is_empty() {
+                return Ok(response);
+            }
+            // A turn can request several tools at once; each is still its own
+            // `chat_loop` round-trip, so the response keeps getting replaced
+            // as calls are resolved, and only the very last one is inspected
+            // for further tool calls once the batch is done.
+            for (index, call) in std::mem::take(&mut response.tool_calls).into_iter().enumerate() {
+                // A span per round rather than `.entered()` held across the
+                // `.await`s below, which tracing's own docs warn against —
+                // an async task can move between threads mid-await, and a
+                // plain guard doesn't follow it.
+                let round_span = tracing::info_span!("round", index, tool = %call.name);
+                async {
+                    let tool_name = call.name.clone();
+                    loop_config
+                        .notify_tool_call_delta(LoopEvent::ToolCallDelta {
+                            index: index as u32,
+                            id: Some(call.id.clone()),
+                            name_delta: Some(call.name.clone()),
+                            arguments_delta: Some(call.arguments.clone()),
+                        })
+                        .await?;
+                    #[cfg(not(target_arch = "wasm32"))]
+                    loop_config.record(TranscriptEvent::ToolCall {
+                        id: call.id.clone(),
+                        name: call.name.clone(),
+                        arguments: call.arguments.clone(),
+                    });
+                    let tool_result = match loop_config.check_tool_call(&call) {
+                        LoopVerdict::Terminate { message, .. } => {
+                            return Err(ProviderError::LoopDetected { message, details: Default::default() });
+                        }
+                        LoopVerdict::Warn { message, .. } => {
+                            let mut result = execute_tool(call).await;
+                            // Appended rather than replacing the tool's
+                            // own output, so the model still sees what
+                            // the call actually returned alongside the
+                            // nudge to try something else.
+                            result.content.push(ContentPart::Text(format!("\n\n[loop detector] {message}")));
+                            result
+                        }
+                        LoopVerdict::Ok => execute_tool(call).await,
+                    };
+                    let tool_result = match loop_config.check_tool_result(&tool_name, tool_result.is_error) {
+                        LoopVerdict::Terminate { message, .. } => {
+                            return Err(ProviderError::LoopDetected { message, details: Default::default() });
+                        }
+                        LoopVerdict::Warn { message, .. } => {
+                            let mut result = tool_result;
+                            result.content.push(ContentPart::Text(format!("\n\n[loop detector] {message}")));
+                            result
+                        }
+                        LoopVerdict::Ok => tool_result,
+                    };
+                    #[cfg(not(target_arch = "wasm32"))]
+                    loop_config.record(TranscriptEvent::ToolResult {
+                        id: tool_result.tool_call_id.clone().unwrap_or_default(),
+                        is_error: tool_result.is_error,
+                        content: tool_result.text_content(),
+                    });
+                    #[cfg(not(target_arch = "wasm32"))]
+                    loop_config.record(TranscriptEvent::Request {
+                        model: model.to_string(),
+                        message_count: provider.get_history().len(),
+                        tool_count: 0,
+                    });
+                    response = provider.chat_loop(tool_result).await?;
+                    loop_config.check(model, provider.state())?;
+                    #[cfg(not(target_arch = "wasm32"))]
+                    loop_config.record(TranscriptEvent::AssistantMessage { message: response.clone() });
+                    loop_config.check_assistant_content(&response.text_content())?;
+                    Ok::<(), ProviderError>(())
+                }
+                .instrument(round_span)
+                .await?;
+            }
+        }
+    };
+
+    let Some(deadline) = loop_config.deadline else {
+        return run.await.map(|message| ChatLoopResponse { message, finish_reason: ChatLoopFinishReason::Completed });
+    };
+    match crate::time::timeout(deadline, run).await {
+        Ok(result) => {
+            result.map(|message| ChatLoopResponse { message, finish_reason: ChatLoopFinishReason::Completed })
+        }
+        Err(()) => {
+            // The in-flight round is simply dropped — whatever `chat_loop`
+            // call was pending doesn't get to finish, but everything
+            // already recorded in `provider`'s history up to that point
+            // stands, so the caller gets the last completed turn rather
+            // than nothing.
+            let last_assistant = provider
+                .get_history()
+                .iter()
+                .rev()
+                .find(|message| message.role == km_core::provider::Role::Assistant)
+                .cloned()
+                .unwrap_or_default();
+            Ok(ChatLoopResponse { message: last_assistant, finish_reason: ChatLoopFinishReason::TimedOut })
+        }
+    }
+}
+
+/// An [`AgentEvent`] source backed by a running
+/// [`chat_loop_with_tools_stream`] task. A thin [`futures::Stream`] wrapper
+/// around the underlying channel rather than the channel itself, so
+/// callers can use `StreamExt` combinators (`next`, `take_while`, ...)
+/// instead of polling `recv` by hand.
+pub struct AgentEventStream {
+    receiver: tokio::sync::mpsc::UnboundedReceiver<AgentEvent>,
+}
+
+impl futures::Stream for AgentEventStream {
+    type Item = AgentEvent;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.receiver.poll_recv(cx)
+    }
+}
+
+/// Stream-based alternative to [`chat_loop_with_tools`] for async UIs:
+/// instead of invoking `loop_config.on_tool_call_delta` synchronously (and
+/// having nowhere to put backpressure or a fallible step — see
+/// `ChatLoopConfig`'s doc comment), runs the same tool-resolving loop on
+/// its own task and hands back an [`AgentEventStream`] the caller awaits
+/// at its own pace. A slow consumer simply doesn't poll; events queue on
+/// the underlying channel rather than blocking the loop.
+///
+/// Takes `provider` and `execute_tool` by value (not `&mut`/`&`) since
+/// both need to outlive this call on the spawned task. Transcript
+/// recording, `loop_config.check`'s spend limits, the loop detector, and
+/// `loop_config.deadline` all still apply, exactly as they do in
+/// `chat_loop_with_tools` — only the delivery mechanism for progress
+/// events differs. A detector `Terminate` or an elapsed deadline ends the
+/// stream with a final `AgentEvent::Done` rather than an `Err`, since
+/// this function has no error channel to return one on.
+///
+/// Native-only: spawns the loop as its own task, the same requirement as
+/// `loop_handle::spawn_chat_loop`.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn chat_loop_with_tools_stream<P, F, Fut>(
+    mut provider: P,
+    model: String,
+    user_message: Message,
+    loop_config: ChatLoopConfig,
+    mut execute_tool: F,
+) -> AgentEventStream
+where
+    P: LLMProvider + Send + 'static,
+    F: FnMut(ToolCall) -> Fut + Send + 'static,
+    Fut: std::future::Future<Output = Message> + Send,
+{
+    let (tx, rx) = tokio::sync::mpsc::unbounded_channel();
+    let span = tracing::info_span!("chat_loop_with_tools_stream", model = %model);
+
+    tokio::spawn(
+        async move {
+            let send = |tx: &tokio::sync::mpsc::UnboundedSender<AgentEvent>, event: AgentEvent| {
+                // The receiver side dropping (consumer lost interest) just
+                // ends the task early; it isn't a loop failure to report.
+                let _ = tx.send(event);
+            };
+
+            let deadline = loop_config.deadline;
+            let run = async {
+                loop_config.record(TranscriptEvent::Request {
+                    model: model.clone(),
+                    message_count: provider.get_history().len(),
+                    tool_count: 0,
+                });
+                let mut response = match provider.chat_loop(user_message).await {
+                    Ok(response) => response,
+                    Err(_) => return,
+                };
+                if loop_config.check(&model, provider.state()).is_err() {
+                    return;
+                }
+                loop_config.record(TranscriptEvent::AssistantMessage { message: response.clone() });
+                if let Err(error) = loop_config.check_assistant_content(&response.text_content()) {
+                    send(&tx, AgentEvent::Content(error.to_string()));
+                    send(&tx, AgentEvent::Done);
+                    return;
+                }
+                send(&tx, AgentEvent::Content(response.text_content()));
+
+                loop {
+                    if response.tool_calls.is_empty() {
+                        send(&tx, AgentEvent::Done);
+                        return;
+                    }
+                    for call in std::mem::take(&mut response.tool_calls) {
+                        send(
+                            &tx,
+                            AgentEvent::ToolCall {
+                                id: call.id.clone(),
+                                name: call.name.clone(),
+                                arguments: call.arguments.clone(),
+                            },
+                        );
+                        loop_config.record(TranscriptEvent::ToolCall {
+                            id: call.id.clone(),
+                            name: call.name.clone(),
+                            arguments: call.arguments.clone(),
+                        });
+                        let tool_name = call.name.clone();
+                        let tool_result = match loop_config.check_tool_call(&call) {
+                            LoopVerdict::Terminate { message, .. } => {
+                                send(&tx, AgentEvent::Content(message));
+                                send(&tx, AgentEvent::Done);
+                                return;
+                            }
+                            LoopVerdict::Warn { message, .. } => {
+                                let mut result = execute_tool(call).await;
+                                result.content.push(ContentPart::Text(format!("\n\n[loop detector] {message}")));
+                                result
+                            }
+                            LoopVerdict::Ok => execute_tool(call).await,
+                        };
+                        let tool_result = match loop_config.check_tool_result(&tool_name, tool_result.is_error) {
+                            LoopVerdict::Terminate { message, .. } => {
+                                send(&tx, AgentEvent::Content(message));
+                                send(&tx, AgentEvent::Done);
+                                return;
+                            }
+                            LoopVerdict::Warn { message, .. } => {
+                                let mut result = tool_result;
+                                result.content.push(ContentPart::Text(format!("\n\n[loop detector] {message}")));
+                                result
+                            }
+                            LoopVerdict::Ok => tool_result,
+                        };
+                        send(
+                            &tx,
+                            AgentEvent::ToolResult {
+                                id: tool_result.tool_call_id.clone().unwrap_or_default(),
+                                is_error: tool_result.is_error,
+                                content: tool_result.text_content(),
+                            },
+                        );
+                        loop_config.record(TranscriptEvent::ToolResult {
+                            id: tool_result.tool_call_id.clone().unwrap_or_default(),
+                            is_error: tool_result.is_error,
+                            content: tool_result.text_content(),
+                        });
+                        loop_config.record(TranscriptEvent::Request {
+                            model: model.clone(),
+                            message_count: provider.get_history().len(),
+                            tool_count: 0,
+                        });
+                        response = match provider.chat_loop(tool_result).await {
+                            Ok(response) => response,
+                            Err(_) => return,
+                        };
+                        if loop_config.check(&model, provider.state()).is_err() {
+                            return;
+                        }
+                        loop_config.record(TranscriptEvent::AssistantMessage { message: response.clone() });
+                        if let Err(error) = loop_config.check_assistant_content(&response.text_content()) {
+                            send(&tx, AgentEvent::Content(error.to_string()));
+                            send(&tx, AgentEvent::Done);
+                            return;
+                        }
+                        send(&tx, AgentEvent::Content(response.text_content()));
+                        send(&tx, AgentEvent::RoundBoundary);
+                    }
+                }
+            };
+
+            match deadline {
+                // The in-flight round is simply dropped, same as
+                // `chat_loop_with_tools`'s deadline handling — whatever
+                // was pending doesn't get to finish.
+                Some(deadline) => {
+                    if crate::time::timeout(deadline, run).await.is_err() {
+                        send(&tx, AgentEvent::Done);
+                    }
+                }
+                None => run.await,
+            }
+        }
+        .instrument(span),
+    );
+
+    AgentEventStream { receiver: rx }
+}
@@ -0,0 +1,490 @@
+//! Anthropic Claude backend.
+
+use async_trait::async_trait;
+
+use crate::helpers::prune_history;
+use km_core::provider::{
+    ContentPart, ErrorDetails, LLMProvider, Message, ProviderConfig, ProviderError, ProviderState, Role, ToolCall, ToolChoice, UsageDelta,
+};
+
+/// Maps a [`ToolChoice`] to Anthropic's `tool_choice` wire shape, folding
+/// in `disable_parallel_tool_use` since Anthropic expresses that as a
+/// sibling field on the same object rather than a top-level request field.
+fn tool_choice_json(choice: &ToolChoice, parallel_tool_calls: bool) -> serde_json::Value {
+    let mut value = match choice {
+        ToolChoice::Auto => serde_json::json!({ "type": "auto" }),
+        ToolChoice::None => serde_json::json!({ "type": "none" }),
+        ToolChoice::Required => serde_json::json!({ "type": "any" }),
+        ToolChoice::Specific(name) => serde_json::json!({ "type": "tool", "name": name }),
+    };
+    if !parallel_tool_calls {
+        value["disable_parallel_tool_use"] = serde_json::json!(true);
+    }
+    value
+}
+
+/// Maps one canonical [`Message`] to an Anthropic Messages API wire
+/// message. Callers filter out `Role::System` first — Anthropic carries
+/// that as a top-level `system` field, not a message in this array.
+///
+/// `pub(crate)` because Bedrock's Claude family speaks this same Messages
+/// format almost verbatim (see `bedrock.rs`), so it reuses this instead
+/// of duplicating the role-mapping logic.
+pub(crate) fn anthropic_message_json(message: &Message) -> serde_json::Value {
+    match message.role {
+        Role::Tool => serde_json::json!({
+            "role": "user",
+            "content": [{
+                "type": "tool_result",
+                "tool_use_id": message.tool_call_id,
+                "content": message.text_content(),
+                "is_error": message.is_error,
+            }],
+        }),
+        Role::Assistant if !message.tool_calls.is_empty() => {
+            let mut content = Vec::new();
+            let text = message.text_content();
+            if !text.is_empty() {
+                content.push(serde_json::json!({ "type": "text", "text": text }));
+            }
+            for call in &message.tool_calls {
+                let input: serde_json::Value = serde_json::from_str(&call.arguments).unwrap_or(serde_json::json!({}));
+                content.push(serde_json::json!({ "type": "tool_use", "id": call.id, "name": call.name, "input": input }));
+            }
+            serde_json::json!({ "role": "assistant", "content": content })
+        }
+        Role::Assistant => serde_json::json!({ "role": "assistant", "content": message.text_content() }),
+        Role::User | Role::System => serde_json::json!({ "role": "user", "content": message.text_content() }),
+    }
+}
+
+const MAX_HISTORY_MESSAGES: usize = 200;
+
+/// Default API base, overridable via `ANTHROPIC_API_BASE` or
+/// [`AnthropicProvider::with_base_url`].
+const DEFAULT_BASE_URL: &str = "https://api.anthropic.com/v1";
+
+/// Default `anthropic-version` header, used when a model's preset (see
+/// `models.rs`) doesn't specify one.
+const DEFAULT_API_VERSION: &str = "2023-06-01";
+
+pub struct AnthropicProvider {
+    config: ProviderConfig,
+    client: reqwest::Client,
+    base_url: String,
+    api_version: String,
+    /// `anthropic-beta` feature flags (prompt caching, extended output,
+    /// etc.), joined with commas into one header value.
+    beta_features: Vec<String>,
+    /// Anthropic server-side tools (`computer_use`, `text_editor`,
+    /// `bash`, `web_search`) passed through as-is in each request's
+    /// `tools` array, alongside any client-side tools the host registers.
+    server_tools: Vec<serde_json::Value>,
+    history: Vec<Message>,
+    state: ProviderState,
+}
+
+impl AnthropicProvider {
+    pub fn new(config: ProviderConfig) -> Self {
+        let base_url = std::env::var("ANTHROPIC_API_BASE").unwrap_or_else(|_| DEFAULT_BASE_URL.to_string());
+        Self {
+            config,
+            client: crate::http::shared_client(),
+            base_url,
+            api_version: DEFAULT_API_VERSION.to_string(),
+            beta_features: Vec::new(),
+            server_tools: Vec::new(),
+            history: Vec::new(),
+            state: ProviderState::default(),
+        }
+    }
+
+    pub fn with_base_url(mut self, base_url: impl Into<String>) -> Self {
+        self.base_url = base_url.into();
+        self
+    }
+
+    /// Overrides the `anthropic-version` header, e.g. to pin to an older
+    /// version while a migration is in flight.
+    pub fn with_api_version(mut self, version: impl Into<String>) -> Self {
+        self.api_version = version.into();
+        self
+    }
+
+    /// Enables an `anthropic-beta` feature flag (e.g.
+    /// `"prompt-caching-2024-07-31"`, `"output-128k-2025-02-19"`).
+    pub fn with_beta_feature(mut self, feature: impl Into<String>) -> Self {
+        self.beta_features.push(feature.into());
+        self
+    }
+
+    /// Adds a server-side tool (e.g. `{"type": "bash_20250124", "name":
+    /// "bash"}`) to every request's `tools` array. Passed through
+    /// verbatim — Anthropic executes these itself, so km never sees the
+    /// invocation, only the resulting `tool_use`/`tool_result` turns.
+    pub fn with_server_tool(mut self, tool: serde_json::Value) -> Self {
+        self.server_tools.push(tool);
+        self
+    }
+
+    /// Lists models currently served at `self.base_url`, so callers can
+    /// discover new releases instead of relying solely on the static
+    /// `models::preset_for` table.
+    pub async fn list_models(&self) -> Result<Vec<crate::catalog::ModelInfo>, ProviderError> {
+        let _ = (&self.client, format!("{}/models", self.base_url), self.request_headers());
+        Err(ProviderError::ApiError {
+            message: "not yet implemented".to_string(),
+            details: Default::default(),
+        })
+    }
+
+    fn request_headers(&self) -> reqwest::header::HeaderMap {
+        let mut headers = reqwest::header::HeaderMap::new();
+        if let Ok(value) = self.api_version.parse() {
+            headers.insert("anthropic-version", value);
+        }
+        if !self.beta_features.is_empty() {
+            if let Ok(value) = self.beta_features.join(",").parse() {
+                headers.insert("anthropic-beta", value);
+            }
+        }
+        headers
+    }
+
+    /// Builds the JSON request body for the Messages API: Anthropic keeps
+    /// `system` out of the `messages` array entirely, assistant turns
+    /// that called tools carry `tool_use` blocks, and a tool's result
+    /// comes back as a `user`-role `tool_result` block rather than its
+    /// own role — so each canonical `Message` needs translating by role,
+    /// not just passing `text_content()` straight through.
+    fn build_request_body(&self, messages: &[Message]) -> serde_json::Value {
+        let system_text = messages.iter().filter(|m| m.role == Role::System).map(|m| m.text_content()).collect::<Vec<_>>().join("\n\n");
+        let wire_messages: Vec<_> = messages.iter().filter(|m| m.role != Role::System).map(anthropic_message_json).collect();
+        let mut body = serde_json::json!({
+            "model": self.config.model,
+            "max_tokens": self.config.max_tokens,
+            "messages": wire_messages,
+            "tools": self.server_tools,
+            "tool_choice": tool_choice_json(&self.config.tool_choice, self.config.parallel_tool_calls),
+        });
+        if !system_text.is_empty() {
+            body["system"] = serde_json::json!(system_text);
+        }
+        // Some models (e.g. the reasoning-style ones) don't honor
+        // `temperature`; consult the per-model preset instead of sending
+        // a parameter the model ignores, which used to mislead callers
+        // into thinking it had an effect.
+        if crate::models::preset_for(&self.config.model).honors_temperature {
+            body["temperature"] = serde_json::json!(self.config.temperature);
+        }
+        self.apply_response_format(&mut body);
+        body
+    }
+
+    /// Parses a Messages API response body into a canonical [`Message`],
+    /// recording its usage against `self.state`.
+    fn parse_response(&mut self, body: &serde_json::Value) -> Result<Message, ProviderError> {
+        if let Some(usage) = body.get("usage") {
+            self.state.apply_usage(UsageDelta {
+                input_tokens: usage.get("input_tokens").and_then(|v| v.as_u64()).unwrap_or(0),
+                output_tokens: usage.get("output_tokens").and_then(|v| v.as_u64()).unwrap_or(0),
+                cached_input_tokens: usage.get("cache_read_input_tokens").and_then(|v| v.as_u64()).unwrap_or(0),
+            });
+        }
+        let blocks = body.get("content").and_then(|v| v.as_array()).ok_or_else(|| ProviderError::ApiError {
+            message: "Anthropic response had no content blocks".to_string(),
+            details: ErrorDetails { raw_body: Some(body.to_string()), ..Default::default() },
+        })?;
+
+        let mut text = String::new();
+        let mut tool_calls = Vec::new();
+        for block in blocks {
+            match block.get("type").and_then(|v| v.as_str()) {
+                Some("text") => text.push_str(block.get("text").and_then(|v| v.as_str()).unwrap_or_default()),
+                Some("tool_use") => tool_calls.push(ToolCall {
+                    id: block.get("id").and_then(|v| v.as_str()).unwrap_or_default().to_string(),
+                    name: block.get("name").and_then(|v| v.as_str()).unwrap_or_default().to_string(),
+                    arguments: block.get("input").map(|v| v.to_string()).unwrap_or_else(|| "{}".to_string()),
+                }),
+                _ => {}
+            }
+        }
+
+        Ok(Message { role: Role::Assistant, content: vec![ContentPart::Text(text)], tool_calls, ..Default::default() })
+    }
+
+    /// Maps an Anthropic error response body's `error.type` to a
+    /// [`ProviderError`] variant, falling back to
+    /// [`crate::http::classify_http_error`] for anything the body alone
+    /// doesn't distinguish.
+    fn classify_error(status: reqwest::StatusCode, body: &serde_json::Value, retry_after: Option<std::time::Duration>) -> ProviderError {
+        let error_type = body.pointer("/error/type").and_then(|v| v.as_str()).map(str::to_string);
+        let message = body.pointer("/error/message").and_then(|v| v.as_str()).unwrap_or("unknown Anthropic error").to_string();
+        let details = ErrorDetails {
+            http_status: Some(status.as_u16()),
+            provider_error_type: error_type.clone(),
+            provider_error_code: None,
+            request_id: None,
+            raw_body: Some(body.to_string()),
+        };
+        match error_type.as_deref() {
+            Some("rate_limit_error") => ProviderError::RateLimitExceeded { retry_after, details },
+            Some("authentication_error") | Some("permission_error") => ProviderError::AuthenticationFailed { details },
+            Some("overloaded_error") => ProviderError::Overloaded { details },
+            Some("invalid_request_error") if message.to_lowercase().contains("context") || message.to_lowercase().contains("too long") => {
+                ProviderError::ContextLengthExceeded { details }
+            }
+            Some("invalid_request_error") | Some("not_found_error") => ProviderError::ApiError { message, details },
+            _ => crate::http::classify_http_error(status, retry_after, details),
+        }
+    }
+
+    /// Anthropic has no native structured-output mode, so
+    /// [`km_core::provider::ResponseFormat::Json`]/`JsonSchema` are
+    /// emulated: a system instruction describing the required shape, plus
+    /// an assistant-turn prefill of `"{"` so the model's first token is
+    /// already committed to JSON instead of a conversational preamble.
+    fn apply_response_format(&self, body: &mut serde_json::Value) {
+        let instruction = match &self.config.response_format {
+            km_core::provider::ResponseFormat::Text => return,
+            km_core::provider::ResponseFormat::Json => {
+                "Respond with JSON only, no surrounding prose.".to_string()
+            }
+            km_core::provider::ResponseFormat::JsonSchema { name, schema } => format!(
+                "Respond with JSON only, no surrounding prose, matching this schema named \"{name}\":\n{schema}"
+            ),
+        };
+        let system = match body.get("system").and_then(|v| v.as_str()) {
+            Some(existing) if !existing.is_empty() => format!("{existing}\n\n{instruction}"),
+            _ => instruction,
+        };
+        body["system"] = serde_json::json!(system);
+        if let Some(messages) = body["messages"].as_array_mut() {
+            messages.push(serde_json::json!({ "role": "assistant", "content": "{" }));
+        }
+    }
+}
+
+#[async_trait]
+impl LLMProvider for AnthropicProvider {
+    #[tracing::instrument(skip(self, messages), fields(provider = "anthropic", model = %self.config.model, message_count = messages.len()))]
+    async fn chat(&mut self, messages: &[Message]) -> Result<Message, ProviderError> {
+        self.state.request_count += 1;
+        // Once SSE parsing lands, `input_json_delta` events should feed a
+        // `ToolCallAssembler` and forward `LoopEvent::ToolCallDelta` the
+        // way `gemini.rs` does; this is the non-streaming path.
+        let body = self.build_request_body(messages);
+        crate::time::with_request_timeout(self.config.request_timeout, async {
+            let started = std::time::Instant::now();
+            let response = self
+                .client
+                .post(format!("{}/messages", self.base_url))
+                .header("x-api-key", &self.config.api_key)
+                .headers(self.request_headers())
+                .json(&body)
+                .send()
+                .await
+                .map_err(|err| ProviderError::ApiError {
+                    message: format!("Anthropic request failed: {err}"),
+                    details: Default::default(),
+                })?;
+
+            let status = response.status();
+            let retry_after = crate::http::retry_after(response.headers());
+            let text = response.text().await.map_err(|err| ProviderError::ApiError {
+                message: format!("failed to read Anthropic response body: {err}"),
+                details: Default::default(),
+            })?;
+            let json: serde_json::Value = serde_json::from_str(&text).map_err(|err| ProviderError::ApiError {
+                message: format!("failed to parse Anthropic response as JSON: {err}"),
+                details: ErrorDetails { http_status: Some(status.as_u16()), raw_body: Some(text.clone()), ..Default::default() },
+            })?;
+
+            if !status.is_success() {
+                return Err(Self::classify_error(status, &json, retry_after));
+            }
+
+            let message = self.parse_response(&json)?;
+            self.state.record_latency(km_core::provider::RequestLatency {
+                time_to_first_token: started.elapsed(),
+                total_duration: started.elapsed(),
+            });
+            Ok(message)
+        })
+        .await
+    }
+
+    #[tracing::instrument(skip(self, user_message), fields(provider = "anthropic", model = %self.config.model, history_len))]
+    async fn chat_loop(&mut self, user_message: Message) -> Result<Message, ProviderError> {
+        self.history.push(user_message);
+        self.history = prune_history(&self.history, MAX_HISTORY_MESSAGES);
+        tracing::Span::current().record("history_len", self.history.len());
+
+        crate::tokens::check_context_window(&self.config.model, &self.history, self.config.max_tokens)?;
+        km_core::provider::log_request_summary(&self.config, "anthropic", self.history.len(), 0);
+        let history = self.history.clone();
+        crate::retry::retry_with_backoff(crate::retry::RetryPolicy::default(), self, crate::retry::hrtb_attempt(move |s: &mut AnthropicProvider| {
+            let history = history.clone();
+            Box::pin(async move { s.chat(&history).await })
+        })).await
+    }
+
+    fn state(&self) -> &ProviderState {
+        &self.state
+    }
+
+    fn config(&self) -> &ProviderConfig {
+        &self.config
+    }
+
+    fn get_history(&self) -> &[Message] {
+        &self.history
+    }
+
+    fn set_history(&mut self, history: Vec<Message>) {
+        self.history = history;
+    }
+
+    /// Anthropic exposes a real `count_tokens` endpoint, but it's a
+    /// separate signed request just to get a number back; until that's
+    /// worth the extra round trip, fall back to the same character-based
+    /// estimate OpenAI and Bedrock use.
+    async fn count_tokens(&self, messages: &[Message]) -> Result<u32, ProviderError> {
+        Ok(messages.iter().map(crate::tokens::estimate_message_tokens).sum())
+    }
+
+    fn update_config(&mut self, f: Box<dyn FnOnce(&mut ProviderConfig) + Send>) {
+        f(&mut self.config);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn provider() -> AnthropicProvider {
+        AnthropicProvider::new(ProviderConfig::new("test-key", "claude-3-5-sonnet-latest"))
+    }
+
+    #[test]
+    fn test_tool_choice_json_maps_every_variant() {
+        assert_eq!(tool_choice_json(&ToolChoice::Auto, true)["type"], "auto");
+        assert_eq!(tool_choice_json(&ToolChoice::None, true)["type"], "none");
+        assert_eq!(tool_choice_json(&ToolChoice::Required, true)["type"], "any");
+        let specific = tool_choice_json(&ToolChoice::Specific("get_weather".to_string()), true);
+        assert_eq!(specific["type"], "tool");
+        assert_eq!(specific["name"], "get_weather");
+    }
+
+    #[test]
+    fn test_tool_choice_json_disables_parallel_tool_use() {
+        let choice = tool_choice_json(&ToolChoice::Auto, false);
+        assert_eq!(choice["disable_parallel_tool_use"], true);
+    }
+
+    #[test]
+    fn test_anthropic_message_json_maps_a_tool_result_message() {
+        let message = Message {
+            role: Role::Tool,
+            tool_call_id: Some("call_1".to_string()),
+            is_error: true,
+            ..Message::text(Role::Tool, "boom")
+        };
+        let json = anthropic_message_json(&message);
+        assert_eq!(json["role"], "user");
+        assert_eq!(json["content"][0]["type"], "tool_result");
+        assert_eq!(json["content"][0]["tool_use_id"], "call_1");
+        assert_eq!(json["content"][0]["is_error"], true);
+    }
+
+    #[test]
+    fn test_anthropic_message_json_includes_tool_use_blocks() {
+        let message = Message {
+            role: Role::Assistant,
+            tool_calls: vec![ToolCall { id: "call_1".to_string(), name: "get_weather".to_string(), arguments: "{\"city\":\"nyc\"}".to_string() }],
+            ..Message::text(Role::Assistant, "checking")
+        };
+        let json = anthropic_message_json(&message);
+        assert_eq!(json["content"][0]["type"], "text");
+        assert_eq!(json["content"][1]["type"], "tool_use");
+        assert_eq!(json["content"][1]["input"]["city"], "nyc");
+    }
+
+    #[test]
+    fn test_anthropic_message_json_maps_system_to_a_user_role() {
+        let json = anthropic_message_json(&Message::text(Role::System, "be nice"));
+        assert_eq!(json["role"], "user");
+    }
+
+    #[test]
+    fn test_build_request_body_pulls_system_messages_out_of_the_array() {
+        let provider = provider();
+        let messages = vec![Message::text(Role::System, "be nice"), Message::text(Role::User, "hi")];
+        let body = provider.build_request_body(&messages);
+        assert_eq!(body["system"], "be nice");
+        assert_eq!(body["messages"].as_array().expect("messages array").len(), 1);
+    }
+
+    #[test]
+    fn test_apply_response_format_adds_an_assistant_prefill_for_json() {
+        let mut config = ProviderConfig::new("test-key", "claude-3-5-sonnet-latest");
+        config.response_format = km_core::provider::ResponseFormat::Json;
+        let provider = AnthropicProvider::new(config);
+        let mut body = serde_json::json!({ "messages": [] });
+        provider.apply_response_format(&mut body);
+        assert!(body["system"].as_str().expect("system string").contains("JSON only"));
+        assert_eq!(body["messages"][0]["content"], "{");
+    }
+
+    #[test]
+    fn test_parse_response_extracts_text_and_tool_calls() {
+        let mut provider = provider();
+        let body = serde_json::json!({
+            "content": [
+                { "type": "text", "text": "checking the weather" },
+                { "type": "tool_use", "id": "call_1", "name": "get_weather", "input": { "city": "nyc" } },
+            ],
+            "usage": { "input_tokens": 10, "output_tokens": 5, "cache_read_input_tokens": 2 },
+        });
+        let message = provider.parse_response(&body).expect("should parse");
+        assert_eq!(message.text_content(), "checking the weather");
+        assert_eq!(message.tool_calls.len(), 1);
+        assert_eq!(provider.state().total_input_tokens, 10);
+        assert_eq!(provider.state().total_cached_input_tokens, 2);
+    }
+
+    #[test]
+    fn test_parse_response_errors_when_content_is_missing() {
+        let mut provider = provider();
+        let result = provider.parse_response(&serde_json::json!({}));
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_classify_error_maps_rate_limit_and_auth_and_overloaded() {
+        let rate_limited = serde_json::json!({ "error": { "type": "rate_limit_error", "message": "slow down" } });
+        assert!(matches!(
+            AnthropicProvider::classify_error(reqwest::StatusCode::TOO_MANY_REQUESTS, &rate_limited, None),
+            ProviderError::RateLimitExceeded { .. }
+        ));
+
+        let auth_failed = serde_json::json!({ "error": { "type": "authentication_error", "message": "bad key" } });
+        assert!(matches!(
+            AnthropicProvider::classify_error(reqwest::StatusCode::UNAUTHORIZED, &auth_failed, None),
+            ProviderError::AuthenticationFailed { .. }
+        ));
+
+        let overloaded = serde_json::json!({ "error": { "type": "overloaded_error", "message": "overloaded" } });
+        assert!(matches!(
+            AnthropicProvider::classify_error(reqwest::StatusCode::SERVICE_UNAVAILABLE, &overloaded, None),
+            ProviderError::Overloaded { .. }
+        ));
+    }
+
+    #[test]
+    fn test_classify_error_maps_context_length_exceeded_from_invalid_request() {
+        let body = serde_json::json!({ "error": { "type": "invalid_request_error", "message": "prompt is too long" } });
+        let error = AnthropicProvider::classify_error(reqwest::StatusCode::BAD_REQUEST, &body, None);
+        assert!(matches!(error, ProviderError::ContextLengthExceeded { .. }));
+    }
+}
@@ -0,0 +1,483 @@
+//! AWS Bedrock backend, covering the Claude and Llama model families so
+//! enterprise users can keep traffic inside their own AWS account/VPC
+//! instead of calling out to `api.anthropic.com`.
+//!
+//! Bedrock has no API key of its own — every request is SigV4-signed
+//! with standard AWS credentials — and each model family has a
+//! different request/response shape, so this provider picks a wire
+//! format per [`BedrockModelFamily`] rather than exposing one.
+
+use async_trait::async_trait;
+use aws_credential_types::Credentials;
+use aws_sigv4::http_request::{sign, SignableBody, SignableRequest, SigningSettings};
+use aws_sigv4::sign::v4;
+
+use crate::helpers::prune_history;
+use km_core::provider::{ContentPart, ErrorDetails, LLMProvider, Message, ProviderConfig, ProviderError, ProviderState, Role, ToolCall, UsageDelta};
+
+const MAX_HISTORY_MESSAGES: usize = 200;
+
+/// Bedrock's AWS service name, used in the SigV4 credential scope.
+const SERVICE_NAME: &str = "bedrock";
+
+/// Which wire format to speak, inferred from the model ID's prefix
+/// (`anthropic.claude-...` vs `meta.llama...`) since Bedrock doesn't
+/// unify these behind one schema the way it does for its newer Converse
+/// API — this provider talks to `invoke-model` directly so it also works
+/// on regions/accounts without Converse enabled yet.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BedrockModelFamily {
+    Claude,
+    Llama,
+}
+
+impl BedrockModelFamily {
+    /// Infers the family from a Bedrock model ID, e.g.
+    /// `anthropic.claude-3-5-sonnet-20241022-v2:0` or
+    /// `meta.llama3-1-70b-instruct-v1:0`.
+    fn from_model_id(model_id: &str) -> Option<Self> {
+        if model_id.starts_with("anthropic.") {
+            Some(Self::Claude)
+        } else if model_id.starts_with("meta.llama") {
+            Some(Self::Llama)
+        } else {
+            None
+        }
+    }
+}
+
+pub struct BedrockProvider {
+    config: ProviderConfig,
+    client: reqwest::Client,
+    /// AWS region the Bedrock runtime endpoint lives in, e.g. `us-east-1`.
+    region: String,
+    credentials: Credentials,
+    family: BedrockModelFamily,
+    history: Vec<Message>,
+    state: ProviderState,
+}
+
+impl BedrockProvider {
+    /// Builds a provider for `region`, reading credentials from the
+    /// standard `AWS_ACCESS_KEY_ID`/`AWS_SECRET_ACCESS_KEY`/
+    /// `AWS_SESSION_TOKEN` environment variables (the same ones the AWS
+    /// CLI and SDKs use), and inferring the wire format from
+    /// `config.model`.
+    ///
+    /// Returns `None` if `config.model` isn't a recognized Claude or
+    /// Llama Bedrock model ID.
+    pub fn new(config: ProviderConfig, region: impl Into<String>) -> Option<Self> {
+        let family = BedrockModelFamily::from_model_id(&config.model)?;
+        let credentials = Credentials::new(
+            std::env::var("AWS_ACCESS_KEY_ID").ok()?,
+            std::env::var("AWS_SECRET_ACCESS_KEY").ok()?,
+            std::env::var("AWS_SESSION_TOKEN").ok(),
+            None,
+            "km-bedrock",
+        );
+        Some(Self {
+            config,
+            client: crate::http::shared_client(),
+            region: region.into(),
+            credentials,
+            family,
+            history: Vec::new(),
+            state: ProviderState::default(),
+        })
+    }
+
+    /// Lists foundation models available in `self.region`, via Bedrock's
+    /// control-plane `ListFoundationModels` operation — a different host
+    /// (`bedrock.*`, not `bedrock-runtime.*`) than the one `chat` talks
+    /// to, since AWS splits discovery and inference into separate
+    /// services.
+    pub async fn list_models(&self) -> Result<Vec<crate::catalog::ModelInfo>, ProviderError> {
+        let endpoint = format!("https://bedrock.{}.amazonaws.com/foundation-models", self.region);
+        let headers = self.sign_request("GET", &endpoint, b"")?;
+        let response = self.client.get(&endpoint).headers(headers).send().await.map_err(|err| ProviderError::ApiError {
+            message: format!("Bedrock ListFoundationModels request failed: {err}"),
+            details: Default::default(),
+        })?;
+
+        let status = response.status();
+        let error_type = response.headers().get("x-amzn-errortype").and_then(|v| v.to_str().ok()).map(str::to_string);
+        let text = response.text().await.map_err(|err| ProviderError::ApiError {
+            message: format!("failed to read Bedrock response body: {err}"),
+            details: Default::default(),
+        })?;
+        let json: serde_json::Value = serde_json::from_str(&text).map_err(|err| ProviderError::ApiError {
+            message: format!("failed to parse Bedrock response as JSON: {err}"),
+            details: ErrorDetails { http_status: Some(status.as_u16()), raw_body: Some(text.clone()), ..Default::default() },
+        })?;
+        if !status.is_success() {
+            return Err(Self::classify_error(status, error_type, &json, None));
+        }
+
+        let summaries = json.get("modelSummaries").and_then(|v| v.as_array()).cloned().unwrap_or_default();
+        Ok(summaries
+            .iter()
+            .filter_map(|summary| summary.get("modelId").and_then(|v| v.as_str()))
+            .map(crate::catalog::catalog_entry)
+            .collect())
+    }
+
+    fn endpoint(&self) -> String {
+        format!(
+            "https://bedrock-runtime.{}.amazonaws.com/model/{}/invoke",
+            self.region, self.config.model
+        )
+    }
+
+    /// Builds the family-specific request body for the `invoke-model`
+    /// endpoint. Claude uses Anthropic's own Messages format (Bedrock
+    /// passes it through almost verbatim, reusing [`crate::anthropic`]'s
+    /// wire-format mapping); Llama uses Meta's flat `prompt`/`max_gen_len`
+    /// shape.
+    fn build_request_body(&self, messages: &[Message]) -> serde_json::Value {
+        match self.family {
+            BedrockModelFamily::Claude => {
+                let system_text = messages.iter().filter(|m| m.role == Role::System).map(|m| m.text_content()).collect::<Vec<_>>().join("\n\n");
+                let wire_messages: Vec<_> =
+                    messages.iter().filter(|m| m.role != Role::System).map(crate::anthropic::anthropic_message_json).collect();
+                let mut body = serde_json::json!({
+                    "anthropic_version": "bedrock-2023-05-31",
+                    "max_tokens": self.config.max_tokens,
+                    "messages": wire_messages,
+                });
+                if !system_text.is_empty() {
+                    body["system"] = serde_json::json!(system_text);
+                }
+                body
+            }
+            BedrockModelFamily::Llama => serde_json::json!({
+                "prompt": messages.iter().map(|m| m.text_content()).collect::<Vec<_>>().join("\n"),
+                "max_gen_len": self.config.max_tokens,
+                "temperature": self.config.temperature,
+            }),
+        }
+    }
+
+    /// Parses an `invoke-model` response body into a canonical [`Message`],
+    /// recording usage against `self.state` when the family's response
+    /// carries it (Claude does; Llama's response has no usage field).
+    fn parse_response(&mut self, body: &serde_json::Value) -> Result<Message, ProviderError> {
+        match self.family {
+            BedrockModelFamily::Claude => {
+                if let Some(usage) = body.get("usage") {
+                    self.state.apply_usage(UsageDelta {
+                        input_tokens: usage.get("input_tokens").and_then(|v| v.as_u64()).unwrap_or(0),
+                        output_tokens: usage.get("output_tokens").and_then(|v| v.as_u64()).unwrap_or(0),
+                        cached_input_tokens: 0,
+                    });
+                }
+                let blocks = body.get("content").and_then(|v| v.as_array()).cloned().unwrap_or_default();
+                let mut text = String::new();
+                let mut tool_calls = Vec::new();
+                for block in &blocks {
+                    match block.get("type").and_then(|v| v.as_str()) {
+                        Some("text") => text.push_str(block.get("text").and_then(|v| v.as_str()).unwrap_or_default()),
+                        Some("tool_use") => tool_calls.push(ToolCall {
+                            id: block.get("id").and_then(|v| v.as_str()).unwrap_or_default().to_string(),
+                            name: block.get("name").and_then(|v| v.as_str()).unwrap_or_default().to_string(),
+                            arguments: block.get("input").map(|v| v.to_string()).unwrap_or_else(|| "{}".to_string()),
+                        }),
+                        _ => {}
+                    }
+                }
+                Ok(Message { role: Role::Assistant, content: vec![ContentPart::Text(text)], tool_calls, ..Default::default() })
+            }
+            BedrockModelFamily::Llama => {
+                let text = body.get("generation").and_then(|v| v.as_str()).unwrap_or_default().to_string();
+                Ok(Message { role: Role::Assistant, content: vec![ContentPart::Text(text)], ..Default::default() })
+            }
+        }
+    }
+
+    /// Maps a Bedrock error to a [`ProviderError`] variant using the
+    /// `x-amzn-ErrorType` header AWS attaches to every error response
+    /// (the body itself is just `{"message": "..."}`, with no error code),
+    /// falling back to [`crate::http::classify_http_error`] for anything
+    /// the header alone doesn't distinguish.
+    fn classify_error(
+        status: reqwest::StatusCode,
+        error_type: Option<String>,
+        body: &serde_json::Value,
+        retry_after: Option<std::time::Duration>,
+    ) -> ProviderError {
+        let message = body.get("message").and_then(|v| v.as_str()).unwrap_or("unknown Bedrock error").to_string();
+        let details = ErrorDetails {
+            http_status: Some(status.as_u16()),
+            provider_error_type: error_type.clone(),
+            provider_error_code: None,
+            request_id: None,
+            raw_body: Some(body.to_string()),
+        };
+        match error_type.as_deref() {
+            Some("ThrottlingException") => ProviderError::RateLimitExceeded { retry_after, details },
+            Some("AccessDeniedException") | Some("UnrecognizedClientException") => ProviderError::AuthenticationFailed { details },
+            Some("ServiceUnavailableException") | Some("ModelTimeoutException") | Some("ModelNotReadyException") => {
+                ProviderError::Overloaded { details }
+            }
+            Some("ModelErrorException") | Some("ValidationException") => ProviderError::ApiError { message, details },
+            _ => crate::http::classify_http_error(status, retry_after, details),
+        }
+    }
+
+    /// Signs `body` for the given `method`/`url` with SigV4, returning the
+    /// headers to attach to the request. Bedrock rejects unsigned or
+    /// incorrectly-scoped requests outright, so this runs before every
+    /// call rather than being cached — signatures are tied to a timestamp
+    /// and are only valid for a short window. The URL being signed must
+    /// match the one actually requested, since Bedrock's control plane
+    /// (`bedrock.*`, used by `list_models`) and runtime
+    /// (`bedrock-runtime.*`, used by `chat`) are different hosts.
+    fn sign_request(&self, method: &str, url: &str, body: &[u8]) -> Result<reqwest::header::HeaderMap, ProviderError> {
+        let identity = self.credentials.clone().into();
+        let signing_settings = SigningSettings::default();
+        let signing_params = v4::SigningParams::builder()
+            .identity(&identity)
+            .region(&self.region)
+            .name(SERVICE_NAME)
+            .time(std::time::SystemTime::now())
+            .settings(signing_settings)
+            .build()
+            .map_err(|err| ProviderError::ApiError {
+                message: format!("failed to build SigV4 signing params: {err}"),
+                details: Default::default(),
+            })?
+            .into();
+
+        let signable_request = SignableRequest::new(method, url, std::iter::empty(), SignableBody::Bytes(body)).map_err(|err| {
+            ProviderError::ApiError {
+                message: format!("failed to build signable request: {err}"),
+                details: Default::default(),
+            }
+        })?;
+
+        let (instructions, _signature) = sign(signable_request, &signing_params)
+            .map_err(|err| ProviderError::ApiError {
+                message: format!("SigV4 signing failed: {err}"),
+                details: Default::default(),
+            })?
+            .into_parts();
+
+        let mut headers = reqwest::header::HeaderMap::new();
+        for (name, value) in instructions.headers() {
+            if let (Ok(name), Ok(value)) = (name.parse::<reqwest::header::HeaderName>(), value.parse()) {
+                headers.insert(name, value);
+            }
+        }
+        Ok(headers)
+    }
+}
+
+#[async_trait]
+impl LLMProvider for BedrockProvider {
+    #[tracing::instrument(skip(self, messages), fields(provider = "bedrock", model = %self.config.model, message_count = messages.len()))]
+    async fn chat(&mut self, messages: &[Message]) -> Result<Message, ProviderError> {
+        self.state.request_count += 1;
+        let body = self.build_request_body(messages);
+        let body_bytes = serde_json::to_vec(&body).unwrap_or_default();
+        let endpoint = self.endpoint();
+        let headers = self.sign_request("POST", &endpoint, &body_bytes)?;
+        crate::time::with_request_timeout(self.config.request_timeout, async {
+            let started = std::time::Instant::now();
+            let response = self
+                .client
+                .post(&endpoint)
+                .headers(headers)
+                .body(body_bytes)
+                .send()
+                .await
+                .map_err(|err| ProviderError::ApiError {
+                    message: format!("Bedrock request failed: {err}"),
+                    details: Default::default(),
+                })?;
+
+            let status = response.status();
+            let retry_after = crate::http::retry_after(response.headers());
+            let error_type = response.headers().get("x-amzn-errortype").and_then(|v| v.to_str().ok()).map(str::to_string);
+            let text = response.text().await.map_err(|err| ProviderError::ApiError {
+                message: format!("failed to read Bedrock response body: {err}"),
+                details: Default::default(),
+            })?;
+            let json: serde_json::Value = serde_json::from_str(&text).map_err(|err| ProviderError::ApiError {
+                message: format!("failed to parse Bedrock response as JSON: {err}"),
+                details: ErrorDetails { http_status: Some(status.as_u16()), raw_body: Some(text.clone()), ..Default::default() },
+            })?;
+
+            if !status.is_success() {
+                return Err(Self::classify_error(status, error_type, &json, retry_after));
+            }
+
+            let message = self.parse_response(&json)?;
+            self.state.record_latency(km_core::provider::RequestLatency {
+                time_to_first_token: started.elapsed(),
+                total_duration: started.elapsed(),
+            });
+            Ok(message)
+        })
+        .await
+    }
+
+    #[tracing::instrument(skip(self, user_message), fields(provider = "bedrock", model = %self.config.model, history_len))]
+    async fn chat_loop(&mut self, user_message: Message) -> Result<Message, ProviderError> {
+        self.history.push(user_message);
+        self.history = prune_history(&self.history, MAX_HISTORY_MESSAGES);
+        tracing::Span::current().record("history_len", self.history.len());
+
+        crate::tokens::check_context_window(&self.config.model, &self.history, self.config.max_tokens)?;
+        km_core::provider::log_request_summary(&self.config, "bedrock", self.history.len(), 0);
+        let history = self.history.clone();
+        crate::retry::retry_with_backoff(crate::retry::RetryPolicy::default(), self, crate::retry::hrtb_attempt(move |s: &mut BedrockProvider| {
+            let history = history.clone();
+            Box::pin(async move { s.chat(&history).await })
+        })).await
+    }
+
+    fn state(&self) -> &ProviderState {
+        &self.state
+    }
+
+    fn config(&self) -> &ProviderConfig {
+        &self.config
+    }
+
+    fn get_history(&self) -> &[Message] {
+        &self.history
+    }
+
+    fn set_history(&mut self, history: Vec<Message>) {
+        self.history = history;
+    }
+
+    /// Bedrock has no dedicated token-counting endpoint, so this falls
+    /// back to the same character-based heuristic OpenAI uses.
+    async fn count_tokens(&self, messages: &[Message]) -> Result<u32, ProviderError> {
+        Ok(messages.iter().map(crate::tokens::estimate_message_tokens).sum())
+    }
+
+    fn update_config(&mut self, f: Box<dyn FnOnce(&mut ProviderConfig) + Send>) {
+        f(&mut self.config);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Builds a provider directly, bypassing `new`'s environment-variable
+    /// credential lookup so tests don't depend on the sandbox's env.
+    fn provider(family: BedrockModelFamily) -> BedrockProvider {
+        let model = match family {
+            BedrockModelFamily::Claude => "anthropic.claude-3-5-sonnet-20241022-v2:0",
+            BedrockModelFamily::Llama => "meta.llama3-1-70b-instruct-v1:0",
+        };
+        BedrockProvider {
+            config: ProviderConfig::new("unused", model),
+            client: crate::http::shared_client(),
+            region: "us-east-1".to_string(),
+            credentials: Credentials::new("test-access-key", "test-secret-key", None, None, "km-bedrock-test"),
+            family,
+            history: Vec::new(),
+            state: ProviderState::default(),
+        }
+    }
+
+    #[test]
+    fn test_from_model_id_infers_family_from_prefix() {
+        assert_eq!(BedrockModelFamily::from_model_id("anthropic.claude-3-5-sonnet-20241022-v2:0"), Some(BedrockModelFamily::Claude));
+        assert_eq!(BedrockModelFamily::from_model_id("meta.llama3-1-70b-instruct-v1:0"), Some(BedrockModelFamily::Llama));
+        assert_eq!(BedrockModelFamily::from_model_id("amazon.titan-text-express-v1"), None);
+    }
+
+    #[test]
+    fn test_build_request_body_claude_splits_system_and_reuses_anthropic_message_json() {
+        let provider = provider(BedrockModelFamily::Claude);
+        let messages = vec![Message::text(Role::System, "be terse"), Message::text(Role::User, "hi")];
+        let body = provider.build_request_body(&messages);
+
+        assert_eq!(body["anthropic_version"], "bedrock-2023-05-31");
+        assert_eq!(body["system"], "be terse");
+        let wire_messages = body["messages"].as_array().expect("messages array");
+        assert_eq!(wire_messages.len(), 1);
+        assert_eq!(wire_messages[0]["role"], "user");
+    }
+
+    #[test]
+    fn test_build_request_body_claude_omits_system_when_absent() {
+        let provider = provider(BedrockModelFamily::Claude);
+        let messages = vec![Message::text(Role::User, "hi")];
+        let body = provider.build_request_body(&messages);
+        assert!(body.get("system").is_none());
+    }
+
+    #[test]
+    fn test_build_request_body_llama_joins_messages_into_a_flat_prompt() {
+        let provider = provider(BedrockModelFamily::Llama);
+        let messages = vec![Message::text(Role::User, "line one"), Message::text(Role::Assistant, "line two")];
+        let body = provider.build_request_body(&messages);
+
+        assert_eq!(body["prompt"], "line one\nline two");
+        assert!(body.get("max_gen_len").is_some());
+        assert!(body.get("messages").is_none());
+    }
+
+    #[test]
+    fn test_parse_response_claude_extracts_text_tool_calls_and_usage() {
+        let mut provider = provider(BedrockModelFamily::Claude);
+        let body = serde_json::json!({
+            "content": [
+                { "type": "text", "text": "the answer is " },
+                { "type": "tool_use", "id": "call_1", "name": "lookup", "input": { "q": "42" } },
+            ],
+            "usage": { "input_tokens": 10, "output_tokens": 5 },
+        });
+
+        let message = provider.parse_response(&body).expect("parse_response");
+        assert_eq!(message.text_content(), "the answer is ");
+        assert_eq!(message.tool_calls.len(), 1);
+        assert_eq!(message.tool_calls[0].name, "lookup");
+        assert_eq!(provider.state.total_input_tokens, 10);
+        assert_eq!(provider.state.total_output_tokens, 5);
+    }
+
+    #[test]
+    fn test_parse_response_llama_extracts_generation_text() {
+        let mut provider = provider(BedrockModelFamily::Llama);
+        let body = serde_json::json!({ "generation": "hello there" });
+
+        let message = provider.parse_response(&body).expect("parse_response");
+        assert_eq!(message.text_content(), "hello there");
+        assert_eq!(provider.state.total_input_tokens, 0);
+    }
+
+    #[test]
+    fn test_classify_error_maps_throttling_and_access_denied() {
+        let body = serde_json::json!({ "message": "rate exceeded" });
+        let err = BedrockProvider::classify_error(reqwest::StatusCode::TOO_MANY_REQUESTS, Some("ThrottlingException".to_string()), &body, None);
+        assert!(matches!(err, ProviderError::RateLimitExceeded { .. }));
+
+        let err = BedrockProvider::classify_error(reqwest::StatusCode::FORBIDDEN, Some("AccessDeniedException".to_string()), &body, None);
+        assert!(matches!(err, ProviderError::AuthenticationFailed { .. }));
+    }
+
+    #[test]
+    fn test_classify_error_maps_overloaded_variants() {
+        let body = serde_json::json!({ "message": "model not ready" });
+        let err = BedrockProvider::classify_error(
+            reqwest::StatusCode::SERVICE_UNAVAILABLE,
+            Some("ModelNotReadyException".to_string()),
+            &body,
+            None,
+        );
+        assert!(matches!(err, ProviderError::Overloaded { .. }));
+    }
+
+    #[test]
+    fn test_classify_error_falls_back_to_http_status_for_unrecognized_error_type() {
+        let body = serde_json::json!({ "message": "boom" });
+        let err = BedrockProvider::classify_error(reqwest::StatusCode::BAD_REQUEST, None, &body, None);
+        assert!(matches!(err, ProviderError::ApiError { .. }));
+    }
+}
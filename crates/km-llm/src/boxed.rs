@@ -0,0 +1,54 @@
+//! A dyn-friendly wrapper over any concrete [`LLMProvider`], for apps that
+//! need to hold "whichever provider the user picked" behind one type
+//! instead of hand-rolling an `ActiveProvider { OpenAi(..), Gemini(..), ... }`
+//! enum per app.
+
+use km_core::provider::{LLMProvider, Message, ProviderConfig, ProviderError, ProviderState};
+
+/// Owns a boxed `dyn LLMProvider` and forwards the subset of the trait
+/// that heterogeneous callers (the CLI, examples) actually need.
+pub struct BoxProvider(Box<dyn LLMProvider + Send>);
+
+/// Alias for [`BoxProvider`] under the name most callers reach for first
+/// when they want "any provider, type-erased" — `update_config` taking a
+/// boxed closure rather than `impl FnOnce` is what makes this possible at
+/// all, since a generic method can't be called through `dyn LLMProvider`.
+pub type AnyProvider = BoxProvider;
+
+impl BoxProvider {
+    pub fn new(provider: impl LLMProvider + Send + 'static) -> Self {
+        Self(Box::new(provider))
+    }
+
+    pub async fn chat(&mut self, messages: &[Message]) -> Result<Message, ProviderError> {
+        self.0.chat(messages).await
+    }
+
+    pub async fn chat_loop(&mut self, user_message: Message) -> Result<Message, ProviderError> {
+        self.0.chat_loop(user_message).await
+    }
+
+    pub fn state(&self) -> &ProviderState {
+        self.0.state()
+    }
+
+    pub fn config(&self) -> &ProviderConfig {
+        self.0.config()
+    }
+
+    pub fn get_history(&self) -> &[Message] {
+        self.0.get_history()
+    }
+
+    pub fn set_history(&mut self, history: Vec<Message>) {
+        self.0.set_history(history);
+    }
+
+    pub async fn count_tokens(&self, messages: &[Message]) -> Result<u32, ProviderError> {
+        self.0.count_tokens(messages).await
+    }
+
+    pub fn update_config(&mut self, f: impl FnOnce(&mut ProviderConfig) + Send + 'static) {
+        self.0.update_config(Box::new(f));
+    }
+}
@@ -0,0 +1,86 @@
+//! Central table of model capabilities and pricing, and live model
+//! discovery against each provider's `/models` endpoint.
+//!
+//! `models::preset_for` answers "how do I build a request for this
+//! model" (temperature quirks, context window); [`catalog_entry`]
+//! answers "which models exist and what do they cost" — a question this
+//! crate used to leave to scattered `is_supported_model` allow-lists
+//! that needed a crate release for every new model launch. Providers'
+//! `list_models()` methods complement the static table with whatever
+//! the provider itself currently serves.
+
+use crate::models::{preset_for, ModelPreset};
+
+/// USD cost per 1M tokens. `None` means the catalog doesn't know the
+/// price (e.g. a model served through a third-party gateway) rather
+/// than that it's free.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ModelPricing {
+    pub input_cost_per_million: Option<f64>,
+    pub output_cost_per_million: Option<f64>,
+}
+
+/// One catalog entry: a model ID plus everything known about it,
+/// whether from the static table or a provider's live listing.
+#[derive(Debug, Clone)]
+pub struct ModelInfo {
+    pub id: String,
+    pub preset: ModelPreset,
+    pub pricing: ModelPricing,
+}
+
+/// Looks up known pricing for `model`. Falls back to
+/// `ModelPricing::default()` (unknown cost) for models not yet priced
+/// here, mirroring [`preset_for`]'s fallback for capabilities.
+pub fn pricing_for(model: &str) -> ModelPricing {
+    match model {
+        "gpt-4o" => ModelPricing {
+            input_cost_per_million: Some(2.5),
+            output_cost_per_million: Some(10.0),
+        },
+        "gpt-4o-mini" => ModelPricing {
+            input_cost_per_million: Some(0.15),
+            output_cost_per_million: Some(0.6),
+        },
+        "o1" => ModelPricing {
+            input_cost_per_million: Some(15.0),
+            output_cost_per_million: Some(60.0),
+        },
+        "o1-mini" | "o3-mini" => ModelPricing {
+            input_cost_per_million: Some(1.1),
+            output_cost_per_million: Some(4.4),
+        },
+        "o3" => ModelPricing {
+            input_cost_per_million: Some(10.0),
+            output_cost_per_million: Some(40.0),
+        },
+        "claude-opus-4" => ModelPricing {
+            input_cost_per_million: Some(15.0),
+            output_cost_per_million: Some(75.0),
+        },
+        "claude-sonnet-4" => ModelPricing {
+            input_cost_per_million: Some(3.0),
+            output_cost_per_million: Some(15.0),
+        },
+        "gemini-1.5-pro" => ModelPricing {
+            input_cost_per_million: Some(1.25),
+            output_cost_per_million: Some(5.0),
+        },
+        "gemini-1.5-flash" => ModelPricing {
+            input_cost_per_million: Some(0.075),
+            output_cost_per_million: Some(0.3),
+        },
+        _ => ModelPricing::default(),
+    }
+}
+
+/// Combines a model's static capabilities ([`preset_for`]) and pricing
+/// ([`pricing_for`]) into one lookup, so callers checking "can this
+/// model do X" and "what will this cost" don't consult two tables.
+pub fn catalog_entry(model: &str) -> ModelInfo {
+    ModelInfo {
+        id: model.to_string(),
+        preset: preset_for(model),
+        pricing: pricing_for(model),
+    }
+}
@@ -0,0 +1,108 @@
+//! Events emitted while a chat loop runs, for hosts that want to stream
+//! progress (tokens, tool calls) instead of waiting for the final message.
+
+/// One event from a running chat loop.
+#[derive(Debug, Clone)]
+pub enum LoopEvent {
+    TextDelta(String),
+    /// A chunk of a tool call's name/arguments as the model streams them
+    /// in, keyed by the call's position in this turn (see
+    /// `helpers::ToolCallAssembler`, which accumulates these into
+    /// complete `ToolCall`s). Providers that don't yet parse their SSE
+    /// stream incrementally emit one delta per call carrying the whole
+    /// thing, rather than not emitting this event at all.
+    ToolCallDelta {
+        index: u32,
+        id: Option<String>,
+        name_delta: Option<String>,
+        arguments_delta: Option<String>,
+    },
+    ToolCallStarted { id: String, name: String },
+    ToolCallFinished { id: String },
+    Done,
+}
+
+/// How a loop's event channel is sized. Bounded is recommended for
+/// anything that streams to a slow consumer (a TUI render loop, a
+/// websocket); unbounded stays available for callers that know their
+/// consumer always keeps up.
+#[derive(Debug, Clone, Copy)]
+pub enum ChannelMode {
+    /// No limit on buffered events. A slow consumer on a verbose stream
+    /// can grow memory without bound — prefer `Bounded` unless you have a
+    /// specific reason not to.
+    Unbounded,
+    /// Bounded to `capacity` events. Once full, `LoopEventSender::send`
+    /// awaits until the consumer drains a slot, applying backpressure to
+    /// the loop itself rather than buffering indefinitely.
+    Bounded { capacity: usize },
+}
+
+pub enum LoopEventSender {
+    Unbounded(tokio::sync::mpsc::UnboundedSender<LoopEvent>),
+    Bounded(tokio::sync::mpsc::Sender<LoopEvent>),
+}
+
+pub enum LoopEventReceiver {
+    Unbounded(tokio::sync::mpsc::UnboundedReceiver<LoopEvent>),
+    Bounded(tokio::sync::mpsc::Receiver<LoopEvent>),
+}
+
+impl LoopEventSender {
+    /// Sends `event`, awaiting for backpressure in `Bounded` mode. Returns
+    /// `Err` only if the receiver has been dropped.
+    pub async fn send(&self, event: LoopEvent) -> Result<(), LoopEvent> {
+        match self {
+            LoopEventSender::Unbounded(tx) => tx.send(event).map_err(|e| e.0),
+            LoopEventSender::Bounded(tx) => tx.send(event).await.map_err(|e| e.0),
+        }
+    }
+}
+
+impl LoopEventReceiver {
+    pub async fn recv(&mut self) -> Option<LoopEvent> {
+        match self {
+            LoopEventReceiver::Unbounded(rx) => rx.recv().await,
+            LoopEventReceiver::Bounded(rx) => rx.recv().await,
+        }
+    }
+}
+
+/// A finer-grained alternative to [`LoopEvent`] for
+/// [`crate::agent_loop::chat_loop_with_tools_stream`]: splits a tool call's
+/// arguments from its eventual result instead of leaving the caller to
+/// correlate `ToolCallStarted`/`ToolCallFinished` by id, and marks the end
+/// of each resolved round explicitly so a UI can render turn-by-turn
+/// without re-deriving boundaries from deltas.
+#[derive(Debug, Clone)]
+pub enum AgentEvent {
+    /// Ordinary assistant text.
+    Content(String),
+    /// Reasoning/"thinking" content, kept separate from `Content` so a
+    /// host can render it differently (or not at all). No provider in
+    /// this crate populates `ContentPart` with a reasoning variant yet,
+    /// so nothing emits this today — it's here so the event shape
+    /// doesn't need to change again once one does.
+    Thinking(String),
+    ToolCall { id: String, name: String, arguments: String },
+    ToolResult { id: String, is_error: bool, content: String },
+    /// One tool call has been resolved and its result fed back to the
+    /// model; the next event is either another `ToolCall` or `Content`
+    /// from the following turn.
+    RoundBoundary,
+    Done,
+}
+
+/// Builds a paired sender/receiver for the given [`ChannelMode`].
+pub fn channel(mode: ChannelMode) -> (LoopEventSender, LoopEventReceiver) {
+    match mode {
+        ChannelMode::Unbounded => {
+            let (tx, rx) = tokio::sync::mpsc::unbounded_channel();
+            (LoopEventSender::Unbounded(tx), LoopEventReceiver::Unbounded(rx))
+        }
+        ChannelMode::Bounded { capacity } => {
+            let (tx, rx) = tokio::sync::mpsc::channel(capacity);
+            (LoopEventSender::Bounded(tx), LoopEventReceiver::Bounded(rx))
+        }
+    }
+}
@@ -0,0 +1,88 @@
+//! Constructing a [`ProviderConfig`] from named profiles declared in
+//! `km.toml`, e.g.:
+//!
+//! ```toml
+//! [profile.fast]
+//! model = "gemini-1.5-flash"
+//! max_tokens = 1024
+//!
+//! [profile.deep]
+//! model = "claude-opus-4"
+//! extra_options = { thinking_level = "high" }
+//! ```
+//!
+//! selectable with `--profile fast` on the CLI or
+//! [`ProviderFactory::from_profile`] programmatically.
+
+use std::collections::HashMap;
+
+use km_core::config::PartialProviderConfig;
+use km_core::provider::ProviderConfig;
+
+use crate::boxed::BoxProvider;
+
+#[derive(Debug, thiserror::Error)]
+pub enum FactoryError {
+    #[error("unknown profile: {0}")]
+    UnknownProfile(String),
+
+    /// `create_provider`'s spec wasn't `<provider>:<model>`, or named a
+    /// provider this crate doesn't implement.
+    #[error("unrecognized provider spec: {0} (expected \"<provider>:<model>\", e.g. \"anthropic:claude-sonnet-4-5\")")]
+    UnknownProviderSpec(String),
+
+    /// No API key was passed in and none could be resolved from the
+    /// keyring or environment either (see
+    /// [`km_core::secrets::resolve_api_key`]).
+    #[error("no API key available for provider {0}")]
+    MissingApiKey(String),
+}
+
+/// Builds a ready-to-use, type-erased provider from a `"<provider>:<model>"`
+/// spec, e.g. `"anthropic:claude-sonnet-4-5"` or `"openai:gpt-4o-mini"`, so
+/// callers can let users pick a provider at runtime (a CLI flag, a config
+/// value) without matching on provider names themselves.
+///
+/// `api_key` is used if given; otherwise the key is resolved the same way
+/// every provider resolves it standalone — keyring, then the provider's
+/// env var (see [`km_core::secrets::resolve_api_key`]).
+pub fn create_provider(spec: &str, api_key: Option<&str>) -> Result<BoxProvider, FactoryError> {
+    let (provider_name, model) = spec
+        .split_once(':')
+        .ok_or_else(|| FactoryError::UnknownProviderSpec(spec.to_string()))?;
+
+    let resolved_key = km_core::secrets::resolve_api_key(provider_name, api_key)
+        .ok_or_else(|| FactoryError::MissingApiKey(provider_name.to_string()))?;
+    let config = ProviderConfig::new(resolved_key, model);
+
+    match provider_name {
+        "openai" => Ok(BoxProvider::new(crate::openai::OpenAiProvider::new(config))),
+        "anthropic" => Ok(BoxProvider::new(crate::anthropic::AnthropicProvider::new(config))),
+        "gemini" => Ok(BoxProvider::new(crate::gemini::GeminiProvider::new(config))),
+        _ => Err(FactoryError::UnknownProviderSpec(spec.to_string())),
+    }
+}
+
+/// Resolves named profiles (the `[profile.*]` tables in `km.toml`) into
+/// concrete provider configs.
+pub struct ProviderFactory {
+    profiles: HashMap<String, PartialProviderConfig>,
+}
+
+impl ProviderFactory {
+    pub fn new(profiles: HashMap<String, PartialProviderConfig>) -> Self {
+        Self { profiles }
+    }
+
+    /// Resolves `name` to a [`ProviderConfig`], applying the same
+    /// default-filling as [`km_core::config::resolve`]. Returns
+    /// [`FactoryError::UnknownProfile`] if `km.toml` has no matching
+    /// `[profile.<name>]` table.
+    pub fn from_profile(&self, name: &str) -> Result<ProviderConfig, FactoryError> {
+        self.profiles
+            .get(name)
+            .cloned()
+            .map(PartialProviderConfig::into_provider_config)
+            .ok_or_else(|| FactoryError::UnknownProfile(name.to_string()))
+    }
+}
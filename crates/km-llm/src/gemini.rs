@@ -0,0 +1,538 @@
+//! Google Gemini backend.
+
+use async_trait::async_trait;
+
+use std::collections::HashMap;
+
+use crate::helpers::{prune_history, ToolCallAssembler};
+use km_core::provider::{ErrorDetails, LLMProvider, Message, ProviderConfig, ProviderError, ProviderState, Role, UsageDelta};
+
+/// Parses one Gemini streaming response body into the accumulated text and
+/// tool calls seen so far.
+///
+/// Gemini sometimes splits a function call's `args` object, or even plain
+/// text, across multiple `data:` chunks, and — for some proxies and
+/// regional endpoints — skips SSE framing entirely and returns a bare JSON
+/// array of candidates instead. Rather than let a `serde_json::Error` from
+/// assuming one shape kill the whole loop, try SSE first and fall back to
+/// parsing the body as plain JSON.
+fn parse_stream_body(body: &str, assembler: &mut ToolCallAssembler, text: &mut String) -> Result<(), ProviderError> {
+    let mut any_sse_event = false;
+    for line in body.lines() {
+        let Some(data) = line.strip_prefix("data: ") else { continue };
+        any_sse_event = true;
+        apply_candidate_json(data, assembler, text)?;
+    }
+    if !any_sse_event {
+        // Non-SSE fallback: the whole body is one JSON document, either a
+        // single response object or an array of them.
+        let value: serde_json::Value = serde_json::from_str(body).map_err(|e| ProviderError::ApiError {
+            message: format!("Gemini parse error: {e}"),
+            details: Default::default(),
+        })?;
+        match value {
+            serde_json::Value::Array(candidates) => {
+                for candidate in candidates {
+                    apply_candidate_json(&candidate.to_string(), assembler, text)?;
+                }
+            }
+            other => apply_candidate_json(&other.to_string(), assembler, text)?,
+        }
+    }
+    Ok(())
+}
+
+/// Merges one response chunk's text and function-call deltas into the
+/// running `text`/`assembler` state. Each chunk only carries a delta, so
+/// this must be additive rather than overwriting.
+fn apply_candidate_json(json: &str, assembler: &mut ToolCallAssembler, text: &mut String) -> Result<(), ProviderError> {
+    let value: serde_json::Value = serde_json::from_str(json).map_err(|e| ProviderError::ApiError {
+        message: format!("Gemini parse error: {e}"),
+        details: Default::default(),
+    })?;
+    let parts = value
+        .pointer("/candidates/0/content/parts")
+        .and_then(|p| p.as_array())
+        .cloned()
+        .unwrap_or_default();
+    for (index, part) in parts.iter().enumerate() {
+        if let Some(chunk) = part.get("text").and_then(|t| t.as_str()) {
+            text.push_str(chunk);
+        }
+        if let Some(call) = part.get("functionCall") {
+            let name = call.get("name").and_then(|n| n.as_str());
+            let args = call.get("args").map(|a| a.to_string());
+            assembler.add_delta(index as u32, None, name, args.as_deref());
+        }
+    }
+    Ok(())
+}
+
+/// Maps one canonical [`Message`] to a Gemini `contents` entry. Gemini has
+/// no `system`/`tool` role of its own: system messages are pulled out
+/// into `systemInstruction` by the caller before this runs, and a tool
+/// result becomes a `functionResponse` part on a `user`-role turn, naming
+/// the function it answers via `call_names` (Gemini's wire format needs
+/// the name, not just the call ID the canonical `Message` carries).
+fn gemini_message_json(message: &Message, call_names: &HashMap<&str, &str>) -> serde_json::Value {
+    match message.role {
+        Role::Tool => {
+            let name = message.tool_call_id.as_deref().and_then(|id| call_names.get(id)).copied().unwrap_or("unknown");
+            serde_json::json!({
+                "role": "user",
+                "parts": [{
+                    "functionResponse": {
+                        "name": name,
+                        "response": { "result": message.text_content() },
+                    },
+                }],
+            })
+        }
+        Role::Assistant if !message.tool_calls.is_empty() => {
+            let mut parts = Vec::new();
+            let text = message.text_content();
+            if !text.is_empty() {
+                parts.push(serde_json::json!({ "text": text }));
+            }
+            for call in &message.tool_calls {
+                let args: serde_json::Value = serde_json::from_str(&call.arguments).unwrap_or(serde_json::json!({}));
+                parts.push(serde_json::json!({ "functionCall": { "name": call.name, "args": args } }));
+            }
+            serde_json::json!({ "role": "model", "parts": parts })
+        }
+        Role::Assistant => serde_json::json!({ "role": "model", "parts": [{ "text": message.text_content() }] }),
+        Role::User | Role::System => serde_json::json!({ "role": "user", "parts": [{ "text": message.text_content() }] }),
+    }
+}
+
+const MAX_HISTORY_MESSAGES: usize = 200;
+
+/// Default API base, overridable via `GEMINI_API_BASE` or
+/// [`GeminiProvider::with_base_url`].
+const DEFAULT_BASE_URL: &str = "https://generativelanguage.googleapis.com/v1beta";
+
+pub struct GeminiProvider {
+    config: ProviderConfig,
+    client: reqwest::Client,
+    base_url: String,
+    history: Vec<Message>,
+    state: ProviderState,
+    /// Gemini function calls don't carry a provider-issued ID the way
+    /// OpenAI/Anthropic tool calls do, so we synthesize one per call and
+    /// need it to stay stable across a session save/load — otherwise a
+    /// resumed session's `tool_result`s would reference IDs the model
+    /// never saw. `next_call_id` is exported/restored by session
+    /// persistence alongside the rest of provider state.
+    next_call_id: u64,
+    /// Whether requests include the `google_search` grounding tool, which
+    /// lets Gemini ground responses in live search results.
+    search_grounding: bool,
+}
+
+/// The part of [`GeminiProvider`] that session persistence needs to save
+/// and restore to keep synthesized call IDs stable across a resumed
+/// session.
+#[derive(Debug, Clone, Copy, serde::Serialize, serde::Deserialize)]
+pub struct GeminiCallIdState {
+    pub next_call_id: u64,
+}
+
+impl GeminiProvider {
+    pub fn new(config: ProviderConfig) -> Self {
+        let base_url = std::env::var("GEMINI_API_BASE").unwrap_or_else(|_| DEFAULT_BASE_URL.to_string());
+        Self {
+            config,
+            client: crate::http::shared_client(),
+            base_url,
+            history: Vec::new(),
+            state: ProviderState::default(),
+            next_call_id: 0,
+            search_grounding: false,
+        }
+    }
+
+    /// Enables Google Search grounding: Gemini can issue search queries
+    /// and cite results in its response. Adds the `google_search` tool to
+    /// every request's `tools` array.
+    pub fn with_search_grounding(mut self, enabled: bool) -> Self {
+        self.search_grounding = enabled;
+        self
+    }
+
+    /// The `tools` array entries this provider always sends, independent
+    /// of whatever tools the host registered — currently just search
+    /// grounding when enabled.
+    fn builtin_tools(&self) -> Vec<serde_json::Value> {
+        if self.search_grounding {
+            vec![serde_json::json!({ "google_search": {} })]
+        } else {
+            Vec::new()
+        }
+    }
+
+    /// Synthesizes the next stable tool-call ID, e.g. `"gemini-call-3"`.
+    fn next_tool_call_id(&mut self) -> String {
+        let id = self.next_call_id;
+        self.next_call_id += 1;
+        format!("gemini-call-{id}")
+    }
+
+    /// Exports the call-ID counter for session persistence to save.
+    pub fn call_id_state(&self) -> GeminiCallIdState {
+        GeminiCallIdState {
+            next_call_id: self.next_call_id,
+        }
+    }
+
+    /// Restores the call-ID counter from a previously saved session, so
+    /// newly synthesized IDs never collide with ones the resumed history
+    /// already references.
+    pub fn restore_call_id_state(&mut self, state: GeminiCallIdState) {
+        self.next_call_id = state.next_call_id;
+    }
+
+    pub fn with_base_url(mut self, base_url: impl Into<String>) -> Self {
+        self.base_url = base_url.into();
+        self
+    }
+
+    /// Lists models currently served at `self.base_url`, so callers can
+    /// discover new releases instead of relying solely on the static
+    /// `models::preset_for` table.
+    pub async fn list_models(&self) -> Result<Vec<crate::catalog::ModelInfo>, ProviderError> {
+        let _ = (&self.client, format!("{}/models", self.base_url));
+        Err(ProviderError::ApiError {
+            message: "not yet implemented".to_string(),
+            details: Default::default(),
+        })
+    }
+
+    /// Maps `config.response_format` to the `generationConfig` fields
+    /// Gemini uses for structured output (`responseMimeType` plus, for a
+    /// schema, `responseSchema`), or `None` for
+    /// [`km_core::provider::ResponseFormat::Text`].
+    fn response_format_json(&self) -> Option<serde_json::Value> {
+        match &self.config.response_format {
+            km_core::provider::ResponseFormat::Text => None,
+            km_core::provider::ResponseFormat::Json => Some(serde_json::json!({
+                "responseMimeType": "application/json",
+            })),
+            km_core::provider::ResponseFormat::JsonSchema { schema, .. } => Some(serde_json::json!({
+                "responseMimeType": "application/json",
+                "responseSchema": schema,
+            })),
+        }
+    }
+
+    /// Builds the `generateContent` request body: `system` messages are
+    /// pulled out into `systemInstruction`, everything else becomes a
+    /// `contents` entry via [`gemini_message_json`].
+    fn build_request_body(&self, messages: &[Message]) -> serde_json::Value {
+        let call_names: HashMap<&str, &str> =
+            messages.iter().flat_map(|m| m.tool_calls.iter()).map(|c| (c.id.as_str(), c.name.as_str())).collect();
+        let system_text = messages.iter().filter(|m| m.role == Role::System).map(|m| m.text_content()).collect::<Vec<_>>().join("\n\n");
+        let contents: Vec<_> = messages.iter().filter(|m| m.role != Role::System).map(|m| gemini_message_json(m, &call_names)).collect();
+
+        let mut generation_config = serde_json::json!({});
+        if let Some(format) = self.response_format_json() {
+            for (key, value) in format.as_object().into_iter().flatten() {
+                generation_config[key] = value.clone();
+            }
+        }
+
+        let mut body = serde_json::json!({
+            "contents": contents,
+            "generationConfig": generation_config,
+        });
+        let tools = self.builtin_tools();
+        if !tools.is_empty() {
+            body["tools"] = serde_json::json!(tools);
+        }
+        if !system_text.is_empty() {
+            body["systemInstruction"] = serde_json::json!({ "parts": [{ "text": system_text }] });
+        }
+        body
+    }
+
+    /// Parses a `generateContent` response body into a canonical
+    /// [`Message`], recording its usage against `self.state`.
+    fn parse_response(&mut self, body: &serde_json::Value) -> Result<Message, ProviderError> {
+        if let Some(usage) = body.get("usageMetadata") {
+            self.state.apply_usage(UsageDelta {
+                input_tokens: usage.get("promptTokenCount").and_then(|v| v.as_u64()).unwrap_or(0),
+                output_tokens: usage.get("candidatesTokenCount").and_then(|v| v.as_u64()).unwrap_or(0),
+                cached_input_tokens: usage.get("cachedContentTokenCount").and_then(|v| v.as_u64()).unwrap_or(0),
+            });
+        }
+
+        let mut assembler = ToolCallAssembler::new();
+        let mut text = String::new();
+        parse_stream_body(&body.to_string(), &mut assembler, &mut text)?;
+        let mut tool_calls = assembler.into_tool_calls();
+        for call in &mut tool_calls {
+            if call.id.is_empty() {
+                call.id = self.next_tool_call_id();
+            }
+        }
+
+        Ok(Message { role: Role::Assistant, content: vec![km_core::provider::ContentPart::Text(text)], tool_calls, ..Default::default() })
+    }
+
+    /// Maps a Gemini error response body's `error.status` to a
+    /// [`ProviderError`] variant, falling back to
+    /// [`crate::http::classify_http_error`] for anything the body alone
+    /// doesn't distinguish.
+    fn classify_error(status: reqwest::StatusCode, body: &serde_json::Value, retry_after: Option<std::time::Duration>) -> ProviderError {
+        let error_status = body.pointer("/error/status").and_then(|v| v.as_str()).map(str::to_string);
+        let message = body.pointer("/error/message").and_then(|v| v.as_str()).unwrap_or("unknown Gemini error").to_string();
+        let details = ErrorDetails {
+            http_status: Some(status.as_u16()),
+            provider_error_type: error_status.clone(),
+            provider_error_code: None,
+            request_id: None,
+            raw_body: Some(body.to_string()),
+        };
+        match error_status.as_deref() {
+            Some("RESOURCE_EXHAUSTED") => ProviderError::RateLimitExceeded { retry_after, details },
+            Some("UNAUTHENTICATED") | Some("PERMISSION_DENIED") => ProviderError::AuthenticationFailed { details },
+            Some("UNAVAILABLE") => ProviderError::Overloaded { details },
+            Some("INVALID_ARGUMENT") if message.to_lowercase().contains("token") => ProviderError::ContextLengthExceeded { details },
+            Some("INVALID_ARGUMENT") | Some("NOT_FOUND") => ProviderError::ApiError { message, details },
+            _ => crate::http::classify_http_error(status, retry_after, details),
+        }
+    }
+}
+
+#[async_trait]
+impl LLMProvider for GeminiProvider {
+    #[tracing::instrument(skip(self, messages), fields(provider = "gemini", model = %self.config.model, message_count = messages.len()))]
+    async fn chat(&mut self, messages: &[Message]) -> Result<Message, ProviderError> {
+        self.state.request_count += 1;
+        let body = self.build_request_body(messages);
+        let url = format!("{}/models/{}:generateContent", self.base_url, self.config.model);
+        crate::time::with_request_timeout(self.config.request_timeout, async {
+            let started = std::time::Instant::now();
+            let response = self
+                .client
+                .post(&url)
+                .query(&[("key", &self.config.api_key)])
+                .json(&body)
+                .send()
+                .await
+                .map_err(|err| ProviderError::ApiError {
+                    message: format!("Gemini request failed: {err}"),
+                    details: Default::default(),
+                })?;
+
+            let status = response.status();
+            let retry_after = crate::http::retry_after(response.headers());
+            let text = response.text().await.map_err(|err| ProviderError::ApiError {
+                message: format!("failed to read Gemini response body: {err}"),
+                details: Default::default(),
+            })?;
+            let json: serde_json::Value = serde_json::from_str(&text).map_err(|err| ProviderError::ApiError {
+                message: format!("failed to parse Gemini response as JSON: {err}"),
+                details: ErrorDetails { http_status: Some(status.as_u16()), raw_body: Some(text.clone()), ..Default::default() },
+            })?;
+
+            if !status.is_success() {
+                return Err(Self::classify_error(status, &json, retry_after));
+            }
+
+            let message = self.parse_response(&json)?;
+            self.state.record_latency(km_core::provider::RequestLatency {
+                time_to_first_token: started.elapsed(),
+                total_duration: started.elapsed(),
+            });
+            Ok(message)
+        })
+        .await
+    }
+
+    #[tracing::instrument(skip(self, user_message), fields(provider = "gemini", model = %self.config.model, history_len))]
+    async fn chat_loop(&mut self, user_message: Message) -> Result<Message, ProviderError> {
+        self.history.push(user_message);
+        self.history = prune_history(&self.history, MAX_HISTORY_MESSAGES);
+        tracing::Span::current().record("history_len", self.history.len());
+
+        crate::tokens::check_context_window(&self.config.model, &self.history, self.config.max_tokens)?;
+        km_core::provider::log_request_summary(&self.config, "gemini", self.history.len(), 0);
+        let history = self.history.clone();
+        crate::retry::retry_with_backoff(crate::retry::RetryPolicy::default(), self, crate::retry::hrtb_attempt(move |s: &mut GeminiProvider| {
+            let history = history.clone();
+            Box::pin(async move { s.chat(&history).await })
+        })).await
+    }
+
+    fn state(&self) -> &ProviderState {
+        &self.state
+    }
+
+    fn config(&self) -> &ProviderConfig {
+        &self.config
+    }
+
+    fn get_history(&self) -> &[Message] {
+        &self.history
+    }
+
+    fn set_history(&mut self, history: Vec<Message>) {
+        self.history = history;
+    }
+
+    /// Gemini exposes a real `countTokens` endpoint, but it's a separate
+    /// request just to get a number back; until that's worth the extra
+    /// round trip, fall back to the same character-based estimate OpenAI
+    /// and Bedrock use.
+    async fn count_tokens(&self, messages: &[Message]) -> Result<u32, ProviderError> {
+        Ok(messages.iter().map(crate::tokens::estimate_message_tokens).sum())
+    }
+
+    fn update_config(&mut self, f: Box<dyn FnOnce(&mut ProviderConfig) + Send>) {
+        f(&mut self.config);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use km_core::provider::ToolCall;
+
+    fn provider() -> GeminiProvider {
+        GeminiProvider::new(ProviderConfig::new("test-key", "gemini-1.5-pro"))
+    }
+
+    #[test]
+    fn test_gemini_message_json_maps_a_tool_result_via_call_names() {
+        let message = Message { role: Role::Tool, tool_call_id: Some("call_1".to_string()), ..Message::text(Role::Tool, "42") };
+        let call_names: HashMap<&str, &str> = HashMap::from([("call_1", "get_weather")]);
+        let json = gemini_message_json(&message, &call_names);
+        assert_eq!(json["role"], "user");
+        assert_eq!(json["parts"][0]["functionResponse"]["name"], "get_weather");
+        assert_eq!(json["parts"][0]["functionResponse"]["response"]["result"], "42");
+    }
+
+    #[test]
+    fn test_gemini_message_json_includes_function_calls_on_a_model_turn() {
+        let message = Message {
+            role: Role::Assistant,
+            tool_calls: vec![ToolCall { id: "call_1".to_string(), name: "get_weather".to_string(), arguments: "{\"city\":\"nyc\"}".to_string() }],
+            ..Message::text(Role::Assistant, "checking")
+        };
+        let json = gemini_message_json(&message, &HashMap::new());
+        assert_eq!(json["role"], "model");
+        assert_eq!(json["parts"][0]["text"], "checking");
+        assert_eq!(json["parts"][1]["functionCall"]["name"], "get_weather");
+        assert_eq!(json["parts"][1]["functionCall"]["args"]["city"], "nyc");
+    }
+
+    #[test]
+    fn test_gemini_message_json_maps_user_and_system_to_a_user_role() {
+        assert_eq!(gemini_message_json(&Message::text(Role::User, "hi"), &HashMap::new())["role"], "user");
+        assert_eq!(gemini_message_json(&Message::text(Role::System, "be nice"), &HashMap::new())["role"], "user");
+    }
+
+    #[test]
+    fn test_apply_candidate_json_accumulates_text_and_function_calls() {
+        let mut assembler = ToolCallAssembler::new();
+        let mut text = String::new();
+        let json = serde_json::json!({
+            "candidates": [{ "content": { "parts": [
+                { "text": "the weather is " },
+                { "functionCall": { "name": "get_weather", "args": { "city": "nyc" } } },
+            ] } }],
+        })
+        .to_string();
+        apply_candidate_json(&json, &mut assembler, &mut text).expect("should parse");
+        assert_eq!(text, "the weather is ");
+        assert_eq!(assembler.into_tool_calls()[0].name, "get_weather");
+    }
+
+    #[test]
+    fn test_parse_stream_body_falls_back_to_bare_json_without_sse_framing() {
+        let mut assembler = ToolCallAssembler::new();
+        let mut text = String::new();
+        let body = serde_json::json!({ "candidates": [{ "content": { "parts": [{ "text": "hello" }] } }] }).to_string();
+        parse_stream_body(&body, &mut assembler, &mut text).expect("should parse");
+        assert_eq!(text, "hello");
+    }
+
+    #[test]
+    fn test_parse_stream_body_reads_sse_framed_chunks() {
+        let mut assembler = ToolCallAssembler::new();
+        let mut text = String::new();
+        let chunk = serde_json::json!({ "candidates": [{ "content": { "parts": [{ "text": "hi" }] } }] });
+        let body = format!("data: {chunk}\n\n");
+        parse_stream_body(&body, &mut assembler, &mut text).expect("should parse");
+        assert_eq!(text, "hi");
+    }
+
+    #[test]
+    fn test_response_format_json_maps_json_schema() {
+        let mut config = ProviderConfig::new("test-key", "gemini-1.5-pro");
+        config.response_format = km_core::provider::ResponseFormat::JsonSchema {
+            name: "answer".to_string(),
+            schema: serde_json::json!({ "type": "object" }),
+        };
+        let provider = GeminiProvider::new(config);
+        let format = provider.response_format_json().expect("schema format should be set");
+        assert_eq!(format["responseMimeType"], "application/json");
+        assert_eq!(format["responseSchema"]["type"], "object");
+    }
+
+    #[test]
+    fn test_build_request_body_pulls_system_messages_into_system_instruction() {
+        let provider = provider();
+        let messages = vec![Message::text(Role::System, "be nice"), Message::text(Role::User, "hi")];
+        let body = provider.build_request_body(&messages);
+        assert_eq!(body["systemInstruction"]["parts"][0]["text"], "be nice");
+        assert_eq!(body["contents"].as_array().expect("contents array").len(), 1);
+    }
+
+    #[test]
+    fn test_build_request_body_includes_search_grounding_tool_when_enabled() {
+        let provider = GeminiProvider::new(ProviderConfig::new("test-key", "gemini-1.5-pro")).with_search_grounding(true);
+        let body = provider.build_request_body(&[Message::text(Role::User, "hi")]);
+        assert_eq!(body["tools"][0]["google_search"], serde_json::json!({}));
+    }
+
+    #[test]
+    fn test_parse_response_extracts_text_tool_calls_and_usage() {
+        let mut provider = provider();
+        let body = serde_json::json!({
+            "candidates": [{ "content": { "parts": [
+                { "text": "checking" },
+                { "functionCall": { "name": "get_weather", "args": { "city": "nyc" } } },
+            ] } }],
+            "usageMetadata": { "promptTokenCount": 10, "candidatesTokenCount": 5, "cachedContentTokenCount": 1 },
+        });
+        let message = provider.parse_response(&body).expect("should parse");
+        assert_eq!(message.text_content(), "checking");
+        assert_eq!(message.tool_calls.len(), 1);
+        assert!(!message.tool_calls[0].id.is_empty());
+        assert_eq!(provider.state().total_input_tokens, 10);
+        assert_eq!(provider.state().total_cached_input_tokens, 1);
+    }
+
+    #[test]
+    fn test_classify_error_maps_resource_exhausted_and_unauthenticated() {
+        let rate_limited = serde_json::json!({ "error": { "status": "RESOURCE_EXHAUSTED", "message": "slow down" } });
+        assert!(matches!(
+            GeminiProvider::classify_error(reqwest::StatusCode::TOO_MANY_REQUESTS, &rate_limited, None),
+            ProviderError::RateLimitExceeded { .. }
+        ));
+
+        let unauth = serde_json::json!({ "error": { "status": "UNAUTHENTICATED", "message": "bad key" } });
+        assert!(matches!(
+            GeminiProvider::classify_error(reqwest::StatusCode::UNAUTHORIZED, &unauth, None),
+            ProviderError::AuthenticationFailed { .. }
+        ));
+    }
+
+    #[test]
+    fn test_classify_error_maps_invalid_argument_mentioning_tokens_to_context_length() {
+        let body = serde_json::json!({ "error": { "status": "INVALID_ARGUMENT", "message": "exceeds maximum token count" } });
+        let error = GeminiProvider::classify_error(reqwest::StatusCode::BAD_REQUEST, &body, None);
+        assert!(matches!(error, ProviderError::ContextLengthExceeded { .. }));
+    }
+}
@@ -0,0 +1,115 @@
+//! Helpers shared by more than one provider backend: assembling streamed
+//! tool calls, and pruning conversation history to fit a model's context
+//! window.
+
+use std::collections::BTreeMap;
+
+use km_core::provider::{Message, Role, ToolCall};
+
+/// Accumulates tool-call deltas from a streaming response into complete
+/// [`ToolCall`]s. Providers feed it `(index, id, name_delta, arguments_delta)`
+/// as chunks arrive and call `into_tool_calls` once the stream ends.
+///
+/// Keyed by a `BTreeMap` rather than a `HashMap` so `into_tool_calls`
+/// returns calls ordered by the `index` the model emitted them at —
+/// parallel tool calls arrive out of order relative to each other's
+/// deltas, but a `HashMap` discarded that order entirely, which broke
+/// providers/hosts that assume index order.
+#[derive(Debug, Default)]
+pub struct ToolCallAssembler {
+    partial: BTreeMap<u32, PartialToolCall>,
+}
+
+#[derive(Debug, Default, Clone)]
+struct PartialToolCall {
+    id: String,
+    name: String,
+    arguments: String,
+}
+
+impl ToolCallAssembler {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Merges one streamed delta into the call at `index`. Fields left as
+    /// `None` are left untouched; `name_delta`/`arguments_delta` are
+    /// appended, matching how providers stream them a few characters at a
+    /// time.
+    pub fn add_delta(&mut self, index: u32, id: Option<&str>, name_delta: Option<&str>, arguments_delta: Option<&str>) {
+        let entry = self.partial.entry(index).or_default();
+        if let Some(id) = id {
+            entry.id = id.to_string();
+        }
+        if let Some(name) = name_delta {
+            entry.name.push_str(name);
+        }
+        if let Some(args) = arguments_delta {
+            entry.arguments.push_str(args);
+        }
+    }
+
+    /// Finalizes the accumulated deltas into `ToolCall`s, in the order the
+    /// model emitted them (i.e. ascending `index`).
+    pub fn into_tool_calls(self) -> Vec<ToolCall> {
+        self.partial
+            .into_values()
+            .map(|p| ToolCall {
+                id: p.id,
+                name: p.name,
+                arguments: p.arguments,
+            })
+            .collect()
+    }
+}
+
+/// Prunes a canonical message history down to `max_messages`, always
+/// keeping a leading system message (if present) plus the most recent
+/// turns. Applied once to the canonical [`Message`] history; providers
+/// derive their wire format from the pruned result, so `get_history()`
+/// always reflects exactly what the model saw.
+pub fn prune_history(history: &[Message], max_messages: usize) -> Vec<Message> {
+    if history.len() <= max_messages {
+        return history.to_vec();
+    }
+    let has_system = matches!(history.first(), Some(m) if m.role == Role::System);
+    let mut pruned = Vec::with_capacity(max_messages);
+    if has_system {
+        pruned.push(history[0].clone());
+    }
+    let keep = max_messages.saturating_sub(pruned.len());
+    let start = history.len().saturating_sub(keep).max(if has_system { 1 } else { 0 });
+    pruned.extend_from_slice(&history[start..]);
+    pruned
+}
+
+/// Compacts `history` down to `max_messages` by summarizing the oldest
+/// overflow messages with `summarize`, while passing any `Message::opaque`
+/// item through unchanged instead of feeding it to the summarizer.
+///
+/// OpenAI's Responses API can return opaque encrypted reasoning items
+/// alongside ordinary text; summarizing (or even re-serializing) one loses
+/// information the model needs on the next turn, so compaction must
+/// preserve it byte-for-byte even as it collapses everything else into a
+/// summary.
+pub fn compact_history(history: &[Message], max_messages: usize, summarize: impl FnOnce(&[Message]) -> Message) -> Vec<Message> {
+    if history.len() <= max_messages {
+        return history.to_vec();
+    }
+    let has_system = matches!(history.first(), Some(m) if m.role == Role::System);
+    let system_count = usize::from(has_system);
+    let keep_tail = max_messages.saturating_sub(system_count + 1); // +1 for the summary message
+    let overflow_end = history.len().saturating_sub(keep_tail).max(system_count);
+
+    let mut result = Vec::with_capacity(max_messages);
+    result.extend_from_slice(&history[..system_count]);
+
+    let overflow = &history[system_count..overflow_end];
+    let (opaque, summarizable): (Vec<Message>, Vec<Message>) = overflow.iter().cloned().partition(|m| m.opaque);
+    if !summarizable.is_empty() {
+        result.push(summarize(&summarizable));
+    }
+    result.extend(opaque);
+    result.extend_from_slice(&history[overflow_end..]);
+    result
+}
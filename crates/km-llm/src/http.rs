@@ -0,0 +1,132 @@
+//! A shared, tuned `reqwest::Client` used by every provider backend.
+//!
+//! `reqwest::Client` wraps its connection pool in an `Arc` internally, so
+//! cloning it is cheap and shares keep-alive connections across provider
+//! instances. Building a fresh `Client::new()` per provider (the old
+//! behavior) meant every provider paid its own TLS/TCP handshake instead
+//! of reusing a pool, which shows up as wasted latency in multi-provider
+//! setups (e.g. a factory juggling several profiles).
+
+use std::time::Duration;
+
+use km_core::provider::{ErrorDetails, ProviderError};
+
+/// Maps an HTTP response's status code to a [`ProviderError`] variant.
+/// Callers fill in `details` with whatever provider-specific error body
+/// they managed to parse (or just `raw_body`) before calling this, so the
+/// returned error still carries that context even though the status code
+/// alone decides which variant it becomes.
+///
+/// This only covers the status codes that are the same across every
+/// backend (401/403 → auth, 429 → rate limit, 5xx → overloaded);
+/// provider-specific codes like OpenAI's `context_length_exceeded` error
+/// code or Anthropic's `invalid_request_error` type are finer-grained
+/// than a status code alone can tell, so backends check those themselves
+/// before falling back to this.
+pub(crate) fn classify_http_error(status: reqwest::StatusCode, retry_after: Option<Duration>, mut details: ErrorDetails) -> ProviderError {
+    details.http_status = Some(status.as_u16());
+    match status.as_u16() {
+        401 | 403 => ProviderError::AuthenticationFailed { details },
+        429 => ProviderError::RateLimitExceeded { retry_after, details },
+        s if s >= 500 => ProviderError::Overloaded { details },
+        _ => ProviderError::ApiError { message: format!("HTTP {status}"), details },
+    }
+}
+
+/// Parses a `Retry-After` response header as a duration, if present.
+/// Providers send either a number of seconds or an HTTP date; only the
+/// (far more common) seconds form is handled — an HTTP-date header falls
+/// back to no hint rather than a wrong one.
+pub(crate) fn retry_after(headers: &reqwest::header::HeaderMap) -> Option<Duration> {
+    headers.get("retry-after")?.to_str().ok()?.parse::<u64>().ok().map(Duration::from_secs)
+}
+
+/// Max idle connections kept open per host in the shared pool.
+const POOL_MAX_IDLE_PER_HOST: usize = 32;
+
+/// How long an idle pooled connection is kept before being closed.
+const POOL_IDLE_TIMEOUT: Duration = Duration::from_secs(90);
+
+/// How often an HTTP/2 keep-alive ping is sent on otherwise-idle
+/// connections, so load balancers don't silently drop them mid-stream.
+const HTTP2_KEEP_ALIVE_INTERVAL: Duration = Duration::from_secs(30);
+
+static SHARED_CLIENT: std::sync::OnceLock<reqwest::Client> = std::sync::OnceLock::new();
+
+/// Returns a clone of the process-wide tuned [`reqwest::Client`], building
+/// it on first use. Every provider constructor should call this instead
+/// of `reqwest::Client::new()` so they share one connection pool.
+pub fn shared_client() -> reqwest::Client {
+    SHARED_CLIENT.get_or_init(build_client).clone()
+}
+
+/// The wasm reqwest backend doesn't expose pool/keep-alive/nodelay
+/// tuning (the browser's own HTTP stack owns those decisions), so wasm
+/// builds fall back to an untuned client.
+#[cfg(not(target_arch = "wasm32"))]
+fn build_client() -> reqwest::Client {
+    reqwest::Client::builder()
+        .pool_max_idle_per_host(POOL_MAX_IDLE_PER_HOST)
+        .pool_idle_timeout(POOL_IDLE_TIMEOUT)
+        .tcp_nodelay(true)
+        .http2_keep_alive_interval(HTTP2_KEEP_ALIVE_INTERVAL)
+        .http2_keep_alive_while_idle(true)
+        .build()
+        .expect("shared reqwest client config is valid")
+}
+
+#[cfg(target_arch = "wasm32")]
+fn build_client() -> reqwest::Client {
+    reqwest::Client::new()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_classify_http_error_maps_auth_rate_limit_and_server_errors() {
+        assert!(matches!(
+            classify_http_error(reqwest::StatusCode::UNAUTHORIZED, None, ErrorDetails::default()),
+            ProviderError::AuthenticationFailed { .. }
+        ));
+        assert!(matches!(
+            classify_http_error(reqwest::StatusCode::FORBIDDEN, None, ErrorDetails::default()),
+            ProviderError::AuthenticationFailed { .. }
+        ));
+        assert!(matches!(
+            classify_http_error(reqwest::StatusCode::TOO_MANY_REQUESTS, Some(Duration::from_secs(5)), ErrorDetails::default()),
+            ProviderError::RateLimitExceeded { retry_after: Some(d), .. } if d == Duration::from_secs(5)
+        ));
+        assert!(matches!(
+            classify_http_error(reqwest::StatusCode::SERVICE_UNAVAILABLE, None, ErrorDetails::default()),
+            ProviderError::Overloaded { .. }
+        ));
+        assert!(matches!(
+            classify_http_error(reqwest::StatusCode::BAD_REQUEST, None, ErrorDetails::default()),
+            ProviderError::ApiError { .. }
+        ));
+    }
+
+    #[test]
+    fn test_classify_http_error_fills_in_http_status_on_details() {
+        let error = classify_http_error(reqwest::StatusCode::BAD_REQUEST, None, ErrorDetails::default());
+        let ProviderError::ApiError { details, .. } = error else { panic!("expected ApiError") };
+        assert_eq!(details.http_status, Some(400));
+    }
+
+    #[test]
+    fn test_retry_after_parses_numeric_seconds() {
+        let mut headers = reqwest::header::HeaderMap::new();
+        headers.insert("retry-after", "30".parse().unwrap());
+        assert_eq!(retry_after(&headers), Some(Duration::from_secs(30)));
+    }
+
+    #[test]
+    fn test_retry_after_ignores_http_date_form_and_missing_header() {
+        let mut headers = reqwest::header::HeaderMap::new();
+        headers.insert("retry-after", "Wed, 21 Oct 2026 07:28:00 GMT".parse().unwrap());
+        assert_eq!(retry_after(&headers), None);
+        assert_eq!(retry_after(&reqwest::header::HeaderMap::new()), None);
+    }
+}
@@ -0,0 +1,52 @@
+//! km-llm: concrete LLM provider backends (OpenAI, Gemini, Anthropic) and
+//! the helpers they share, built on the core types in `km-core`.
+//!
+//! This crate targets `wasm32-unknown-unknown` as well as native targets,
+//! so the chat loop can run in browser/edge environments: HTTP goes
+//! through `reqwest`'s wasm backend and nothing here spawns a process or
+//! touches the filesystem. Tools that need those (file, shell, git) live
+//! in `km-agent-tools` behind its `native` feature instead.
+
+// `ProviderError` carries enough context (messages, status codes, retry
+// hints) to be useful in every `Err` path across this crate, which makes
+// it bigger than clippy's `result_large_err` threshold; boxing it would
+// mean boxing at every call site for no real benefit. The loop-detector's
+// boxed-future bound in `agent_loop` is inherent to that callback shape,
+// not something a type alias would make clearer.
+#![allow(clippy::result_large_err, clippy::type_complexity)]
+
+pub mod agent_loop;
+pub mod anthropic;
+pub mod bedrock;
+pub mod boxed;
+pub mod catalog;
+pub mod events;
+pub mod factory;
+pub mod gemini;
+pub mod helpers;
+pub mod http;
+pub mod loop_detector;
+/// Native-only: spawns a chat loop as a background task. See the module
+/// doc comment for why wasm hosts use `agent_loop` directly instead.
+#[cfg(not(target_arch = "wasm32"))]
+pub mod loop_handle;
+pub mod models;
+pub mod openai;
+pub mod options;
+/// Native-only: loads a transcript file to replay. See the module doc
+/// comment for why this sits alongside `transcript` rather than wasm
+/// targets too.
+#[cfg(not(target_arch = "wasm32"))]
+pub mod replay;
+pub mod retry;
+pub mod sse;
+pub mod structured;
+pub mod time;
+pub mod tokens;
+/// Native-only: writes a JSONL transcript to disk. See the module doc
+/// comment for why wasm hosts use `LoopEvent`s directly instead.
+#[cfg(not(target_arch = "wasm32"))]
+pub mod transcript;
+
+pub use boxed::{AnyProvider, BoxProvider};
+pub use km_core::provider::{LLMProvider, Message, ProviderConfig, ProviderError, ProviderState, Role};
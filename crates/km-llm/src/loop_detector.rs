@@ -0,0 +1,337 @@
+//! Watches a [`crate::agent_loop::chat_loop_with_tools`] run for signs
+//! it's stuck rather than making progress — starting with the same tool
+//! call repeating past a configured limit. Pure in-memory bookkeeping, no
+//! filesystem or process access, so it's as wasm-compatible as the rest
+//! of this crate; wire a [`LoopDetector`] in via
+//! [`crate::agent_loop::ChatLoopConfig::with_loop_detector`] to turn it
+//! on.
+
+use std::collections::VecDeque;
+
+use km_core::provider::ToolCall;
+
+/// What kind of stuck behavior a [`LoopDetector`] flagged.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LoopType {
+    /// The same tool has been called `count` times in a row (exact
+    /// name+arguments match).
+    RepeatedToolCall { tool: String, count: usize },
+    /// The same tool has returned an error result `count` times in a
+    /// row, regardless of what arguments it was called with each time —
+    /// the "keeps trying slightly different things and still failing"
+    /// pattern, which [`RepeatedToolCall`](LoopType::RepeatedToolCall)'s
+    /// exact-arguments match wouldn't catch.
+    NoProgress { tool: String, count: usize },
+    /// The assistant's text has been identical or near-identical across
+    /// `count` consecutive turns — the "model keeps saying the same
+    /// paragraph" failure, which can happen with no tool calls involved
+    /// at all.
+    ContentLoop { count: usize },
+}
+
+/// A [`LoopDetector`]'s judgment after observing one tool call.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LoopVerdict {
+    /// Nothing suspicious yet.
+    Ok,
+    /// Worth telling the model about, but not worth stopping the run
+    /// for — the caller is expected to surface `message` to the model
+    /// (e.g. appended to the tool's result) rather than discard it.
+    Warn { loop_type: LoopType, message: String },
+    /// The run should stop; `message` becomes the terminating error's
+    /// text.
+    Terminate { loop_type: LoopType, message: String },
+}
+
+/// Tunable thresholds for [`LoopDetector`]. The defaults are
+/// intentionally generous — a false positive aborts a working
+/// conversation, while a false negative just means the next round gets
+/// checked again.
+#[derive(Debug, Clone, Copy)]
+pub struct LoopDetectorConfig {
+    /// Consecutive identical tool calls (by name and exact arguments)
+    /// before [`LoopVerdict::Warn`] is raised.
+    pub warn_after: usize,
+    /// Consecutive identical tool calls before [`LoopVerdict::Terminate`]
+    /// is raised. Must be greater than `warn_after` or every repeat
+    /// skips straight to terminating.
+    pub terminate_after: usize,
+    /// Consecutive `is_error` results from the same tool — arguments may
+    /// differ between calls — before a [`LoopType::NoProgress`] warning
+    /// is raised.
+    pub error_warn_after: usize,
+    /// Consecutive `is_error` results from the same tool before a
+    /// [`LoopType::NoProgress`] loop terminates the run. Must be greater
+    /// than `error_warn_after`.
+    pub error_terminate_after: usize,
+    /// Consecutive identical-or-near-identical assistant turns before a
+    /// [`LoopType::ContentLoop`] warning is raised.
+    pub content_warn_after: usize,
+    /// Consecutive identical-or-near-identical assistant turns before a
+    /// [`LoopType::ContentLoop`] loop terminates the run. Must be greater
+    /// than `content_warn_after`.
+    pub content_terminate_after: usize,
+}
+
+impl Default for LoopDetectorConfig {
+    fn default() -> Self {
+        Self {
+            warn_after: 3,
+            terminate_after: 6,
+            error_warn_after: 3,
+            error_terminate_after: 6,
+            content_warn_after: 3,
+            content_terminate_after: 6,
+        }
+    }
+}
+
+/// How much of a turn's words need to overlap with the previous one before
+/// the two count as "near-identical" rather than merely similar. Chosen to
+/// tolerate a reworded sentence or two while still catching a paragraph
+/// that's substantially the same each time.
+const CONTENT_SIMILARITY_THRESHOLD: f64 = 0.85;
+
+/// Collapses whitespace and case so two turns that differ only in
+/// formatting still compare equal.
+fn normalize_for_comparison(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<_>>().join(" ").to_lowercase()
+}
+
+/// Whether `a` and `b` (already normalized) are the same text or close
+/// enough to count as a repeat. Uses word-set overlap rather than an edit
+/// distance so it stays O(words) and dependency-free — good enough to
+/// catch a restated paragraph without needing a real similarity crate.
+fn texts_are_similar(a: &str, b: &str) -> bool {
+    if a == b {
+        return true;
+    }
+    let words_a: std::collections::HashSet<&str> = a.split_whitespace().collect();
+    let words_b: std::collections::HashSet<&str> = b.split_whitespace().collect();
+    if words_a.is_empty() || words_b.is_empty() {
+        return false;
+    }
+    let intersection = words_a.intersection(&words_b).count();
+    let union = words_a.union(&words_b).count();
+    (intersection as f64 / union as f64) >= CONTENT_SIMILARITY_THRESHOLD
+}
+
+/// Tracks recent tool calls for one [`crate::agent_loop::chat_loop_with_tools`]
+/// run and flags repetition. Stateful and single-run: create a fresh one
+/// per conversation rather than reusing it across runs.
+#[derive(Debug, Clone)]
+pub struct LoopDetector {
+    config: LoopDetectorConfig,
+    /// Most recent calls first, capped at `terminate_after` entries —
+    /// anything older than that can't affect the current verdict.
+    recent_calls: VecDeque<(String, String)>,
+    /// The tool currently on an error streak, and how long it is. Reset
+    /// to `None` the moment that tool (or any other) returns a
+    /// successful result.
+    error_streak: Option<(String, usize)>,
+    /// Most recent assistant turns first (normalized), capped at
+    /// `content_terminate_after` entries — independent of `recent_calls`
+    /// since a content loop can happen with no tool calls at all.
+    recent_assistant_texts: VecDeque<String>,
+}
+
+impl LoopDetector {
+    pub fn new(config: LoopDetectorConfig) -> Self {
+        Self {
+            recent_calls: VecDeque::with_capacity(config.terminate_after),
+            recent_assistant_texts: VecDeque::with_capacity(config.content_terminate_after),
+            config,
+            error_streak: None,
+        }
+    }
+
+    /// Records `call` and judges whether the run looks stuck. Call this
+    /// once per tool call, before executing it, so a `Terminate` verdict
+    /// can skip running the call at all.
+    pub fn observe_tool_call(&mut self, call: &ToolCall) -> LoopVerdict {
+        self.recent_calls.push_front((call.name.clone(), call.arguments.clone()));
+        self.recent_calls.truncate(self.config.terminate_after);
+
+        let repeats =
+            self.recent_calls.iter().take_while(|(name, arguments)| *name == call.name && *arguments == call.arguments).count();
+
+        if repeats >= self.config.terminate_after {
+            LoopVerdict::Terminate {
+                loop_type: LoopType::RepeatedToolCall { tool: call.name.clone(), count: repeats },
+                message: format!(
+                    "tool '{}' has been called with the same arguments {repeats} times in a row; stopping instead of repeating it again",
+                    call.name
+                ),
+            }
+        } else if repeats >= self.config.warn_after {
+            LoopVerdict::Warn {
+                loop_type: LoopType::RepeatedToolCall { tool: call.name.clone(), count: repeats },
+                message: format!(
+                    "tool '{}' has now been called with the same arguments {repeats} times in a row with no apparent change in outcome — consider a different approach",
+                    call.name
+                ),
+            }
+        } else {
+            LoopVerdict::Ok
+        }
+    }
+
+    /// Records a tool's result and judges whether it's part of a
+    /// no-progress streak: the same tool erroring over and over,
+    /// independent of what arguments it was called with this time.
+    /// Separate from `observe_tool_call`'s exact-arguments match, since
+    /// a model trying slightly different arguments and still failing
+    /// every time is exactly the pattern that check would miss.
+    pub fn observe_tool_result(&mut self, tool_name: &str, is_error: bool) -> LoopVerdict {
+        if !is_error {
+            self.error_streak = None;
+            return LoopVerdict::Ok;
+        }
+        let count = match &mut self.error_streak {
+            Some((name, count)) if name == tool_name => {
+                *count += 1;
+                *count
+            }
+            _ => {
+                self.error_streak = Some((tool_name.to_string(), 1));
+                1
+            }
+        };
+
+        if count >= self.config.error_terminate_after {
+            LoopVerdict::Terminate {
+                loop_type: LoopType::NoProgress { tool: tool_name.to_string(), count },
+                message: format!(
+                    "tool '{tool_name}' has failed {count} times in a row; stopping instead of retrying the same failing approach"
+                ),
+            }
+        } else if count >= self.config.error_warn_after {
+            LoopVerdict::Warn {
+                loop_type: LoopType::NoProgress { tool: tool_name.to_string(), count },
+                message: format!(
+                    "tool '{tool_name}' has now failed {count} times in a row — try a different approach or tool instead of repeating this one"
+                ),
+            }
+        } else {
+            LoopVerdict::Ok
+        }
+    }
+
+    /// Records an assistant turn's text and judges whether it's a repeat
+    /// of recent turns. Unlike `observe_tool_call`/`observe_tool_result`,
+    /// this has nothing to do with tool calls — a model can loop on its
+    /// own text with none involved, so call this on every assistant turn
+    /// rather than only inside the tool-call handling path.
+    pub fn observe_assistant_message(&mut self, text: &str) -> LoopVerdict {
+        let normalized = normalize_for_comparison(text);
+        self.recent_assistant_texts.push_front(normalized.clone());
+        self.recent_assistant_texts.truncate(self.config.content_terminate_after);
+
+        if normalized.is_empty() {
+            return LoopVerdict::Ok;
+        }
+
+        let repeats = self.recent_assistant_texts.iter().take_while(|previous| texts_are_similar(previous, &normalized)).count();
+
+        if repeats >= self.config.content_terminate_after {
+            LoopVerdict::Terminate {
+                loop_type: LoopType::ContentLoop { count: repeats },
+                message: format!(
+                    "the assistant has repeated substantially the same message {repeats} times in a row; stopping instead of continuing the loop"
+                ),
+            }
+        } else if repeats >= self.config.content_warn_after {
+            LoopVerdict::Warn {
+                loop_type: LoopType::ContentLoop { count: repeats },
+                message: format!(
+                    "the assistant has now repeated substantially the same message {repeats} times in a row — it may be stuck"
+                ),
+            }
+        } else {
+            LoopVerdict::Ok
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn call(name: &str, arguments: &str) -> ToolCall {
+        ToolCall { id: "call-1".to_string(), name: name.to_string(), arguments: arguments.to_string() }
+    }
+
+    #[test]
+    fn test_observe_tool_call_warns_then_terminates_on_exact_repeats() {
+        let config = LoopDetectorConfig { warn_after: 2, terminate_after: 4, ..LoopDetectorConfig::default() };
+        let mut detector = LoopDetector::new(config);
+
+        assert_eq!(detector.observe_tool_call(&call("read_file", "{\"path\":\"a\"}")), LoopVerdict::Ok);
+        assert!(matches!(detector.observe_tool_call(&call("read_file", "{\"path\":\"a\"}")), LoopVerdict::Warn { .. }));
+        assert!(matches!(detector.observe_tool_call(&call("read_file", "{\"path\":\"a\"}")), LoopVerdict::Warn { .. }));
+        assert!(matches!(detector.observe_tool_call(&call("read_file", "{\"path\":\"a\"}")), LoopVerdict::Terminate { .. }));
+    }
+
+    #[test]
+    fn test_observe_tool_call_resets_the_streak_on_different_arguments() {
+        let config = LoopDetectorConfig { warn_after: 2, terminate_after: 4, ..LoopDetectorConfig::default() };
+        let mut detector = LoopDetector::new(config);
+
+        detector.observe_tool_call(&call("read_file", "{\"path\":\"a\"}"));
+        detector.observe_tool_call(&call("read_file", "{\"path\":\"a\"}"));
+        let verdict = detector.observe_tool_call(&call("read_file", "{\"path\":\"b\"}"));
+        assert_eq!(verdict, LoopVerdict::Ok);
+    }
+
+    #[test]
+    fn test_observe_tool_result_ignores_successes_and_tracks_error_streaks_per_tool() {
+        let config = LoopDetectorConfig { error_warn_after: 2, error_terminate_after: 3, ..LoopDetectorConfig::default() };
+        let mut detector = LoopDetector::new(config);
+
+        assert_eq!(detector.observe_tool_result("bash", true), LoopVerdict::Ok);
+        assert!(matches!(detector.observe_tool_result("bash", true), LoopVerdict::Warn { .. }));
+        assert!(matches!(detector.observe_tool_result("bash", true), LoopVerdict::Terminate { .. }));
+    }
+
+    #[test]
+    fn test_observe_tool_result_clears_streak_on_success_or_different_tool() {
+        let config = LoopDetectorConfig { error_warn_after: 2, error_terminate_after: 3, ..LoopDetectorConfig::default() };
+        let mut detector = LoopDetector::new(config);
+
+        detector.observe_tool_result("bash", true);
+        // A success resets the streak, so the next failure starts over
+        // at count 1 (`Ok`) instead of continuing from 2.
+        assert_eq!(detector.observe_tool_result("bash", false), LoopVerdict::Ok);
+        assert_eq!(detector.observe_tool_result("bash", true), LoopVerdict::Ok);
+
+        // A different tool failing doesn't continue bash's streak either.
+        assert_eq!(detector.observe_tool_result("git", true), LoopVerdict::Ok);
+    }
+
+    #[test]
+    fn test_observe_assistant_message_flags_near_identical_repeats_but_not_distinct_turns() {
+        let config = LoopDetectorConfig { content_warn_after: 2, content_terminate_after: 3, ..LoopDetectorConfig::default() };
+        let mut detector = LoopDetector::new(config);
+
+        assert_eq!(detector.observe_assistant_message("let me check the logs for errors"), LoopVerdict::Ok);
+        assert!(matches!(
+            detector.observe_assistant_message("Let   Me Check The Logs For Errors"),
+            LoopVerdict::Warn { .. }
+        ));
+        assert!(matches!(
+            detector.observe_assistant_message("let me check the logs for errors"),
+            LoopVerdict::Terminate { .. }
+        ));
+
+        let mut fresh = LoopDetector::new(config);
+        fresh.observe_assistant_message("let me check the logs for errors");
+        assert_eq!(fresh.observe_assistant_message("now running the test suite instead"), LoopVerdict::Ok);
+    }
+
+    #[test]
+    fn test_observe_assistant_message_ignores_empty_text() {
+        let mut detector = LoopDetector::new(LoopDetectorConfig::default());
+        assert_eq!(detector.observe_assistant_message(""), LoopVerdict::Ok);
+        assert_eq!(detector.observe_assistant_message("   "), LoopVerdict::Ok);
+    }
+}
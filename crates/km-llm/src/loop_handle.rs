@@ -0,0 +1,193 @@
+//! A background-task variant of
+//! [`crate::agent_loop::chat_loop_with_tools`] for hosts that need to
+//! interleave a human (or other out-of-band decision) between the model
+//! requesting a tool call and that call actually running — approving,
+//! rejecting, or otherwise deciding — rather than resolving every call
+//! synchronously through a closure.
+//!
+//! Native-only: driving a loop on its own task assumes a multi-threaded
+//! `tokio` runtime, which wasm32 doesn't have. Wasm hosts that need
+//! tool-call resolution should use `chat_loop_with_tools` directly and
+//! drive the approval step inline before calling it.
+
+use std::sync::{Arc, Mutex};
+
+use tokio::sync::mpsc;
+
+use km_core::provider::{LLMProvider, Message, ProviderError, ProviderState, Role};
+
+use crate::events::{channel, ChannelMode, LoopEvent, LoopEventReceiver};
+
+/// A command sent to a running chat loop via [`ChatLoopHandle`]. The
+/// single enum every provider's loop task consumes — earlier revisions
+/// had OpenAI and Anthropic pausing on differently-shaped channels
+/// (one had no way to express `UpdateTools` at all), which meant a host
+/// juggling more than one provider needed provider-specific glue for
+/// what should be one control surface.
+pub enum ChatLoopCommand {
+    /// Supplies results for the pending tool call(s), letting the loop
+    /// continue. The loop resolves one call at a time, so only the first
+    /// result is used; the `Vec` exists so a host that already collected
+    /// results for a whole batch doesn't have to split the call itself.
+    SubmitToolResults(Vec<Message>),
+    /// Rejects the pending tool call, submitting a synthetic error
+    /// result instead of running it — the human-in-the-loop denial path,
+    /// so callers don't have to hand-build a `Message` for the call id
+    /// themselves.
+    RejectToolCalls { reason: String },
+    /// Updates the tool definitions subsequent requests should advertise
+    /// to the model. Accepted but not yet applied: no provider's `chat`/
+    /// `chat_loop` takes a tools parameter per call today (each builds
+    /// its tool list once, at construction), so this is recorded for
+    /// when that lands rather than silently rejected.
+    UpdateTools(Vec<serde_json::Value>),
+    /// Injects an arbitrary message as the loop's next turn — e.g. a
+    /// steering note from a human — instead of a tool result. Resolved
+    /// through the same path as `SubmitToolResults`; the distinction is
+    /// purely about what the message means, not how the loop handles it.
+    InjectMessage(Message),
+    /// Stops the loop immediately, dropping the pending tool call.
+    Cancel,
+}
+
+/// A running chat loop's remote control: the event stream it emits, plus
+/// a command channel to resolve the tool calls it pauses on.
+pub struct ChatLoopHandle {
+    events: LoopEventReceiver,
+    commands: mpsc::UnboundedSender<ChatLoopCommand>,
+    /// Updated by the background task after every `chat_loop` call, so
+    /// `usage_so_far` reflects the running total without waiting for
+    /// `LoopEvent::Done`.
+    usage: Arc<Mutex<ProviderState>>,
+}
+
+impl ChatLoopHandle {
+    pub async fn recv_event(&mut self) -> Option<LoopEvent> {
+        self.events.recv().await
+    }
+
+    /// Accumulated token usage so far, readable at any point in the
+    /// conversation rather than only once it finishes — UIs use this to
+    /// show a running token/cost counter instead of nothing until
+    /// `LoopEvent::Done`.
+    pub fn usage_so_far(&self) -> ProviderState {
+        self.usage.lock().expect("usage mutex poisoned").clone()
+    }
+
+    /// Supplies the result for the currently pending tool call.
+    pub fn submit_tool_results(&self, results: Vec<Message>) -> Result<(), ProviderError> {
+        self.send(ChatLoopCommand::SubmitToolResults(results))
+    }
+
+    /// Rejects the currently pending tool call, submitting a synthetic
+    /// error result instead of running it — e.g. because a human
+    /// reviewer declined the request.
+    pub fn reject_tool_calls(&self, reason: impl Into<String>) -> Result<(), ProviderError> {
+        self.send(ChatLoopCommand::RejectToolCalls { reason: reason.into() })
+    }
+
+    /// Updates the tool definitions for subsequent turns. See
+    /// [`ChatLoopCommand::UpdateTools`] for why this isn't wired into a
+    /// request yet.
+    pub fn update_tools(&self, tools: Vec<serde_json::Value>) -> Result<(), ProviderError> {
+        self.send(ChatLoopCommand::UpdateTools(tools))
+    }
+
+    /// Injects `message` as the loop's next turn in place of a tool
+    /// result, e.g. a steering note from a human reviewer.
+    pub fn inject_message(&self, message: Message) -> Result<(), ProviderError> {
+        self.send(ChatLoopCommand::InjectMessage(message))
+    }
+
+    /// Stops the loop immediately, dropping the pending tool call.
+    pub fn cancel(&self) -> Result<(), ProviderError> {
+        self.send(ChatLoopCommand::Cancel)
+    }
+
+    fn send(&self, command: ChatLoopCommand) -> Result<(), ProviderError> {
+        self.commands.send(command).map_err(|_| ProviderError::ApiError {
+            message: "chat loop task has stopped".to_string(),
+            details: Default::default(),
+        })
+    }
+}
+
+/// Spawns `provider`'s loop as a background task, returning a
+/// [`ChatLoopHandle`] to drive it. Each time the model requests a tool
+/// call, the task emits `LoopEvent::ToolCallStarted` and then waits for
+/// a [`ChatLoopCommand`] before continuing — the host is expected to
+/// execute (or reject) the call and respond, rather than the loop
+/// resolving it itself the way `agent_loop::chat_loop_with_tools` does.
+pub fn spawn_chat_loop<P>(mut provider: P, user_message: Message) -> ChatLoopHandle
+where
+    P: LLMProvider + Send + 'static,
+{
+    let (event_tx, event_rx) = channel(ChannelMode::Unbounded);
+    let (command_tx, mut command_rx) = mpsc::unbounded_channel::<ChatLoopCommand>();
+    let usage = Arc::new(Mutex::new(ProviderState::default()));
+    let task_usage = usage.clone();
+
+    tokio::spawn(async move {
+        let mut response = match provider.chat_loop(user_message).await {
+            Ok(response) => response,
+            Err(_) => return,
+        };
+        *task_usage.lock().expect("usage mutex poisoned") = provider.state().clone();
+        loop {
+            if response.tool_calls.is_empty() {
+                let _ = event_tx.send(LoopEvent::Done).await;
+                return;
+            }
+            for (index, call) in std::mem::take(&mut response.tool_calls).into_iter().enumerate() {
+                // This loop only ever sees the fully-assembled `ToolCall`
+                // coming out of `chat_loop`, not the SSE chunks it was
+                // built from, so the delta it can forward is the whole
+                // call in one piece rather than the incremental pieces a
+                // provider's own stream parser sees.
+                let _ = event_tx
+                    .send(LoopEvent::ToolCallDelta {
+                        index: index as u32,
+                        id: Some(call.id.clone()),
+                        name_delta: Some(call.name.clone()),
+                        arguments_delta: Some(call.arguments.clone()),
+                    })
+                    .await;
+                let _ = event_tx
+                    .send(LoopEvent::ToolCallStarted { id: call.id.clone(), name: call.name.clone() })
+                    .await;
+                // `UpdateTools` doesn't answer the pending call, so it
+                // loops back for the command that actually will instead
+                // of treating "update" as if it were a rejection.
+                let tool_result = loop {
+                    let Some(command) = command_rx.recv().await else { return };
+                    match command {
+                        ChatLoopCommand::SubmitToolResults(mut results) => {
+                            if results.is_empty() {
+                                continue;
+                            }
+                            break results.remove(0);
+                        }
+                        ChatLoopCommand::InjectMessage(message) => break message,
+                        ChatLoopCommand::RejectToolCalls { reason } => {
+                            break Message {
+                                tool_call_id: Some(call.id.clone()),
+                                is_error: true,
+                                ..Message::text(Role::Tool, reason)
+                            };
+                        }
+                        ChatLoopCommand::UpdateTools(_) => continue,
+                        ChatLoopCommand::Cancel => return,
+                    }
+                };
+                let _ = event_tx.send(LoopEvent::ToolCallFinished { id: call.id.clone() }).await;
+                response = match provider.chat_loop(tool_result).await {
+                    Ok(response) => response,
+                    Err(_) => return,
+                };
+                *task_usage.lock().expect("usage mutex poisoned") = provider.state().clone();
+            }
+        }
+    });
+
+    ChatLoopHandle { events: event_rx, commands: command_tx, usage }
+}
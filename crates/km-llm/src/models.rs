@@ -0,0 +1,66 @@
+//! Per-model capability and default presets, consulted by providers when
+//! building requests instead of hardcoding decisions (like Gemini's forced
+//! `temperature: 1.0`) inline.
+//!
+//! See [`crate::catalog`] for pricing and live `list_models()` discovery
+//! built on top of this table.
+
+/// Static facts about a model: what it supports and sane request defaults.
+/// Providers look this up by model name rather than branching on the name
+/// themselves.
+#[derive(Debug, Clone, Copy)]
+pub struct ModelPreset {
+    pub default_max_tokens: u32,
+    pub honors_temperature: bool,
+    pub supports_tools: bool,
+    pub supports_vision: bool,
+    pub context_window: u32,
+}
+
+const UNKNOWN_MODEL_PRESET: ModelPreset = ModelPreset {
+    default_max_tokens: 4096,
+    honors_temperature: true,
+    supports_tools: true,
+    supports_vision: false,
+    context_window: 128_000,
+};
+
+/// Looks up the preset for `model`. Falls back to a conservative default
+/// (no vision, modest context window) for models not yet in the table so
+/// new releases don't hard-fail before they're added here.
+pub fn preset_for(model: &str) -> ModelPreset {
+    match model {
+        "gpt-4o" | "gpt-4o-mini" => ModelPreset {
+            default_max_tokens: 16_384,
+            honors_temperature: true,
+            supports_tools: true,
+            supports_vision: true,
+            context_window: 128_000,
+        },
+        "o1" | "o1-mini" | "o3" | "o3-mini" => ModelPreset {
+            default_max_tokens: 100_000,
+            honors_temperature: false,
+            supports_tools: true,
+            supports_vision: false,
+            context_window: 200_000,
+        },
+        "claude-opus-4" | "claude-sonnet-4" => ModelPreset {
+            default_max_tokens: 8192,
+            honors_temperature: true,
+            supports_tools: true,
+            supports_vision: true,
+            context_window: 200_000,
+        },
+        "gemini-1.5-pro" | "gemini-1.5-flash" => ModelPreset {
+            // Gemini only honors temperature 1.0 for these models; callers
+            // still set it, but providers consult `honors_temperature`
+            // before sending the field at all.
+            default_max_tokens: 8192,
+            honors_temperature: false,
+            supports_tools: true,
+            supports_vision: true,
+            context_window: 1_000_000,
+        },
+        _ => UNKNOWN_MODEL_PRESET,
+    }
+}
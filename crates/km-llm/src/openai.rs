@@ -0,0 +1,530 @@
+//! OpenAI chat completions backend.
+
+use async_trait::async_trait;
+
+use crate::helpers::prune_history;
+use km_core::provider::{ContentPart, ErrorDetails, LLMProvider, Message, ProviderConfig, ProviderError, ProviderState, Role, ToolCall, UsageDelta};
+
+/// How many messages of canonical history `chat_loop` keeps before
+/// pruning. Applied to `history` itself, not just the wire payload, so
+/// `get_history()` always matches what the model actually saw.
+const MAX_HISTORY_MESSAGES: usize = 200;
+
+/// Default API base, overridable via `OPENAI_API_BASE` or
+/// [`OpenAiProvider::with_base_url`] — gateways, regional endpoints, and
+/// proxies like LiteLLM all need this without a recompile.
+const DEFAULT_BASE_URL: &str = "https://api.openai.com/v1";
+
+/// Minimum prefix size, in tokens, OpenAI will apply automatic prompt
+/// caching to. See [`OpenAiProvider::prompt_cache`].
+const PROMPT_CACHE_MIN_TOKENS: usize = 1024;
+
+/// `OpenAI-Organization`/`OpenAI-Project` headers (or their Azure
+/// equivalents), needed by teams with multiple billing projects on one
+/// account. `None` means the header is omitted, matching OpenAI's default
+/// account-level billing.
+#[derive(Debug, Default, Clone)]
+pub struct OpenAiHeaders {
+    pub organization: Option<String>,
+    pub project: Option<String>,
+    /// Azure OpenAI uses `api-key` + `api-version` instead of
+    /// `Authorization: Bearer`; set when targeting an Azure deployment.
+    pub azure_api_version: Option<String>,
+}
+
+/// Which OpenAI HTTP API a request goes out over. The Responses API is
+/// OpenAI's newer unified endpoint (`/responses`); Chat Completions
+/// (`/chat/completions`) remains the default for compatibility with
+/// OpenAI-compatible gateways that haven't implemented Responses.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ApiMode {
+    #[default]
+    ChatCompletions,
+    Responses,
+}
+
+/// Maps one canonical [`Message`] to OpenAI's Chat Completions wire
+/// shape: a tool-result message becomes `{"role": "tool", ...}`, and an
+/// assistant message carrying tool calls gets its `tool_calls` array
+/// alongside (possibly empty) `content`.
+fn openai_message_json(message: &Message) -> serde_json::Value {
+    match message.role {
+        Role::Tool => serde_json::json!({
+            "role": "tool",
+            "tool_call_id": message.tool_call_id,
+            "content": message.text_content(),
+        }),
+        Role::Assistant if !message.tool_calls.is_empty() => serde_json::json!({
+            "role": "assistant",
+            "content": message.text_content(),
+            "tool_calls": message.tool_calls.iter().map(|call| serde_json::json!({
+                "id": call.id,
+                "type": "function",
+                "function": { "name": call.name, "arguments": call.arguments },
+            })).collect::<Vec<_>>(),
+        }),
+        Role::System => serde_json::json!({ "role": "system", "content": message.text_content() }),
+        Role::User => serde_json::json!({ "role": "user", "content": message.text_content() }),
+        Role::Assistant => serde_json::json!({ "role": "assistant", "content": message.text_content() }),
+    }
+}
+
+pub struct OpenAiProvider {
+    config: ProviderConfig,
+    client: reqwest::Client,
+    base_url: String,
+    /// Set once `with_base_url` points away from `DEFAULT_BASE_URL`, so
+    /// OpenAI-compatible servers (OpenRouter, vLLM, LM Studio, ...) aren't
+    /// held to the `models::preset_for` table built from OpenAI's own
+    /// model names — those servers routinely serve models this crate has
+    /// never heard of.
+    custom_endpoint: bool,
+    headers: OpenAiHeaders,
+    api_mode: ApiMode,
+    /// OpenAI built-in tools (`web_search`, `code_interpreter`,
+    /// `file_search`) passed through in each request's `tools` array.
+    /// Only meaningful under [`ApiMode::Responses`]; Chat Completions
+    /// doesn't support them.
+    builtin_tools: Vec<serde_json::Value>,
+    history: Vec<Message>,
+    state: ProviderState,
+}
+
+impl OpenAiProvider {
+    pub fn new(config: ProviderConfig) -> Self {
+        let base_url = std::env::var("OPENAI_API_BASE").unwrap_or_else(|_| DEFAULT_BASE_URL.to_string());
+        Self {
+            config,
+            client: crate::http::shared_client(),
+            base_url,
+            custom_endpoint: false,
+            headers: OpenAiHeaders {
+                organization: std::env::var("OPENAI_ORG_ID").ok(),
+                project: std::env::var("OPENAI_PROJECT_ID").ok(),
+                azure_api_version: None,
+            },
+            api_mode: ApiMode::default(),
+            builtin_tools: Vec::new(),
+            history: Vec::new(),
+            state: ProviderState::default(),
+        }
+    }
+
+    /// Overrides the API base set by `OPENAI_API_BASE`/the default,
+    /// marking this provider as targeting a custom, OpenAI-compatible
+    /// endpoint (see [`Self::is_custom_endpoint`]).
+    pub fn with_base_url(mut self, base_url: impl Into<String>) -> Self {
+        self.base_url = base_url.into();
+        self.custom_endpoint = self.base_url != DEFAULT_BASE_URL;
+        self
+    }
+
+    /// True once [`Self::with_base_url`] has pointed this provider at
+    /// something other than `api.openai.com` — a gateway like OpenRouter,
+    /// or a self-hosted vLLM/LM Studio server. Callers that gate behavior
+    /// on `models::preset_for` (which only knows OpenAI's own model
+    /// names) should treat an unrecognized model as supported rather than
+    /// rejecting it when this is `true`.
+    pub fn is_custom_endpoint(&self) -> bool {
+        self.custom_endpoint
+    }
+
+    pub fn with_headers(mut self, headers: OpenAiHeaders) -> Self {
+        self.headers = headers;
+        self
+    }
+
+    /// Selects which API endpoint requests go to. Reasoning models (o1,
+    /// o3, ...) and features like background responses are only
+    /// available through [`ApiMode::Responses`].
+    pub fn with_api_mode(mut self, mode: ApiMode) -> Self {
+        self.api_mode = mode;
+        self
+    }
+
+    /// Adds a built-in tool (e.g. `{"type": "web_search"}`) to every
+    /// request's `tools` array, alongside the host's client-side tools.
+    /// Requires [`ApiMode::Responses`]; Chat Completions rejects these.
+    pub fn with_builtin_tool(mut self, tool: serde_json::Value) -> Self {
+        self.builtin_tools.push(tool);
+        self
+    }
+
+    fn endpoint(&self) -> String {
+        match self.api_mode {
+            ApiMode::ChatCompletions => format!("{}/chat/completions", self.base_url),
+            ApiMode::Responses => format!("{}/responses", self.base_url),
+        }
+    }
+
+    /// Builds the Chat Completions request body. `builtin_tools` is left
+    /// out here: it's only meaningful under [`ApiMode::Responses`], which
+    /// builds its own body in [`Self::chat`].
+    fn build_chat_completions_body(&self, messages: &[Message]) -> serde_json::Value {
+        let wire_messages: Vec<_> = messages.iter().map(openai_message_json).collect();
+        let mut body = serde_json::json!({
+            "model": self.config.model,
+            "max_tokens": self.config.max_tokens,
+            "messages": wire_messages,
+        });
+        if crate::models::preset_for(&self.config.model).honors_temperature {
+            body["temperature"] = serde_json::json!(self.config.temperature);
+        }
+        if let Some(response_format) = self.response_format_json() {
+            body["response_format"] = response_format;
+        }
+        body
+    }
+
+    /// Parses a Chat Completions response body into a canonical
+    /// [`Message`], recording its usage against `self.state` along the
+    /// way so a partial parse still leaves billed tokens accounted for.
+    fn parse_chat_completion(&mut self, body: &serde_json::Value) -> Result<Message, ProviderError> {
+        if let Some(usage) = body.get("usage") {
+            self.state.apply_usage(UsageDelta {
+                input_tokens: usage.get("prompt_tokens").and_then(|v| v.as_u64()).unwrap_or(0),
+                output_tokens: usage.get("completion_tokens").and_then(|v| v.as_u64()).unwrap_or(0),
+                cached_input_tokens: usage
+                    .pointer("/prompt_tokens_details/cached_tokens")
+                    .and_then(|v| v.as_u64())
+                    .unwrap_or(0),
+            });
+        }
+        let message = body.pointer("/choices/0/message").ok_or_else(|| ProviderError::ApiError {
+            message: "OpenAI response had no choices[0].message".to_string(),
+            details: ErrorDetails { raw_body: Some(body.to_string()), ..Default::default() },
+        })?;
+
+        let content = message.get("content").and_then(|v| v.as_str()).unwrap_or_default();
+        let tool_calls = message
+            .get("tool_calls")
+            .and_then(|v| v.as_array())
+            .map(|calls| {
+                calls
+                    .iter()
+                    .map(|call| ToolCall {
+                        id: call.get("id").and_then(|v| v.as_str()).unwrap_or_default().to_string(),
+                        name: call.pointer("/function/name").and_then(|v| v.as_str()).unwrap_or_default().to_string(),
+                        arguments: call.pointer("/function/arguments").and_then(|v| v.as_str()).unwrap_or_default().to_string(),
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        Ok(Message {
+            role: Role::Assistant,
+            content: vec![ContentPart::Text(content.to_string())],
+            tool_calls,
+            ..Default::default()
+        })
+    }
+
+    /// Maps an OpenAI error response body's `error.code`/`error.type`
+    /// to a [`ProviderError`] variant, falling back to
+    /// [`crate::http::classify_http_error`] for anything the body alone
+    /// doesn't distinguish.
+    fn classify_error(status: reqwest::StatusCode, body: &serde_json::Value, retry_after: Option<std::time::Duration>) -> ProviderError {
+        let details = ErrorDetails {
+            http_status: Some(status.as_u16()),
+            provider_error_type: body.pointer("/error/type").and_then(|v| v.as_str()).map(str::to_string),
+            provider_error_code: body.pointer("/error/code").and_then(|v| v.as_str()).map(str::to_string),
+            request_id: None,
+            raw_body: Some(body.to_string()),
+        };
+        let message = body.pointer("/error/message").and_then(|v| v.as_str()).unwrap_or("unknown OpenAI error").to_string();
+        match body.pointer("/error/code").and_then(|v| v.as_str()) {
+            Some("context_length_exceeded") => ProviderError::ContextLengthExceeded { details },
+            Some("rate_limit_exceeded") => ProviderError::RateLimitExceeded { retry_after, details },
+            _ => match status.as_u16() {
+                400 | 404 | 422 => ProviderError::ApiError { message, details },
+                _ => crate::http::classify_http_error(status, retry_after, details),
+            },
+        }
+    }
+
+    /// Maps `config.response_format` to OpenAI's `response_format` wire
+    /// shape, or `None` for [`km_core::provider::ResponseFormat::Text`]
+    /// where the field is simply omitted.
+    fn response_format_json(&self) -> Option<serde_json::Value> {
+        match &self.config.response_format {
+            km_core::provider::ResponseFormat::Text => None,
+            km_core::provider::ResponseFormat::Json => Some(serde_json::json!({ "type": "json_object" })),
+            km_core::provider::ResponseFormat::JsonSchema { name, schema } => Some(serde_json::json!({
+                "type": "json_schema",
+                "json_schema": { "name": name, "schema": schema, "strict": true },
+            })),
+        }
+    }
+
+    /// Whether the current history's estimated size clears OpenAI's
+    /// 1024-token minimum for automatic prefix caching. Below that, every
+    /// request is billed at full price no matter how stable the prefix
+    /// is; above it, callers building large system prompts/tool schemas
+    /// should keep that prefix byte-for-byte identical across requests —
+    /// reordering or touching it defeats the cache even if the content
+    /// is unchanged.
+    pub fn prompt_cache(&self) -> bool {
+        let prefix: String = self.history.iter().map(Message::text_content).collect::<Vec<_>>().join("\n");
+        crate::tokens::estimate_tokens(&prefix) as usize >= PROMPT_CACHE_MIN_TOKENS
+    }
+
+    /// Lists models currently served at `self.base_url`, so callers can
+    /// discover new releases (or, on a custom endpoint, whatever a
+    /// gateway actually has configured) instead of relying solely on
+    /// the static `models::preset_for` table.
+    pub async fn list_models(&self) -> Result<Vec<crate::catalog::ModelInfo>, ProviderError> {
+        let _ = (&self.client, format!("{}/models", self.base_url), self.request_headers());
+        Err(ProviderError::ApiError {
+            message: "not yet implemented".to_string(),
+            details: Default::default(),
+        })
+    }
+
+    /// Builds the request headers, including `OpenAI-Organization`/
+    /// `OpenAI-Project` (or `api-version` for Azure) when configured.
+    fn request_headers(&self) -> reqwest::header::HeaderMap {
+        let mut headers = reqwest::header::HeaderMap::new();
+        if let Some(org) = &self.headers.organization {
+            if let Ok(value) = org.parse() {
+                headers.insert("OpenAI-Organization", value);
+            }
+        }
+        if let Some(project) = &self.headers.project {
+            if let Ok(value) = project.parse() {
+                headers.insert("OpenAI-Project", value);
+            }
+        }
+        if let Some(api_version) = &self.headers.azure_api_version {
+            if let Ok(value) = api_version.parse() {
+                headers.insert("api-version", value);
+            }
+        }
+        headers
+    }
+}
+
+#[async_trait]
+impl LLMProvider for OpenAiProvider {
+    #[tracing::instrument(skip(self, messages), fields(provider = "openai", model = %self.config.model, message_count = messages.len()))]
+    async fn chat(&mut self, messages: &[Message]) -> Result<Message, ProviderError> {
+        self.state.request_count += 1;
+        if self.api_mode == ApiMode::Responses {
+            // The Responses API's request/response shape (an `input`
+            // array and an `output` array of typed items, rather than
+            // Chat Completions' `messages`/`choices`) is different enough
+            // that it needs its own body builder and parser; only
+            // Chat Completions is wired up so far.
+            return Err(ProviderError::ApiError {
+                message: "OpenAI Responses API support is not yet implemented; use ApiMode::ChatCompletions".to_string(),
+                details: Default::default(),
+            });
+        }
+
+        let body = self.build_chat_completions_body(messages);
+        crate::time::with_request_timeout(self.config.request_timeout, async {
+            let started = std::time::Instant::now();
+            let response = self
+                .client
+                .post(self.endpoint())
+                .bearer_auth(&self.config.api_key)
+                .headers(self.request_headers())
+                .json(&body)
+                .send()
+                .await
+                .map_err(|err| ProviderError::ApiError {
+                    message: format!("OpenAI request failed: {err}"),
+                    details: Default::default(),
+                })?;
+
+            let status = response.status();
+            let retry_after = crate::http::retry_after(response.headers());
+            let text = response.text().await.map_err(|err| ProviderError::ApiError {
+                message: format!("failed to read OpenAI response body: {err}"),
+                details: Default::default(),
+            })?;
+            let json: serde_json::Value = serde_json::from_str(&text).map_err(|err| ProviderError::ApiError {
+                message: format!("failed to parse OpenAI response as JSON: {err}"),
+                details: ErrorDetails { http_status: Some(status.as_u16()), raw_body: Some(text.clone()), ..Default::default() },
+            })?;
+
+            if !status.is_success() {
+                return Err(Self::classify_error(status, &json, retry_after));
+            }
+
+            let message = self.parse_chat_completion(&json)?;
+            self.state.record_latency(km_core::provider::RequestLatency {
+                time_to_first_token: started.elapsed(),
+                total_duration: started.elapsed(),
+            });
+            Ok(message)
+        })
+        .await
+    }
+
+    /// History is pruned once, here, on the canonical `Message` list
+    /// before it's translated to the OpenAI wire format, so
+    /// `get_history()` always matches what the model saw.
+    #[tracing::instrument(skip(self, user_message), fields(provider = "openai", model = %self.config.model, history_len))]
+    async fn chat_loop(&mut self, user_message: Message) -> Result<Message, ProviderError> {
+        self.history.push(user_message);
+        self.history = prune_history(&self.history, MAX_HISTORY_MESSAGES);
+        tracing::Span::current().record("history_len", self.history.len());
+
+        crate::tokens::check_context_window(&self.config.model, &self.history, self.config.max_tokens)?;
+        km_core::provider::log_request_summary(&self.config, "openai", self.history.len(), 0);
+        let history = self.history.clone();
+        crate::retry::retry_with_backoff(crate::retry::RetryPolicy::default(), self, crate::retry::hrtb_attempt(move |s: &mut OpenAiProvider| {
+            let history = history.clone();
+            Box::pin(async move { s.chat(&history).await })
+        })).await
+    }
+
+    fn state(&self) -> &ProviderState {
+        &self.state
+    }
+
+    fn config(&self) -> &ProviderConfig {
+        &self.config
+    }
+
+    fn get_history(&self) -> &[Message] {
+        &self.history
+    }
+
+    fn set_history(&mut self, history: Vec<Message>) {
+        self.history = history;
+    }
+
+    /// OpenAI has no public token-counting endpoint, so this uses the
+    /// same character-based heuristic as `check_context_window` rather
+    /// than a real tiktoken encoder — good enough to budget context, not
+    /// exact enough to predict billed usage down to the token.
+    async fn count_tokens(&self, messages: &[Message]) -> Result<u32, ProviderError> {
+        Ok(messages.iter().map(crate::tokens::estimate_message_tokens).sum())
+    }
+
+    fn update_config(&mut self, f: Box<dyn FnOnce(&mut ProviderConfig) + Send>) {
+        f(&mut self.config);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use km_core::provider::ToolCall as CoreToolCall;
+
+    fn provider() -> OpenAiProvider {
+        OpenAiProvider::new(ProviderConfig::new("test-key", "gpt-4o"))
+    }
+
+    #[test]
+    fn test_openai_message_json_maps_a_tool_result_message() {
+        let message = Message {
+            role: Role::Tool,
+            tool_call_id: Some("call_1".to_string()),
+            ..Message::text(Role::Tool, "42")
+        };
+        let json = openai_message_json(&message);
+        assert_eq!(json["role"], "tool");
+        assert_eq!(json["tool_call_id"], "call_1");
+        assert_eq!(json["content"], "42");
+    }
+
+    #[test]
+    fn test_openai_message_json_includes_tool_calls_on_an_assistant_message() {
+        let message = Message {
+            role: Role::Assistant,
+            tool_calls: vec![CoreToolCall { id: "call_1".to_string(), name: "get_weather".to_string(), arguments: "{}".to_string() }],
+            ..Message::text(Role::Assistant, "")
+        };
+        let json = openai_message_json(&message);
+        assert_eq!(json["role"], "assistant");
+        assert_eq!(json["tool_calls"][0]["function"]["name"], "get_weather");
+    }
+
+    #[test]
+    fn test_openai_message_json_maps_plain_roles() {
+        assert_eq!(openai_message_json(&Message::text(Role::User, "hi"))["role"], "user");
+        assert_eq!(openai_message_json(&Message::text(Role::System, "be nice"))["role"], "system");
+        assert_eq!(openai_message_json(&Message::text(Role::Assistant, "ok"))["role"], "assistant");
+    }
+
+    #[test]
+    fn test_build_chat_completions_body_includes_model_and_messages() {
+        let provider = provider();
+        let messages = vec![Message::text(Role::User, "hello")];
+        let body = provider.build_chat_completions_body(&messages);
+        assert_eq!(body["model"], "gpt-4o");
+        assert_eq!(body["messages"][0]["role"], "user");
+    }
+
+    #[test]
+    fn test_response_format_json_is_none_for_text() {
+        let provider = provider();
+        assert!(provider.response_format_json().is_none());
+    }
+
+    #[test]
+    fn test_response_format_json_maps_json_object() {
+        let mut config = ProviderConfig::new("test-key", "gpt-4o");
+        config.response_format = km_core::provider::ResponseFormat::Json;
+        let provider = OpenAiProvider::new(config);
+        let format = provider.response_format_json().expect("json format should be set");
+        assert_eq!(format["type"], "json_object");
+    }
+
+    #[test]
+    fn test_parse_chat_completion_extracts_content_and_usage() {
+        let mut provider = provider();
+        let body = serde_json::json!({
+            "choices": [{ "message": { "role": "assistant", "content": "hi there" } }],
+            "usage": { "prompt_tokens": 10, "completion_tokens": 5 },
+        });
+        let message = provider.parse_chat_completion(&body).expect("should parse");
+        assert_eq!(message.text_content(), "hi there");
+        assert_eq!(provider.state().total_input_tokens, 10);
+        assert_eq!(provider.state().total_output_tokens, 5);
+    }
+
+    #[test]
+    fn test_parse_chat_completion_extracts_tool_calls() {
+        let mut provider = provider();
+        let body = serde_json::json!({
+            "choices": [{ "message": { "role": "assistant", "content": null, "tool_calls": [
+                { "id": "call_1", "function": { "name": "get_weather", "arguments": "{\"city\":\"nyc\"}" } }
+            ] } }],
+        });
+        let message = provider.parse_chat_completion(&body).expect("should parse");
+        assert_eq!(message.tool_calls.len(), 1);
+        assert_eq!(message.tool_calls[0].name, "get_weather");
+    }
+
+    #[test]
+    fn test_parse_chat_completion_errors_when_choices_are_missing() {
+        let mut provider = provider();
+        let body = serde_json::json!({});
+        let result = provider.parse_chat_completion(&body);
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_classify_error_maps_context_length_exceeded() {
+        let body = serde_json::json!({ "error": { "code": "context_length_exceeded", "message": "too long" } });
+        let error = OpenAiProvider::classify_error(reqwest::StatusCode::BAD_REQUEST, &body, None);
+        assert!(matches!(error, ProviderError::ContextLengthExceeded { .. }));
+    }
+
+    #[test]
+    fn test_classify_error_maps_rate_limit_exceeded() {
+        let body = serde_json::json!({ "error": { "code": "rate_limit_exceeded", "message": "slow down" } });
+        let error = OpenAiProvider::classify_error(reqwest::StatusCode::TOO_MANY_REQUESTS, &body, Some(std::time::Duration::from_secs(1)));
+        assert!(matches!(error, ProviderError::RateLimitExceeded { .. }));
+    }
+
+    #[test]
+    fn test_classify_error_falls_back_to_http_status_for_unrecognized_codes() {
+        let body = serde_json::json!({ "error": { "message": "server exploded" } });
+        let error = OpenAiProvider::classify_error(reqwest::StatusCode::INTERNAL_SERVER_ERROR, &body, None);
+        assert!(matches!(error, ProviderError::Overloaded { .. }));
+    }
+}
@@ -0,0 +1,57 @@
+//! Typed, validated provider-specific options, replacing stringly lookups
+//! into `ProviderConfig::extra_options` (e.g. `extra_options["thinking_level"]`)
+//! with builder methods that reject bad values at set time instead of
+//! silently ignoring them at request time.
+
+#[derive(Debug, thiserror::Error)]
+pub enum OptionsError {
+    #[error("invalid thinking_level {0:?}: expected \"low\", \"medium\", or \"high\"")]
+    InvalidThinkingLevel(String),
+    #[error("invalid thinking_budget {0}: must be positive")]
+    InvalidThinkingBudget(u32),
+}
+
+/// Gemini-specific request options.
+#[derive(Debug, Default, Clone)]
+pub struct GeminiOptions {
+    pub thinking_level: Option<ThinkingLevel>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ThinkingLevel {
+    Low,
+    Medium,
+    High,
+}
+
+impl GeminiOptions {
+    pub fn with_thinking_level(mut self, level: &str) -> Result<Self, OptionsError> {
+        let level = match level {
+            "low" => ThinkingLevel::Low,
+            "medium" => ThinkingLevel::Medium,
+            "high" => ThinkingLevel::High,
+            other => return Err(OptionsError::InvalidThinkingLevel(other.to_string())),
+        };
+        self.thinking_level = Some(level);
+        Ok(self)
+    }
+}
+
+/// Anthropic-specific request options.
+#[derive(Debug, Default, Clone)]
+pub struct AnthropicOptions {
+    pub thinking_budget: Option<u32>,
+}
+
+impl AnthropicOptions {
+    /// Sets the extended-thinking token budget. Must be positive; Claude
+    /// rejects a zero budget, so we reject it here instead of round-
+    /// tripping to the API to find out.
+    pub fn with_thinking_budget(mut self, tokens: u32) -> Result<Self, OptionsError> {
+        if tokens == 0 {
+            return Err(OptionsError::InvalidThinkingBudget(tokens));
+        }
+        self.thinking_budget = Some(tokens);
+        Ok(self)
+    }
+}
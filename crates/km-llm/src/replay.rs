@@ -0,0 +1,93 @@
+//! An [`LLMProvider`] that plays back a recorded [`crate::transcript`]
+//! instead of calling a real backend, so integration tests of the
+//! helper/registry/loop-detector stack run deterministically and
+//! offline: same transcript in, same turns out, every time, no network
+//! or API key required.
+//!
+//! Native-only for the same reason `crate::transcript` is — loading a
+//! transcript means reading a file.
+
+use std::collections::VecDeque;
+
+use async_trait::async_trait;
+use km_core::provider::{LLMProvider, Message, ProviderConfig, ProviderError, ProviderState};
+
+use crate::transcript::{read_assistant_messages, TranscriptError};
+
+/// Replays a fixed sequence of assistant turns, one per `chat`/`chat_loop`
+/// call, regardless of what's actually sent in. Once the recorded turns
+/// are exhausted, further calls return [`ProviderError::ApiError`] — a
+/// test driving this past the end of its fixture is a bug in the test,
+/// not something to paper over with an empty message.
+pub struct ReplayProvider {
+    config: ProviderConfig,
+    state: ProviderState,
+    history: Vec<Message>,
+    remaining_turns: VecDeque<Message>,
+}
+
+impl ReplayProvider {
+    /// Loads every recorded assistant turn from `path` (as written by
+    /// [`crate::transcript::TranscriptRecorder`]) and replays them in
+    /// order. `config` is whatever the test wants `config()` to report —
+    /// it plays no part in which turn comes back next.
+    pub fn from_transcript(path: impl AsRef<std::path::Path>, config: ProviderConfig) -> Result<Self, TranscriptError> {
+        let turns = read_assistant_messages(path)?;
+        Ok(Self { config, state: ProviderState::default(), history: Vec::new(), remaining_turns: turns.into() })
+    }
+
+    /// Replays an in-memory sequence directly, for tests that build
+    /// fixture turns by hand instead of recording a real transcript.
+    pub fn from_turns(turns: Vec<Message>, config: ProviderConfig) -> Self {
+        Self { config, state: ProviderState::default(), history: Vec::new(), remaining_turns: turns.into() }
+    }
+
+    fn next_turn(&mut self) -> Result<Message, ProviderError> {
+        self.remaining_turns.pop_front().ok_or_else(|| ProviderError::ApiError {
+            message: "replay transcript exhausted: no more recorded turns".to_string(),
+            details: Default::default(),
+        })
+    }
+}
+
+#[async_trait]
+impl LLMProvider for ReplayProvider {
+    async fn chat(&mut self, _messages: &[Message]) -> Result<Message, ProviderError> {
+        self.next_turn()
+    }
+
+    async fn chat_loop(&mut self, user_message: Message) -> Result<Message, ProviderError> {
+        self.history.push(user_message);
+        let response = self.next_turn()?;
+        self.history.push(response.clone());
+        Ok(response)
+    }
+
+    fn state(&self) -> &ProviderState {
+        &self.state
+    }
+
+    fn config(&self) -> &ProviderConfig {
+        &self.config
+    }
+
+    fn get_history(&self) -> &[Message] {
+        &self.history
+    }
+
+    fn set_history(&mut self, history: Vec<Message>) {
+        self.history = history;
+    }
+
+    /// A rough word-count estimate, same as a backend with no real
+    /// counting endpoint would fall back to — replayed turns don't hit a
+    /// real tokenizer either way, so there's nothing more accurate to do
+    /// here.
+    async fn count_tokens(&self, messages: &[Message]) -> Result<u32, ProviderError> {
+        Ok(messages.iter().map(|m| (m.text_content().len() / 4) as u32).sum())
+    }
+
+    fn update_config(&mut self, f: Box<dyn FnOnce(&mut ProviderConfig) + Send>) {
+        f(&mut self.config);
+    }
+}
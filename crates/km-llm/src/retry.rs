@@ -0,0 +1,225 @@
+//! Shared retry-with-backoff layer used by every provider's `chat`/
+//! `chat_loop`. Without this, a transient 429 or 5xx surfaces straight to
+//! the caller as a hard failure instead of the momentary blip it usually
+//! is.
+
+use std::time::Duration;
+
+use km_core::provider::ProviderError;
+
+/// How a retry loop backs off between attempts: `max_attempts` total
+/// tries, doubling `base_delay` each time (capped at `max_delay`), plus
+/// up to `jitter` of random extra delay so a fleet of retrying clients
+/// doesn't all hammer the server on the same tick.
+#[derive(Debug, Clone, Copy)]
+pub struct RetryPolicy {
+    pub max_attempts: u32,
+    pub base_delay: Duration,
+    pub max_delay: Duration,
+    pub jitter: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 3,
+            base_delay: Duration::from_millis(500),
+            max_delay: Duration::from_secs(20),
+            jitter: Duration::from_millis(250),
+        }
+    }
+}
+
+/// Whether `error` is worth retrying. Rate limits and overload are
+/// transient by definition; a 5xx caught as [`ProviderError::ApiError`]
+/// is retried too since providers don't always map those to a dedicated
+/// variant. Everything else (auth failures, context length, 4xx client
+/// errors) is left alone since a retry can't fix them.
+fn is_retryable(error: &ProviderError) -> bool {
+    match error {
+        ProviderError::RateLimitExceeded { .. } | ProviderError::Overloaded { .. } | ProviderError::Timeout { .. } => {
+            true
+        }
+        ProviderError::ApiError { details, .. } => {
+            matches!(details.http_status, Some(status) if status >= 500)
+        }
+        ProviderError::AuthenticationFailed { .. }
+        | ProviderError::ContextLengthExceeded { .. }
+        | ProviderError::BudgetExceeded { .. }
+        // Repeating the same call again is exactly the behavior the loop
+        // detector just flagged; retrying it would defeat the point.
+        | ProviderError::LoopDetected { .. } => false,
+    }
+}
+
+/// The delay to wait before the next attempt: the provider's own
+/// `Retry-After` when it gave one (rate limits should be honored as
+/// stated, not second-guessed), otherwise the policy's exponential
+/// backoff with jitter.
+fn delay_for(policy: &RetryPolicy, attempt: u32, error: &ProviderError) -> Duration {
+    if let ProviderError::RateLimitExceeded { retry_after: Some(retry_after), .. } = error {
+        return *retry_after;
+    }
+    let backoff = policy.base_delay * 2u32.pow(attempt.saturating_sub(1));
+    let backoff = backoff.min(policy.max_delay);
+    // `attempt` already varies the jitter seed across calls within one
+    // retry loop without pulling in a RNG crate or `Math.random()`
+    // (unavailable under wasm's deterministic-build constraints anyway).
+    let jitter = policy.jitter.mul_f64(f64::from(attempt % 4) / 4.0);
+    backoff + jitter
+}
+
+/// Coerces a closure literal into the higher-ranked `for<'a> FnMut(&'a mut
+/// A) -> ...` bound `retry_with_backoff` needs. Plain closure literals like
+/// `|s: &mut Provider| Box::pin(s.chat(&history))` get their parameter's
+/// lifetime inferred from one call site rather than generalized, so passing
+/// them straight to `retry_with_backoff` fails to typecheck even though the
+/// closure itself is fine; running them through this identity function
+/// first forces the higher-ranked signature before inference narrows it.
+pub fn hrtb_attempt<A, T, F>(f: F) -> F
+where
+    F: for<'a> FnMut(&'a mut A) -> std::pin::Pin<Box<dyn std::future::Future<Output = Result<T, ProviderError>> + Send + 'a>>,
+{
+    f
+}
+
+/// Calls `attempt` up to `policy.max_attempts` times, retrying on
+/// transient errors (see [`is_retryable`]) with backoff between tries.
+/// Returns the last error once attempts are exhausted.
+///
+/// `attempt` takes `target` as a parameter rather than capturing it:
+/// callers build it from a `&mut self` method (e.g.
+/// `|s| Box::pin(s.chat(&history))`), and a closure that instead
+/// *captures* `self` can't return a future borrowing it — `FnMut::call_mut`
+/// only hands out that borrow for the duration of one call, so the
+/// returned future can't outlive it. Taking `target` as an explicit,
+/// higher-ranked-lifetime argument sidesteps that: each call gets its own
+/// fresh reborrow, scoped to that call alone.
+pub async fn retry_with_backoff<A, F, T>(policy: RetryPolicy, target: &mut A, mut attempt: F) -> Result<T, ProviderError>
+where
+    F: for<'a> FnMut(&'a mut A) -> std::pin::Pin<Box<dyn std::future::Future<Output = Result<T, ProviderError>> + Send + 'a>>,
+{
+    let mut last_error = None;
+    for attempt_number in 1..=policy.max_attempts {
+        match attempt(target).await {
+            Ok(value) => return Ok(value),
+            Err(error) => {
+                if attempt_number == policy.max_attempts || !is_retryable(&error) {
+                    return Err(error);
+                }
+                crate::time::sleep(delay_for(&policy, attempt_number, &error)).await;
+                last_error = Some(error);
+            }
+        }
+    }
+    // Unreachable in practice (the loop always returns on its last
+    // iteration), but keeps the function total without `unwrap`.
+    Err(last_error.unwrap_or(ProviderError::ApiError {
+        message: "retry loop exited without an attempt".to_string(),
+        details: Default::default(),
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rate_limited(retry_after: Option<Duration>) -> ProviderError {
+        ProviderError::RateLimitExceeded { retry_after, details: Default::default() }
+    }
+
+    #[test]
+    fn test_is_retryable_treats_rate_limit_overload_and_timeout_as_transient() {
+        assert!(is_retryable(&rate_limited(None)));
+        assert!(is_retryable(&ProviderError::Overloaded { details: Default::default() }));
+        assert!(is_retryable(&ProviderError::Timeout { details: Default::default() }));
+    }
+
+    #[test]
+    fn test_is_retryable_treats_api_error_as_transient_only_for_5xx() {
+        let server_error = ProviderError::ApiError {
+            message: "boom".to_string(),
+            details: km_core::provider::ErrorDetails { http_status: Some(503), ..Default::default() },
+        };
+        let client_error = ProviderError::ApiError {
+            message: "bad request".to_string(),
+            details: km_core::provider::ErrorDetails { http_status: Some(400), ..Default::default() },
+        };
+        assert!(is_retryable(&server_error));
+        assert!(!is_retryable(&client_error));
+    }
+
+    #[test]
+    fn test_is_retryable_never_retries_auth_context_budget_or_loop_errors() {
+        assert!(!is_retryable(&ProviderError::AuthenticationFailed { details: Default::default() }));
+        assert!(!is_retryable(&ProviderError::ContextLengthExceeded { details: Default::default() }));
+        assert!(!is_retryable(&ProviderError::BudgetExceeded { details: Default::default() }));
+        assert!(!is_retryable(&ProviderError::LoopDetected { message: "repeat".to_string(), details: Default::default() }));
+    }
+
+    #[test]
+    fn test_delay_for_honors_provider_supplied_retry_after() {
+        let policy = RetryPolicy::default();
+        let error = rate_limited(Some(Duration::from_secs(7)));
+        assert_eq!(delay_for(&policy, 1, &error), Duration::from_secs(7));
+    }
+
+    #[test]
+    fn test_delay_for_caps_exponential_backoff_at_max_delay() {
+        let policy = RetryPolicy { max_delay: Duration::from_secs(1), ..RetryPolicy::default() };
+        let error = ProviderError::Overloaded { details: Default::default() };
+        // A large attempt number would overflow the naive exponential
+        // backoff without the cap; it should clamp to max_delay instead.
+        let delay = delay_for(&policy, 20, &error);
+        assert!(delay >= policy.max_delay && delay <= policy.max_delay + policy.jitter);
+    }
+
+    #[tokio::test]
+    async fn test_retry_with_backoff_stops_retrying_non_retryable_errors() {
+        let mut target = 0u32;
+        let policy = RetryPolicy { max_attempts: 5, base_delay: Duration::from_millis(1), max_delay: Duration::from_millis(1), jitter: Duration::ZERO };
+        let mut attempts = 0u32;
+        let result: Result<(), ProviderError> = retry_with_backoff(policy, &mut target, hrtb_attempt(|_: &mut u32| {
+            attempts += 1;
+            Box::pin(async { Err(ProviderError::AuthenticationFailed { details: Default::default() }) })
+        }))
+        .await;
+        assert!(result.is_err());
+        assert_eq!(attempts, 1);
+    }
+
+    #[tokio::test]
+    async fn test_retry_with_backoff_retries_transient_errors_up_to_max_attempts() {
+        let mut target = 0u32;
+        let policy = RetryPolicy { max_attempts: 3, base_delay: Duration::from_millis(1), max_delay: Duration::from_millis(1), jitter: Duration::ZERO };
+        let mut attempts = 0u32;
+        let result: Result<(), ProviderError> = retry_with_backoff(policy, &mut target, hrtb_attempt(|_: &mut u32| {
+            attempts += 1;
+            Box::pin(async { Err(ProviderError::Overloaded { details: Default::default() }) })
+        }))
+        .await;
+        assert!(result.is_err());
+        assert_eq!(attempts, 3);
+    }
+
+    #[tokio::test]
+    async fn test_retry_with_backoff_returns_ok_once_attempt_succeeds() {
+        let mut target = 0u32;
+        let policy = RetryPolicy { max_attempts: 3, base_delay: Duration::from_millis(1), max_delay: Duration::from_millis(1), jitter: Duration::ZERO };
+        let mut attempts = 0u32;
+        let result = retry_with_backoff(policy, &mut target, hrtb_attempt(|_: &mut u32| {
+            attempts += 1;
+            let this_attempt = attempts;
+            Box::pin(async move {
+                if this_attempt < 2 {
+                    Err(ProviderError::Overloaded { details: Default::default() })
+                } else {
+                    Ok(42)
+                }
+            })
+        }))
+        .await;
+        assert_eq!(result.expect("second attempt should have succeeded"), 42);
+        assert_eq!(attempts, 2);
+    }
+}
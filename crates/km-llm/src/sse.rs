@@ -0,0 +1,108 @@
+//! Reconnecting on a dropped SSE stream instead of failing the whole
+//! request when a transient disconnect happens mid-stream.
+
+use std::time::Duration;
+
+use crate::time::sleep;
+
+/// How a reconnect attempt is retried: up to `max_attempts`, waiting
+/// `base_delay * 2^attempt` between tries (capped implicitly by
+/// `max_attempts`).
+#[derive(Debug, Clone, Copy)]
+pub struct ReconnectPolicy {
+    pub max_attempts: u32,
+    pub base_delay: Duration,
+}
+
+impl Default for ReconnectPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 3,
+            base_delay: Duration::from_millis(500),
+        }
+    }
+}
+
+/// Tracks how much of a streamed response has been consumed so a
+/// reconnect can resume rather than restart: `last_event_id` (when the
+/// server sends one) or, failing that, the length of text already
+/// accumulated, which callers use to skip/dedupe the prefix a fresh
+/// connection would otherwise replay.
+#[derive(Debug, Clone, Default)]
+pub struct StreamCheckpoint {
+    pub last_event_id: Option<String>,
+    pub text_consumed: usize,
+}
+
+/// Retries `connect` (which opens a fresh SSE connection and streams until
+/// it errors or completes, returning the final checkpoint) up to
+/// `policy.max_attempts` times on failure, with exponential backoff.
+/// `connect` receives the checkpoint from the previous attempt so it can
+/// send `Last-Event-ID` or otherwise resume instead of starting over.
+pub async fn connect_with_resume<F, Fut, T, E>(policy: ReconnectPolicy, mut connect: F) -> Result<T, E>
+where
+    F: FnMut(StreamCheckpoint) -> Fut,
+    Fut: std::future::Future<Output = Result<T, (E, StreamCheckpoint)>>,
+{
+    let mut checkpoint = StreamCheckpoint::default();
+    let mut attempt = 0;
+    loop {
+        match connect(checkpoint.clone()).await {
+            Ok(result) => return Ok(result),
+            Err((err, new_checkpoint)) => {
+                attempt += 1;
+                if attempt >= policy.max_attempts {
+                    return Err(err);
+                }
+                checkpoint = new_checkpoint;
+                sleep(policy.base_delay * 2u32.pow(attempt - 1)).await;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fast_policy(max_attempts: u32) -> ReconnectPolicy {
+        ReconnectPolicy { max_attempts, base_delay: Duration::from_millis(1) }
+    }
+
+    #[tokio::test]
+    async fn test_connect_with_resume_returns_ok_without_retrying_on_first_success() {
+        let mut attempts = 0;
+        let result: Result<&str, ()> = connect_with_resume(fast_policy(3), |_checkpoint| {
+            attempts += 1;
+            async { Ok("done") }
+        })
+        .await;
+        assert_eq!(result, Ok("done"));
+        assert_eq!(attempts, 1);
+    }
+
+    #[tokio::test]
+    async fn test_connect_with_resume_retries_up_to_max_attempts_then_gives_up() {
+        let mut attempts = 0;
+        let result: Result<(), &str> = connect_with_resume(fast_policy(3), |checkpoint| {
+            attempts += 1;
+            async move { Err(("connection reset", checkpoint)) }
+        })
+        .await;
+        assert_eq!(result, Err("connection reset"));
+        assert_eq!(attempts, 3);
+    }
+
+    #[tokio::test]
+    async fn test_connect_with_resume_passes_the_previous_checkpoint_to_the_next_attempt() {
+        let mut seen_checkpoints = Vec::new();
+        let result: Result<(), &str> = connect_with_resume(fast_policy(3), |checkpoint| {
+            seen_checkpoints.push(checkpoint.text_consumed);
+            let next = StreamCheckpoint { last_event_id: None, text_consumed: checkpoint.text_consumed + 10 };
+            async move { Err(("disconnected", next)) }
+        })
+        .await;
+        assert_eq!(result, Err("disconnected"));
+        assert_eq!(seen_checkpoints, vec![0, 10, 20]);
+    }
+}
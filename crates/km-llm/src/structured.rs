@@ -0,0 +1,28 @@
+//! A typed-deserialize helper layered on top of
+//! [`ProviderConfig::response_format`], for callers that want `T` back
+//! instead of hand-parsing the final message's text.
+//!
+//! This is a free function rather than a trait method: `LLMProvider` has
+//! to stay object-safe for [`crate::boxed::BoxProvider`]/`AnyProvider`,
+//! and a generic method can't be called through `dyn LLMProvider`.
+
+use serde::de::DeserializeOwned;
+
+use km_core::provider::{LLMProvider, Message, ProviderError};
+
+/// Runs `provider.chat_loop(user_message)` and deserializes the response
+/// text as `T`. Callers are expected to have already set a matching
+/// [`km_core::provider::ResponseFormat`] on the provider's config so the
+/// model actually returns JSON; this only validates and parses what comes
+/// back; it doesn't request the format itself.
+pub async fn chat_structured<P, T>(provider: &mut P, user_message: Message) -> Result<T, ProviderError>
+where
+    P: LLMProvider + ?Sized,
+    T: DeserializeOwned,
+{
+    let response = provider.chat_loop(user_message).await?;
+    serde_json::from_str(&response.text_content()).map_err(|err| ProviderError::ApiError {
+        message: format!("structured response did not match the expected shape: {err}"),
+        details: Default::default(),
+    })
+}
@@ -0,0 +1,55 @@
+//! Cross-target time primitives: `tokio::time` isn't available on
+//! `wasm32-unknown-unknown` (no timer driver there), so `sleep` and
+//! `timeout` pick a wasm-compatible implementation at compile time
+//! instead of every caller special-casing the target itself.
+
+use std::time::Duration;
+
+/// Sleeps for `duration`. Used by [`crate::retry`]'s backoff delay and
+/// [`crate::sse`]'s reconnect backoff.
+#[cfg(not(target_arch = "wasm32"))]
+pub(crate) async fn sleep(duration: Duration) {
+    tokio::time::sleep(duration).await;
+}
+
+#[cfg(target_arch = "wasm32")]
+pub(crate) async fn sleep(duration: Duration) {
+    gloo_timers::future::sleep(duration).await;
+}
+
+/// Runs `fut` to completion, or returns `Err(())` if `duration` elapses
+/// first. Used for per-request timeouts and SSE idle/stall detection, so
+/// a server that stops responding mid-stream doesn't hang the caller
+/// forever.
+#[cfg(not(target_arch = "wasm32"))]
+pub(crate) async fn timeout<F: std::future::Future>(duration: Duration, fut: F) -> Result<F::Output, ()> {
+    tokio::time::timeout(duration, fut).await.map_err(|_| ())
+}
+
+#[cfg(target_arch = "wasm32")]
+pub(crate) async fn timeout<F: std::future::Future>(duration: Duration, fut: F) -> Result<F::Output, ()> {
+    use futures::future::{select, Either};
+    futures::pin_mut!(fut);
+    let sleep_fut = gloo_timers::future::sleep(duration);
+    futures::pin_mut!(sleep_fut);
+    match select(fut, sleep_fut).await {
+        Either::Left((value, _)) => Ok(value),
+        Either::Right(_) => Err(()),
+    }
+}
+
+/// Runs `fut`, mapping a timeout to [`km_core::provider::ProviderError::Timeout`]
+/// so every backend's `chat` reports a stall the same way instead of each
+/// hand-rolling the conversion.
+pub(crate) async fn with_request_timeout<F, T>(
+    duration: Duration,
+    fut: F,
+) -> Result<T, km_core::provider::ProviderError>
+where
+    F: std::future::Future<Output = Result<T, km_core::provider::ProviderError>>,
+{
+    match timeout(duration, fut).await {
+        Ok(result) => result,
+        Err(()) => Err(km_core::provider::ProviderError::Timeout { details: Default::default() }),
+    }
+}
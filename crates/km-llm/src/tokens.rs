@@ -0,0 +1,36 @@
+//! Rough token estimation, used for pre-flight context window checks
+//! before a provider-specific tokenizer is wired in.
+
+use crate::models::preset_for;
+use km_core::provider::{Message, ProviderError};
+
+/// A rough, provider-agnostic token estimate: ~4 characters per token,
+/// the same heuristic OpenAI documents for English text. Good enough to
+/// catch "this history is obviously too big" before a request round-trips
+/// just to fail; not a substitute for a real tokenizer.
+pub fn estimate_tokens(text: &str) -> u32 {
+    (text.chars().count() as u32 / 4).max(1)
+}
+
+pub(crate) fn estimate_message_tokens(message: &Message) -> u32 {
+    let mut tokens = estimate_tokens(&message.text_content());
+    for call in &message.tool_calls {
+        tokens += estimate_tokens(&call.arguments);
+    }
+    tokens
+}
+
+/// Checks whether `history` plus `max_tokens` of response headroom fits
+/// within `model`'s context window, before a request is sent. Returns
+/// [`ProviderError::ContextLengthExceeded`] rather than letting the
+/// provider reject the request after paying for the round trip.
+pub fn check_context_window(model: &str, history: &[Message], max_tokens: u32) -> Result<(), ProviderError> {
+    let history_tokens: u32 = history.iter().map(estimate_message_tokens).sum();
+    let context_window = preset_for(model).context_window;
+    if history_tokens + max_tokens > context_window {
+        return Err(ProviderError::ContextLengthExceeded {
+            details: Default::default(),
+        });
+    }
+    Ok(())
+}
@@ -0,0 +1,172 @@
+//! Records a chat loop's requests, streamed text, tool calls, and tool
+//! results to a JSONL file — one event per line — for debugging a
+//! runaway agent after the fact or auditing what it actually did. Off
+//! by default; wire a [`TranscriptRecorder`] into
+//! [`crate::agent_loop::ChatLoopConfig::with_transcript`] to turn it on.
+//!
+//! Tool arguments and results carry whatever the model or a tool put
+//! there, including copy-pasted credentials, so every event's text is
+//! run through [`redact_secrets`] before it's written.
+//!
+//! Native-only: writing to a file isn't available on wasm32. Wasm hosts
+//! that want the same visibility should subscribe to `LoopEvent`s
+//! instead (see `crate::events`) and persist them however their host
+//! environment allows.
+
+use std::fs::{File, OpenOptions};
+use std::io::{BufRead, BufReader, Write};
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use km_core::provider::Message;
+
+#[derive(Debug, thiserror::Error)]
+pub enum TranscriptError {
+    #[error("failed to open transcript file {path}: {source}")]
+    Open { path: String, source: std::io::Error },
+    #[error("failed to write transcript entry: {0}")]
+    Write(std::io::Error),
+    #[error("failed to read transcript file {path}: {source}")]
+    Read { path: String, source: std::io::Error },
+    #[error("invalid transcript line in {path}: {source}")]
+    Parse { path: String, source: serde_json::Error },
+}
+
+/// One line of the transcript, tagged by `kind` when serialized.
+///
+/// `AssistantMessage` is the odd one out: it's not passed through
+/// [`redact_secrets`], since [`crate::replay::ReplayProvider`] needs the
+/// turn back byte-for-byte to replay it. A transcript recorded from a
+/// real conversation should be treated as sensitive for that reason,
+/// even though the other event kinds are redacted.
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum TranscriptEvent {
+    Request { model: String, message_count: usize, tool_count: usize },
+    TextDelta { text: String },
+    ToolCall { id: String, name: String, arguments: String },
+    ToolResult { id: String, is_error: bool, content: String },
+    AssistantMessage { message: Message },
+}
+
+/// Appends JSONL-encoded [`TranscriptEvent`]s to a file, each line
+/// timestamped and redacted first. Cloneable so one recorder can be
+/// shared between a [`crate::agent_loop::ChatLoopConfig`] and whatever
+/// else wants to log to the same file.
+#[derive(Clone)]
+pub struct TranscriptRecorder {
+    file: Arc<Mutex<File>>,
+}
+
+impl TranscriptRecorder {
+    /// Opens `path` for appending, creating it if it doesn't exist yet —
+    /// a recorder reused across several runs builds up one continuous
+    /// log rather than overwriting the last run's.
+    pub fn create(path: impl AsRef<Path>) -> Result<Self, TranscriptError> {
+        let path = path.as_ref();
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .map_err(|source| TranscriptError::Open { path: path.display().to_string(), source })?;
+        Ok(Self { file: Arc::new(Mutex::new(file)) })
+    }
+
+    pub fn record(&self, event: TranscriptEvent) -> Result<(), TranscriptError> {
+        let event = redact_event(event);
+        let timestamp_ms = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_millis();
+        let line = serde_json::json!({ "timestamp_ms": timestamp_ms, "event": event });
+        let mut file = self.file.lock().expect("transcript file lock poisoned");
+        writeln!(file, "{line}").map_err(TranscriptError::Write)
+    }
+}
+
+/// Reads every [`TranscriptEvent::AssistantMessage`] out of a transcript
+/// file, in recorded order, for [`crate::replay::ReplayProvider`] to
+/// play back one at a time. Other event kinds in the same file are
+/// ignored — they're there for a human reading the transcript, not for
+/// replay.
+pub fn read_assistant_messages(path: impl AsRef<Path>) -> Result<Vec<Message>, TranscriptError> {
+    let path = path.as_ref();
+    let file = File::open(path).map_err(|source| TranscriptError::Read { path: path.display().to_string(), source })?;
+    let mut messages = Vec::new();
+    for line in BufReader::new(file).lines() {
+        let line = line.map_err(|source| TranscriptError::Read { path: path.display().to_string(), source })?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let parsed: serde_json::Value =
+            serde_json::from_str(&line).map_err(|source| TranscriptError::Parse { path: path.display().to_string(), source })?;
+        let Some(event) = parsed.get("event") else { continue };
+        if let Ok(TranscriptEvent::AssistantMessage { message }) = serde_json::from_value(event.clone()) {
+            messages.push(message);
+        }
+    }
+    Ok(messages)
+}
+
+fn redact_event(event: TranscriptEvent) -> TranscriptEvent {
+    match event {
+        TranscriptEvent::Request { model, message_count, tool_count } => {
+            TranscriptEvent::Request { model, message_count, tool_count }
+        }
+        TranscriptEvent::TextDelta { text } => TranscriptEvent::TextDelta { text: redact_secrets(&text) },
+        TranscriptEvent::ToolCall { id, name, arguments } => {
+            TranscriptEvent::ToolCall { id, name, arguments: redact_secrets(&arguments) }
+        }
+        TranscriptEvent::ToolResult { id, is_error, content } => {
+            TranscriptEvent::ToolResult { id, is_error, content: redact_secrets(&content) }
+        }
+        message @ TranscriptEvent::AssistantMessage { .. } => message,
+    }
+}
+
+/// Known secret-token prefixes, checked case-sensitively against each
+/// whitespace-delimited word. Covers the common vendor formats likely to
+/// show up in a tool's arguments or output, not an exhaustive list.
+const SECRET_PREFIXES: &[&str] = &["sk-", "sk_", "ghp_", "gho_", "AKIA", "AIza", "xox"];
+
+/// Keys whose value, if it looks like a real secret rather than a
+/// placeholder, gets masked even without a recognized prefix — catches
+/// `password=...`, `api_key: "..."`, and similar key/value text that a
+/// tool call's JSON arguments or a shell command's output might contain.
+const SECRET_KEY_MARKERS: &[&str] = &["key", "token", "secret", "password", "passwd", "authorization"];
+
+/// Words whose *following* word is the actual secret, e.g. the
+/// `<token>` in an `Authorization: Bearer <token>` header — redacting
+/// "Bearer" itself would leave the token sitting right next to it.
+const SECRET_INTRODUCERS: &[&str] = &["bearer", "basic"];
+
+/// Replaces anything in `text` that looks like a credential with
+/// `[redacted]`. Heuristic, not a parser — it scans whitespace-delimited
+/// words rather than understanding JSON or shell syntax, trading a
+/// missed edge case or two for staying simple enough to audit by eye.
+pub fn redact_secrets(text: &str) -> String {
+    let mut previous_introduced_secret = false;
+    text.split_inclusive(char::is_whitespace)
+        .map(|word| {
+            let trimmed = word.trim_end();
+            let trailing = &word[trimmed.len()..];
+            let unquoted = trimmed.trim_matches(|c: char| c == '"' || c == '\'' || c == ',');
+            let redact = previous_introduced_secret || looks_like_secret(unquoted);
+            previous_introduced_secret = SECRET_INTRODUCERS.contains(&unquoted.to_lowercase().as_str());
+            if redact {
+                format!("[redacted]{trailing}")
+            } else {
+                word.to_string()
+            }
+        })
+        .collect()
+}
+
+fn looks_like_secret(word: &str) -> bool {
+    if SECRET_PREFIXES.iter().any(|prefix| word.starts_with(prefix)) {
+        return true;
+    }
+    let Some((key, value)) = word.split_once(['=', ':']) else {
+        return false;
+    };
+    let key = key.trim_matches(|c: char| c == '"' || c == '\'').to_lowercase();
+    SECRET_KEY_MARKERS.iter().any(|marker| key.contains(marker)) && value.len() >= 8
+}